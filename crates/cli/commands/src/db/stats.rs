@@ -202,18 +202,10 @@ impl Command {
                 let columns = jar_provider.columns();
                 let rows = jar_provider.rows();
 
-                let data_size = fs::metadata(jar_provider.data_path())
-                    .map(|metadata| metadata.len())
-                    .unwrap_or_default();
-                let index_size = fs::metadata(jar_provider.index_path())
-                    .map(|metadata| metadata.len())
-                    .unwrap_or_default();
-                let offsets_size = fs::metadata(jar_provider.offsets_path())
-                    .map(|metadata| metadata.len())
-                    .unwrap_or_default();
-                let config_size = fs::metadata(jar_provider.config_path())
-                    .map(|metadata| metadata.len())
-                    .unwrap_or_default();
+                let data_size = fs::file_size(jar_provider.data_path()).unwrap_or_default();
+                let index_size = fs::file_size(jar_provider.index_path()).unwrap_or_default();
+                let offsets_size = fs::file_size(jar_provider.offsets_path()).unwrap_or_default();
+                let config_size = fs::file_size(jar_provider.config_path()).unwrap_or_default();
 
                 if self.detailed_segments {
                     let mut row = Row::new();
@@ -44,8 +44,8 @@ pub fn validate_header_base_fee<H: BlockHeader, ChainSpec: EthereumHardforks>(
 pub fn validate_shanghai_withdrawals<B: Block>(
     block: &SealedBlock<B>,
 ) -> Result<(), ConsensusError> {
-    let withdrawals = block.body().withdrawals().ok_or(ConsensusError::BodyWithdrawalsMissing)?;
-    let withdrawals_root = alloy_consensus::proofs::calculate_withdrawals_root(withdrawals);
+    let withdrawals_root =
+        block.body().calculate_withdrawals_root().ok_or(ConsensusError::BodyWithdrawalsMissing)?;
     let header_withdrawals_root =
         block.withdrawals_root().ok_or(ConsensusError::WithdrawalsRootMissing)?;
     if withdrawals_root != *header_withdrawals_root {
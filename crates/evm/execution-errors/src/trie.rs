@@ -190,6 +190,40 @@ pub enum TrieWitnessError {
     /// Missing account.
     #[error("missing account {_0}")]
     MissingAccount(B256),
+    /// The storage root of a wiped storage trie doesn't match the root of its new slots.
+    #[error(
+        "storage root mismatch for account {hashed_address}: expected {expected}, got {actual}"
+    )]
+    RootMismatch {
+        /// The hashed address of the account.
+        hashed_address: B256,
+        /// The storage root of the new slots.
+        expected: B256,
+        /// The storage root computed from the storage trie.
+        actual: B256,
+    },
+    /// A proof node can't be decoded, e.g. because the database is corrupt.
+    #[error("invalid proof node at {path:?}: {source}")]
+    InvalidProofNode {
+        /// The path of the node.
+        path: Nibbles,
+        /// The decoding error.
+        source: alloy_rlp::Error,
+    },
+    /// A witness node is not keyed by its hash.
+    #[error("witness node {key} has hash {hash}")]
+    NodeHashMismatch {
+        /// The key of the node in the witness.
+        key: B256,
+        /// The hash of the node.
+        hash: B256,
+    },
+    /// The witness exceeds the maximum number of nodes.
+    #[error("witness exceeds the maximum of {_0} nodes")]
+    WitnessTooLarge(usize),
+    /// Error recording a node in a witness sink.
+    #[error("failed to record witness node: {_0}")]
+    Sink(Box<dyn core::error::Error + Send + Sync>),
 }
 
 impl From<SparseStateTrieErrorKind> for TrieWitnessError {
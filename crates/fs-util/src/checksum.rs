@@ -0,0 +1,153 @@
+//! Checksummed files for integrity-critical data.
+//!
+//! A checksummed file starts with a header of the `MAGIC` bytes, the header `VERSION` and the
+//! little-endian CRC32 of the payload, followed by the payload itself.
+
+use crate::{read, write_atomically, FsPathError, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+
+/// Magic bytes at the start of every checksummed file.
+const MAGIC: [u8; 4] = *b"RCHK";
+
+/// Current version of the header format.
+const VERSION: u8 = 1;
+
+/// Length of the header: magic bytes, version and CRC32.
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4;
+
+/// Writes `contents` atomically to the file at `path`, prefixed with a header containing the
+/// CRC32 of `contents`.
+///
+/// The file can be read back with [`read_with_checksum`].
+pub fn write_with_checksum(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<()> {
+    let contents = contents.as_ref();
+    let mut bytes = Vec::with_capacity(HEADER_LEN + contents.len());
+    bytes.extend_from_slice(&MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&crc32fast::hash(contents).to_le_bytes());
+    bytes.extend_from_slice(contents);
+    write_atomically(path, bytes)
+}
+
+/// Reads a file written by [`write_with_checksum`] and returns its payload after verifying the
+/// checksum.
+///
+/// Returns [`FsPathError::MissingChecksumHeader`] if the file doesn't start with a checksum
+/// header, [`FsPathError::UnsupportedChecksumVersion`] if the header version is unknown, and
+/// [`FsPathError::ChecksumMismatch`] if the payload is corrupted.
+pub fn read_with_checksum(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    let mut bytes = read(path)?;
+    if bytes.len() < HEADER_LEN || bytes[..MAGIC.len()] != MAGIC {
+        return Err(FsPathError::MissingChecksumHeader { path: path.into() })
+    }
+
+    let version = bytes[MAGIC.len()];
+    if version != VERSION {
+        return Err(FsPathError::UnsupportedChecksumVersion { path: path.into(), version })
+    }
+
+    let expected = u32::from_le_bytes(bytes[MAGIC.len() + 1..HEADER_LEN].try_into().unwrap());
+    let actual = crc32fast::hash(&bytes[HEADER_LEN..]);
+    if expected != actual {
+        return Err(FsPathError::ChecksumMismatch { path: path.into(), expected, actual })
+    }
+
+    bytes.drain(..HEADER_LEN);
+    Ok(bytes)
+}
+
+/// Writes the object as a pretty-printed JSON object with a checksum header, see
+/// [`write_with_checksum`].
+pub fn write_json_file_checked<T: Serialize>(path: &Path, obj: &T) -> Result<()> {
+    let bytes = serde_json::to_vec_pretty(obj)
+        .map_err(|source| FsPathError::WriteJson { source, path: path.into() })?;
+    write_with_checksum(path, bytes)
+}
+
+/// Reads a JSON file written by [`write_json_file_checked`] and deserializes it into the provided
+/// type after verifying the checksum, see [`read_with_checksum`].
+pub fn read_json_file_checked<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let bytes = read_with_checksum(path)?;
+    serde_json::from_slice(&bytes)
+        .map_err(|source| FsPathError::ReadJson { source, path: path.into() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn checksum_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data");
+
+        write_with_checksum(&path, b"hello").unwrap();
+        assert_eq!(read_with_checksum(&path).unwrap(), b"hello");
+        assert_eq!(fs::read(&path).unwrap().len(), HEADER_LEN + 5);
+
+        write_with_checksum(&path, b"").unwrap();
+        assert!(read_with_checksum(&path).unwrap().is_empty());
+
+        let value = vec![1u64, 2, 3];
+        write_json_file_checked(&path, &value).unwrap();
+        assert_eq!(read_json_file_checked::<Vec<u64>>(&path).unwrap(), value);
+    }
+
+    #[test]
+    fn checksum_detects_corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data");
+
+        write_with_checksum(&path, b"hello").unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[HEADER_LEN] ^= 1;
+        fs::write(&path, bytes).unwrap();
+
+        let err = read_with_checksum(&path).unwrap_err();
+        assert_eq!(
+            err,
+            FsPathError::ChecksumMismatch {
+                path: path.clone(),
+                expected: crc32fast::hash(b"hello"),
+                actual: crc32fast::hash(b"iello"),
+            }
+        );
+        assert!(matches!(
+            read_json_file_checked::<String>(&path),
+            Err(FsPathError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn checksum_rejects_invalid_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data");
+
+        // legacy file without a header
+        fs::write(&path, b"{\"legacy\":true}").unwrap();
+        assert_eq!(
+            read_with_checksum(&path).unwrap_err(),
+            FsPathError::MissingChecksumHeader { path: path.clone() }
+        );
+
+        // truncated header
+        fs::write(&path, MAGIC).unwrap();
+        assert_eq!(
+            read_with_checksum(&path).unwrap_err(),
+            FsPathError::MissingChecksumHeader { path: path.clone() }
+        );
+
+        // unknown version
+        write_with_checksum(&path, b"hello").unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[MAGIC.len()] = VERSION + 1;
+        fs::write(&path, bytes).unwrap();
+        assert_eq!(
+            read_with_checksum(&path).unwrap_err(),
+            FsPathError::UnsupportedChecksumVersion { path, version: VERSION + 1 }
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Disk space inspection.
+
+use crate::{read_dir, FsPathError, Result};
+use std::{fs, path::Path};
+
+/// Space usage of the filesystem containing a path, as returned by [`disk_usage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// Total size of the filesystem in bytes.
+    pub total: u64,
+    /// Bytes available to the current user.
+    pub available: u64,
+    /// Free bytes, including those reserved for privileged users.
+    pub free: u64,
+}
+
+/// Returns the space usage of the filesystem containing `path`.
+pub fn disk_usage(path: impl AsRef<Path>) -> Result<DiskUsage> {
+    let path = path.as_ref();
+    sys::disk_usage(path).map_err(|err| FsPathError::disk_usage(err, path))
+}
+
+/// Returns the total size in bytes of all files under `path`.
+///
+/// Symlinks are not followed, so symlink loops can't occur. On unix, files with multiple hard
+/// links are only counted once.
+pub fn dir_size(path: impl AsRef<Path>) -> Result<u64> {
+    let mut seen = std::collections::HashSet::new();
+    let mut size = 0;
+    let mut stack = vec![path.as_ref().to_path_buf()];
+
+    while let Some(path) = stack.pop() {
+        let meta = fs::symlink_metadata(&path).map_err(|err| FsPathError::metadata(err, &path))?;
+        if meta.is_dir() {
+            for entry in read_dir(&path)? {
+                stack.push(entry.map_err(|err| FsPathError::read_dir(err, &path))?.path());
+            }
+        } else if meta.is_file() && sys::is_first_link(&meta, &mut seen) {
+            size += meta.len();
+        }
+    }
+
+    Ok(size)
+}
+
+#[cfg(unix)]
+mod sys {
+    use super::DiskUsage;
+    use std::{
+        collections::HashSet,
+        ffi::CString,
+        fs::Metadata,
+        io,
+        os::unix::{ffi::OsStrExt, fs::MetadataExt},
+        path::Path,
+    };
+
+    pub(super) fn disk_usage(path: &Path) -> io::Result<DiskUsage> {
+        let path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        // SAFETY: `path` is a valid nul-terminated string and `stat` is a valid out pointer.
+        let stat = unsafe {
+            let mut stat: libc::statvfs = std::mem::zeroed();
+            if libc::statvfs(path.as_ptr(), &raw mut stat) != 0 {
+                return Err(io::Error::last_os_error())
+            }
+            stat
+        };
+        #[allow(clippy::unnecessary_cast, clippy::useless_conversion)]
+        let block_size = stat.f_frsize as u64;
+        #[allow(clippy::unnecessary_cast, clippy::useless_conversion)]
+        Ok(DiskUsage {
+            total: stat.f_blocks as u64 * block_size,
+            available: stat.f_bavail as u64 * block_size,
+            free: stat.f_bfree as u64 * block_size,
+        })
+    }
+
+    /// Returns `false` if another hard link to the same file was already seen.
+    pub(super) fn is_first_link(meta: &Metadata, seen: &mut HashSet<(u64, u64)>) -> bool {
+        meta.nlink() <= 1 || seen.insert((meta.dev(), meta.ino()))
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use super::DiskUsage;
+    use std::{
+        collections::HashSet, fs::Metadata, io, iter::once, os::windows::ffi::OsStrExt, path::Path,
+    };
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    pub(super) fn disk_usage(path: &Path) -> io::Result<DiskUsage> {
+        let path = path.as_os_str().encode_wide().chain(once(0)).collect::<Vec<_>>();
+        let (mut available, mut total, mut free) = (0, 0, 0);
+        // SAFETY: `path` is a valid nul-terminated wide string and the out pointers are valid.
+        if unsafe {
+            GetDiskFreeSpaceExW(path.as_ptr(), &raw mut available, &raw mut total, &raw mut free)
+        } == 0
+        {
+            return Err(io::Error::last_os_error())
+        }
+        Ok(DiskUsage { total, available, free })
+    }
+
+    /// Hard links can't be identified from [`Metadata`] on windows, so every file is counted.
+    pub(super) const fn is_first_link(_meta: &Metadata, _seen: &mut HashSet<(u64, u64)>) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dir_size_sums_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        fs::write(dir.path().join("one"), [0; 10]).unwrap();
+        fs::write(dir.path().join("a/two"), [0; 20]).unwrap();
+        fs::write(dir.path().join("a/b/three"), [0; 30]).unwrap();
+
+        assert_eq!(dir_size(dir.path()).unwrap(), 60);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dir_size_skips_hard_links_and_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file"), [0; 10]).unwrap();
+        fs::hard_link(dir.path().join("file"), dir.path().join("link")).unwrap();
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("loop")).unwrap();
+
+        assert_eq!(dir_size(dir.path()).unwrap(), 10);
+    }
+
+    #[test]
+    fn disk_usage_of_tempdir() {
+        let dir = tempfile::tempdir().unwrap();
+        let usage = disk_usage(dir.path()).unwrap();
+        assert!(usage.total > 0);
+        assert!(usage.available <= usage.free && usage.free <= usage.total);
+
+        let missing = dir.path().join("missing");
+        assert!(matches!(disk_usage(&missing), Err(FsPathError::DiskUsage { .. })));
+    }
+}
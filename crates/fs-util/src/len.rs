@@ -0,0 +1,147 @@
+//! File truncation and preallocation.
+
+use crate::{FsPathError, Result};
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    path::Path,
+};
+
+/// Sets the length of the file at `path` to `len` bytes, truncating or zero-extending it.
+///
+/// Fails if the file doesn't exist.
+pub fn truncate(path: impl AsRef<Path>, len: u64) -> Result<()> {
+    let path = path.as_ref();
+    OpenOptions::new()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_len(len))
+        .map_err(|err| FsPathError::truncate(err, path, len))
+}
+
+/// Allocates disk space for the first `len` bytes of `file`, growing the file to `len` bytes if it
+/// is smaller. The file is never shrunk.
+///
+/// On linux this uses `fallocate`, so the blocks are reserved up front and later writes to the
+/// range don't fragment the file. Elsewhere, or if the filesystem doesn't support `fallocate`,
+/// the file is extended with [`File::set_len`], which may leave it sparse.
+///
+/// `path` is the path of `file` and only used for the error.
+pub fn preallocate(file: &File, len: u64, path: &Path) -> Result<()> {
+    if len == 0 {
+        return Ok(())
+    }
+    sys::preallocate(file, len).map_err(|err| FsPathError::allocate(err, path, len))
+}
+
+/// Grows `file` to `len` bytes if it is smaller.
+fn extend(file: &File, len: u64) -> io::Result<()> {
+    if file.metadata()?.len() < len {
+        file.set_len(len)?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::{fs::File, io, os::unix::io::AsRawFd};
+
+    pub(super) fn preallocate(file: &File, len: u64) -> io::Result<()> {
+        let Ok(off_len) = libc::off_t::try_from(len) else {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+        };
+        // SAFETY: the file descriptor is owned by `file` and valid for the duration of the call.
+        if unsafe { libc::fallocate(file.as_raw_fd(), 0, 0, off_len) } == 0 {
+            return Ok(())
+        }
+        let err = io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::EOPNOTSUPP | libc::ENOSYS) => super::extend(file, len),
+            _ => Err(err),
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use std::{fs::File, io};
+
+    pub(super) fn preallocate(file: &File, len: u64) -> io::Result<()> {
+        super::extend(file, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_file, file_size, read, write};
+
+    #[test]
+    fn truncate_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        write(&path, b"hello world").unwrap();
+
+        truncate(&path, 5).unwrap();
+        assert_eq!(file_size(&path).unwrap(), 5);
+        assert_eq!(read(&path).unwrap(), b"hello");
+
+        // growing fills the file with zeros
+        truncate(&path, 8).unwrap();
+        assert_eq!(file_size(&path).unwrap(), 8);
+        assert_eq!(read(&path).unwrap(), b"hello\0\0\0");
+    }
+
+    #[test]
+    fn preallocate_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        let file = create_file(&path).unwrap();
+
+        preallocate(&file, 4096, &path).unwrap();
+        assert_eq!(file_size(&path).unwrap(), 4096);
+
+        // a smaller length doesn't shrink the file
+        preallocate(&file, 100, &path).unwrap();
+        preallocate(&file, 0, &path).unwrap();
+        assert_eq!(file_size(&path).unwrap(), 4096);
+
+        preallocate(&file, 8192, &path).unwrap();
+        assert_eq!(file_size(&path).unwrap(), 8192);
+        assert!(read(&path).unwrap().iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn truncate_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing");
+
+        let err = truncate(&path, 10).unwrap_err();
+        assert!(err.is_not_found());
+        match err {
+            FsPathError::Truncate { path: err_path, len, .. } => {
+                assert_eq!(err_path, path);
+                assert_eq!(len, 10);
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn preallocate_read_only_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        write(&path, b"hello").unwrap();
+        let file = crate::open(&path).unwrap();
+
+        match preallocate(&file, 4096, &path).unwrap_err() {
+            FsPathError::Allocate { path: err_path, len, .. } => {
+                assert_eq!(err_path, path);
+                assert_eq!(len, 4096);
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+        assert_eq!(file_size(&path).unwrap(), 5);
+    }
+}
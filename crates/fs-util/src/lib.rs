@@ -8,11 +8,51 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
-    fs::{self, File, OpenOptions, ReadDir},
-    io::{self, BufWriter, Error, ErrorKind, Write},
+    fs::{self, DirEntry, File, FileType, OpenOptions, ReadDir},
+    io::{self, BufRead, BufReader, BufWriter, Error, ErrorKind, Write},
+    marker::PhantomData,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
+mod checksum;
+pub use checksum::{
+    read_json_file_checked, read_with_checksum, write_json_file_checked, write_with_checksum,
+};
+
+mod disk;
+pub use disk::{dir_size, disk_usage, DiskUsage};
+
+mod len;
+pub use len::{preallocate, truncate};
+
+mod link;
+pub use link::{hard_link, link_or_copy, LinkOutcome};
+
+mod lock;
+pub use lock::FileLock;
+
+mod options;
+pub use options::FsOpenOptions;
+
+mod retry;
+pub use retry::{
+    default_retryable, remove_file_retry, rename_retry, with_retry, write_retry, Backoff,
+    RetryPolicy,
+};
+
+mod sanitize;
+pub use sanitize::{safe_join, sanitize_file_name, InvalidPathReason};
+
+mod temp;
+pub use temp::TempPath;
+
+mod watch;
+pub use watch::{watch_file, FileWatcher};
+
+#[cfg(feature = "async")]
+pub mod tokio;
+
 /// Result alias for [`FsPathError`].
 pub type Result<T> = std::result::Result<T, FsPathError>;
 
@@ -92,6 +132,15 @@ pub enum FsPathError {
         path: PathBuf,
     },
 
+    /// Error variant for failed append operation with additional path context.
+    #[error("failed to append to {path:?}: {source}")]
+    Append {
+        /// The source `io::Error`.
+        source: io::Error,
+        /// The path related to the operation.
+        path: PathBuf,
+    },
+
     /// Error variant for failed file renaming operation with additional path context.
     #[error("failed to rename {from:?} to {to:?}: {source}")]
     Rename {
@@ -138,6 +187,16 @@ pub enum FsPathError {
         /// The path related to the operation.
         path: PathBuf,
     },
+
+    /// Error variant for failed filesystem space query with additional path context.
+    #[error("failed to get disk usage for {path:?}: {source}")]
+    DiskUsage {
+        /// The source `io::Error`.
+        source: io::Error,
+        /// The path related to the operation.
+        path: PathBuf,
+    },
+
     /// Error variant for failed fsync operation with additional path context.
     #[error("failed to sync path {path:?}: {source}")]
     Fsync {
@@ -146,6 +205,169 @@ pub enum FsPathError {
         /// The path related to the operation.
         path: PathBuf,
     },
+
+    /// Error variant for failed file copy operation with additional path context.
+    #[error("failed to copy {from:?} to {to:?}: {source}")]
+    Copy {
+        /// The source `io::Error`.
+        source: io::Error,
+        /// The original path.
+        from: PathBuf,
+        /// The target path.
+        to: PathBuf,
+    },
+
+    /// Error variant for failed file truncation with additional path and length context.
+    #[error("failed to set length of {path:?} to {len} bytes: {source}")]
+    Truncate {
+        /// The source `io::Error`.
+        source: io::Error,
+        /// The path related to the operation.
+        path: PathBuf,
+        /// The requested length in bytes.
+        len: u64,
+    },
+
+    /// Error variant for failed disk space allocation with additional path and length context.
+    #[error("failed to allocate {len} bytes for {path:?}: {source}")]
+    Allocate {
+        /// The source `io::Error`.
+        source: io::Error,
+        /// The path related to the operation.
+        path: PathBuf,
+        /// The requested length in bytes.
+        len: u64,
+    },
+
+    /// Error variant for failed hard link creation with additional path context.
+    #[error("failed to create hard link {to:?} pointing to {from:?}: {source}")]
+    HardLink {
+        /// The source `io::Error`.
+        source: io::Error,
+        /// The original path.
+        from: PathBuf,
+        /// The path of the hard link.
+        to: PathBuf,
+    },
+
+    /// Error variant for failed symlink creation with additional path context.
+    #[error("failed to create symlink {link:?} pointing to {original:?}: {source}")]
+    CreateSymlink {
+        /// The source `io::Error`.
+        source: io::Error,
+        /// The path the symlink points to.
+        original: PathBuf,
+        /// The path of the symlink.
+        link: PathBuf,
+    },
+
+    /// Error variant for failed advisory file lock operation with additional path context.
+    #[error("failed to lock {path:?}: {source}")]
+    Lock {
+        /// The source `io::Error`.
+        source: io::Error,
+        /// The path related to the operation.
+        path: PathBuf,
+    },
+
+    /// Error variant for a lock file that is already held by another [`FileLock`].
+    #[error("{path:?} is already locked{}", .pid.map(|pid| format!(" by process {pid}")).unwrap_or_default())]
+    AlreadyLocked {
+        /// The PID recorded in the lock file by the current holder, if it could be read.
+        pid: Option<u32>,
+        /// The path related to the operation.
+        path: PathBuf,
+    },
+
+    /// Error variant for a file that was expected to be newly created but already exists.
+    #[error("file {path:?} already exists")]
+    AlreadyExists {
+        /// The path related to the operation.
+        path: PathBuf,
+    },
+
+    /// Error variant for an untrusted path rejected by [`safe_join`].
+    #[error("invalid path {path:?}: {reason}")]
+    InvalidPath {
+        /// The rejected path.
+        path: PathBuf,
+        /// Why the path was rejected.
+        reason: InvalidPathReason,
+    },
+
+    /// Error variant for a JSON file that matches neither the current nor the previous schema
+    /// during a migration, with additional path context.
+    #[error(
+        "failed to migrate json file {path:?}: {new_schema_error} (current schema), {old_schema_error} (previous schema)"
+    )]
+    MigrateJson {
+        /// The `serde_json::Error` of parsing the file with the current schema.
+        new_schema_error: serde_json::Error,
+        /// The `serde_json::Error` of parsing the file with the previous schema.
+        old_schema_error: serde_json::Error,
+        /// The path related to the operation.
+        path: PathBuf,
+    },
+
+    /// Error variant for a line of a JSON-lines file that failed to parse, with additional path
+    /// and line context.
+    #[error("failed to parse line {line} of json-lines file {path:?}: {source}")]
+    ReadJsonLine {
+        /// The source `serde_json::Error`.
+        source: serde_json::Error,
+        /// The path related to the operation.
+        path: PathBuf,
+        /// The 1-based number of the line that failed to parse.
+        line: usize,
+    },
+
+    /// Error variant for a checksummed file whose payload doesn't match the checksum in its
+    /// header, see [`read_with_checksum`].
+    #[error("checksum mismatch in {path:?}: expected {expected:#010x}, got {actual:#010x}")]
+    ChecksumMismatch {
+        /// The path related to the operation.
+        path: PathBuf,
+        /// The checksum recorded in the header.
+        expected: u32,
+        /// The checksum of the payload that was read.
+        actual: u32,
+    },
+
+    /// Error variant for a file that was expected to be checksummed but has no valid checksum
+    /// header, e.g. a legacy file written without [`write_with_checksum`].
+    #[error("file {path:?} has no checksum header")]
+    MissingChecksumHeader {
+        /// The path related to the operation.
+        path: PathBuf,
+    },
+
+    /// Error variant for a checksummed file with a header version this release can't read.
+    #[error("file {path:?} has unsupported checksum header version {version}")]
+    UnsupportedChecksumVersion {
+        /// The path related to the operation.
+        path: PathBuf,
+        /// The version found in the header.
+        version: u8,
+    },
+
+    /// Error variant for a blocking task of an async wrapper that panicked or was cancelled,
+    /// with additional path context.
+    #[error("blocking task for {path:?} failed: {source}")]
+    TaskJoin {
+        /// The source `io::Error`, converted from the task's join error.
+        source: io::Error,
+        /// The path related to the operation.
+        path: PathBuf,
+    },
+
+    /// Error variant for an operation that still failed after all attempts of [`with_retry`].
+    #[error("{source} (gave up after {attempts} attempts)")]
+    RetriesExhausted {
+        /// The error of the last attempt.
+        source: Box<Self>,
+        /// The number of attempts made.
+        attempts: u32,
+    },
 }
 
 impl FsPathError {
@@ -154,6 +376,11 @@ impl FsPathError {
         Self::Write { source, path: path.into() }
     }
 
+    /// Returns the complementary error variant for appending to a file.
+    pub fn append(source: io::Error, path: impl Into<PathBuf>) -> Self {
+        Self::Append { source, path: path.into() }
+    }
+
     /// Returns the complementary error variant for [`std::fs::read`].
     pub fn read(source: io::Error, path: impl Into<PathBuf>) -> Self {
         Self::Read { source, path: path.into() }
@@ -204,10 +431,435 @@ impl FsPathError {
         Self::Metadata { source, path: path.into() }
     }
 
+    /// Returns the complementary error variant for [`disk_usage`].
+    pub fn disk_usage(source: io::Error, path: impl Into<PathBuf>) -> Self {
+        Self::DiskUsage { source, path: path.into() }
+    }
+
     /// Returns the complementary error variant for `fsync`.
     pub fn fsync(source: io::Error, path: impl Into<PathBuf>) -> Self {
         Self::Fsync { source, path: path.into() }
     }
+
+    /// Returns the complementary error variant for [`std::fs::copy`].
+    pub fn copy(source: io::Error, from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> Self {
+        Self::Copy { source, from: from.into(), to: to.into() }
+    }
+
+    /// Returns the complementary error variant for [`File::set_len`].
+    pub fn truncate(source: io::Error, path: impl Into<PathBuf>, len: u64) -> Self {
+        Self::Truncate { source, path: path.into(), len }
+    }
+
+    /// Returns the complementary error variant for [`preallocate`].
+    pub fn allocate(source: io::Error, path: impl Into<PathBuf>, len: u64) -> Self {
+        Self::Allocate { source, path: path.into(), len }
+    }
+
+    /// Returns the complementary error variant for [`std::fs::hard_link`].
+    pub fn hard_link(source: io::Error, from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> Self {
+        Self::HardLink { source, from: from.into(), to: to.into() }
+    }
+
+    /// Returns the complementary error variant for [`std::os::unix::fs::symlink`].
+    pub fn create_symlink(
+        source: io::Error,
+        original: impl Into<PathBuf>,
+        link: impl Into<PathBuf>,
+    ) -> Self {
+        Self::CreateSymlink { source, original: original.into(), link: link.into() }
+    }
+
+    /// Returns the complementary error variant for acquiring a [`FileLock`].
+    pub fn lock(source: io::Error, path: impl Into<PathBuf>) -> Self {
+        Self::Lock { source, path: path.into() }
+    }
+
+    /// Returns the complementary error variant for [`safe_join`].
+    pub fn invalid_path(path: impl Into<PathBuf>, reason: InvalidPathReason) -> Self {
+        Self::InvalidPath { path: path.into(), reason }
+    }
+
+    /// Returns the complementary error variant for a failed blocking task of an async wrapper.
+    pub fn task_join(source: io::Error, path: impl Into<PathBuf>) -> Self {
+        Self::TaskJoin { source, path: path.into() }
+    }
+
+    /// Returns the complementary error variant for an operation that failed after all attempts of
+    /// [`with_retry`].
+    pub fn retries_exhausted(source: Self, attempts: u32) -> Self {
+        Self::RetriesExhausted { source: Box::new(source), attempts }
+    }
+
+    /// Returns the [`ErrorKind`] of the underlying `io::Error`, if the variant carries one.
+    ///
+    /// Returns `None` for variants without an `io::Error`, e.g. [`FsPathError::ReadJson`].
+    pub fn io_error_kind(&self) -> Option<ErrorKind> {
+        self.source_io().map(io::Error::kind)
+    }
+
+    /// Returns `true` if the underlying `io::Error` is [`ErrorKind::NotFound`].
+    pub fn is_not_found(&self) -> bool {
+        self.io_error_kind() == Some(ErrorKind::NotFound)
+    }
+
+    /// Returns `true` if the underlying `io::Error` is [`ErrorKind::PermissionDenied`].
+    pub fn is_permission_denied(&self) -> bool {
+        self.io_error_kind() == Some(ErrorKind::PermissionDenied)
+    }
+
+    /// Returns `true` if the underlying `io::Error` was caused by an operation across
+    /// filesystems, e.g. a [`FsPathError::HardLink`] or [`FsPathError::Rename`] to another mount.
+    ///
+    /// Note: this relies on the OS error code, which is not preserved by [`Clone`].
+    pub fn is_cross_device(&self) -> bool {
+        self.source_io().is_some_and(link::is_cross_device)
+    }
+
+    /// Returns the path of the failed operation.
+    ///
+    /// For operations involving two paths, such as [`FsPathError::Rename`],
+    /// [`FsPathError::Copy`] and [`FsPathError::HardLink`], this is the source path. For
+    /// [`FsPathError::CreateSymlink`] it is the path of the link.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Write { path, .. } |
+            Self::Read { path, .. } |
+            Self::ReadLink { path, .. } |
+            Self::CreateFile { path, .. } |
+            Self::RemoveFile { path, .. } |
+            Self::CreateDir { path, .. } |
+            Self::RemoveDir { path, .. } |
+            Self::ReadDir { path, .. } |
+            Self::Append { path, .. } |
+            Self::Open { path, .. } |
+            Self::ReadJson { path, .. } |
+            Self::WriteJson { path, .. } |
+            Self::Metadata { path, .. } |
+            Self::DiskUsage { path, .. } |
+            Self::Fsync { path, .. } |
+            Self::Lock { path, .. } |
+            Self::Truncate { path, .. } |
+            Self::Allocate { path, .. } |
+            Self::AlreadyLocked { path, .. } |
+            Self::AlreadyExists { path } |
+            Self::InvalidPath { path, .. } |
+            Self::MigrateJson { path, .. } |
+            Self::ReadJsonLine { path, .. } |
+            Self::ChecksumMismatch { path, .. } |
+            Self::MissingChecksumHeader { path } |
+            Self::UnsupportedChecksumVersion { path, .. } |
+            Self::TaskJoin { path, .. } => path,
+            Self::Rename { from, .. } | Self::Copy { from, .. } | Self::HardLink { from, .. } => {
+                from
+            }
+            Self::CreateSymlink { link, .. } => link,
+            Self::RetriesExhausted { source, .. } => source.path(),
+        }
+    }
+
+    /// Returns both paths of the failed operation, the path returned by [`Self::path`] and the
+    /// second path of operations involving two paths.
+    ///
+    /// For [`FsPathError::Rename`], [`FsPathError::Copy`] and [`FsPathError::HardLink`], the
+    /// second path is the target path. For [`FsPathError::CreateSymlink`] it is the path the link
+    /// points to.
+    pub fn paths(&self) -> (&Path, Option<&Path>) {
+        match self {
+            Self::Rename { from, to, .. } |
+            Self::Copy { from, to, .. } |
+            Self::HardLink { from, to, .. } => (from, Some(to)),
+            Self::CreateSymlink { original, link, .. } => (link, Some(original)),
+            Self::RetriesExhausted { source, .. } => source.paths(),
+            _ => (self.path(), None),
+        }
+    }
+
+    /// Returns the underlying `io::Error`, if the variant carries one.
+    ///
+    /// Returns `None` for variants without an `io::Error`, e.g. [`FsPathError::ReadJson`]. For
+    /// [`FsPathError::RetriesExhausted`], this is the `io::Error` of the last attempt.
+    pub fn source_io(&self) -> Option<&io::Error> {
+        match self {
+            Self::Write { source, .. } |
+            Self::Read { source, .. } |
+            Self::ReadLink { source, .. } |
+            Self::CreateFile { source, .. } |
+            Self::RemoveFile { source, .. } |
+            Self::CreateDir { source, .. } |
+            Self::RemoveDir { source, .. } |
+            Self::ReadDir { source, .. } |
+            Self::Append { source, .. } |
+            Self::Rename { source, .. } |
+            Self::Open { source, .. } |
+            Self::Metadata { source, .. } |
+            Self::DiskUsage { source, .. } |
+            Self::Fsync { source, .. } |
+            Self::Copy { source, .. } |
+            Self::HardLink { source, .. } |
+            Self::CreateSymlink { source, .. } |
+            Self::Lock { source, .. } |
+            Self::Truncate { source, .. } |
+            Self::Allocate { source, .. } |
+            Self::TaskJoin { source, .. } => Some(source),
+            Self::RetriesExhausted { source, .. } => source.source_io(),
+            Self::ReadJson { .. } |
+            Self::WriteJson { .. } |
+            Self::AlreadyLocked { .. } |
+            Self::AlreadyExists { .. } |
+            Self::InvalidPath { .. } |
+            Self::MigrateJson { .. } |
+            Self::ReadJsonLine { .. } |
+            Self::ChecksumMismatch { .. } |
+            Self::MissingChecksumHeader { .. } |
+            Self::UnsupportedChecksumVersion { .. } => None,
+        }
+    }
+
+    /// Returns the [`ErrorKind`] used when converting a variant without an `io::Error` into an
+    /// `io::Error`.
+    fn fallback_error_kind(&self) -> ErrorKind {
+        match self {
+            Self::AlreadyLocked { .. } => ErrorKind::WouldBlock,
+            Self::AlreadyExists { .. } => ErrorKind::AlreadyExists,
+            Self::InvalidPath { .. } => ErrorKind::InvalidInput,
+            Self::ReadJson { .. } |
+            Self::WriteJson { .. } |
+            Self::MigrateJson { .. } |
+            Self::ReadJsonLine { .. } |
+            Self::ChecksumMismatch { .. } |
+            Self::MissingChecksumHeader { .. } |
+            Self::UnsupportedChecksumVersion { .. } => ErrorKind::InvalidData,
+            Self::RetriesExhausted { source, .. } => source.fallback_error_kind(),
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// Clones the `io::Error` sources by [`io::ErrorKind`] and message, and the `serde_json::Error`
+/// sources by message. The OS error code of an `io::Error` is not preserved.
+impl Clone for FsPathError {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Write { source, path } => {
+                Self::Write { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::Read { source, path } => {
+                Self::Read { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::ReadLink { source, path } => {
+                Self::ReadLink { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::CreateFile { source, path } => {
+                Self::CreateFile { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::RemoveFile { source, path } => {
+                Self::RemoveFile { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::CreateDir { source, path } => {
+                Self::CreateDir { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::RemoveDir { source, path } => {
+                Self::RemoveDir { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::ReadDir { source, path } => {
+                Self::ReadDir { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::Append { source, path } => {
+                Self::Append { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::Open { source, path } => {
+                Self::Open { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::Metadata { source, path } => {
+                Self::Metadata { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::DiskUsage { source, path } => {
+                Self::DiskUsage { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::Fsync { source, path } => {
+                Self::Fsync { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::Lock { source, path } => {
+                Self::Lock { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::TaskJoin { source, path } => {
+                Self::TaskJoin { source: clone_io_error(source), path: path.clone() }
+            }
+            Self::ReadJson { source, path } => {
+                Self::ReadJson { source: clone_json_error(source), path: path.clone() }
+            }
+            Self::WriteJson { source, path } => {
+                Self::WriteJson { source: clone_json_error(source), path: path.clone() }
+            }
+            Self::Rename { source, from, to } => {
+                Self::Rename { source: clone_io_error(source), from: from.clone(), to: to.clone() }
+            }
+            Self::Copy { source, from, to } => {
+                Self::Copy { source: clone_io_error(source), from: from.clone(), to: to.clone() }
+            }
+            Self::HardLink { source, from, to } => Self::HardLink {
+                source: clone_io_error(source),
+                from: from.clone(),
+                to: to.clone(),
+            },
+            Self::CreateSymlink { source, original, link } => Self::CreateSymlink {
+                source: clone_io_error(source),
+                original: original.clone(),
+                link: link.clone(),
+            },
+            Self::Truncate { source, path, len } => {
+                Self::Truncate { source: clone_io_error(source), path: path.clone(), len: *len }
+            }
+            Self::Allocate { source, path, len } => {
+                Self::Allocate { source: clone_io_error(source), path: path.clone(), len: *len }
+            }
+            Self::AlreadyLocked { pid, path } => {
+                Self::AlreadyLocked { pid: *pid, path: path.clone() }
+            }
+            Self::AlreadyExists { path } => Self::AlreadyExists { path: path.clone() },
+            Self::InvalidPath { path, reason } => {
+                Self::InvalidPath { path: path.clone(), reason: *reason }
+            }
+            Self::MigrateJson { new_schema_error, old_schema_error, path } => Self::MigrateJson {
+                new_schema_error: clone_json_error(new_schema_error),
+                old_schema_error: clone_json_error(old_schema_error),
+                path: path.clone(),
+            },
+            Self::ReadJsonLine { source, path, line } => Self::ReadJsonLine {
+                source: clone_json_error(source),
+                path: path.clone(),
+                line: *line,
+            },
+            Self::ChecksumMismatch { path, expected, actual } => {
+                Self::ChecksumMismatch { path: path.clone(), expected: *expected, actual: *actual }
+            }
+            Self::MissingChecksumHeader { path } => {
+                Self::MissingChecksumHeader { path: path.clone() }
+            }
+            Self::UnsupportedChecksumVersion { path, version } => {
+                Self::UnsupportedChecksumVersion { path: path.clone(), version: *version }
+            }
+            Self::RetriesExhausted { source, attempts } => {
+                Self::RetriesExhausted { source: source.clone(), attempts: *attempts }
+            }
+        }
+    }
+}
+
+/// Compares `io::Error` sources by [`io::ErrorKind`] and `serde_json::Error` sources by message.
+impl PartialEq for FsPathError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Write { source: a, path: pa }, Self::Write { source: b, path: pb }) |
+            (Self::Read { source: a, path: pa }, Self::Read { source: b, path: pb }) |
+            (Self::ReadLink { source: a, path: pa }, Self::ReadLink { source: b, path: pb }) |
+            (
+                Self::CreateFile { source: a, path: pa },
+                Self::CreateFile { source: b, path: pb },
+            ) |
+            (
+                Self::RemoveFile { source: a, path: pa },
+                Self::RemoveFile { source: b, path: pb },
+            ) |
+            (Self::CreateDir { source: a, path: pa }, Self::CreateDir { source: b, path: pb }) |
+            (Self::RemoveDir { source: a, path: pa }, Self::RemoveDir { source: b, path: pb }) |
+            (Self::ReadDir { source: a, path: pa }, Self::ReadDir { source: b, path: pb }) |
+            (Self::Append { source: a, path: pa }, Self::Append { source: b, path: pb }) |
+            (Self::Open { source: a, path: pa }, Self::Open { source: b, path: pb }) |
+            (Self::Metadata { source: a, path: pa }, Self::Metadata { source: b, path: pb }) |
+            (Self::DiskUsage { source: a, path: pa }, Self::DiskUsage { source: b, path: pb }) |
+            (Self::Fsync { source: a, path: pa }, Self::Fsync { source: b, path: pb }) |
+            (Self::Lock { source: a, path: pa }, Self::Lock { source: b, path: pb }) |
+            (Self::TaskJoin { source: a, path: pa }, Self::TaskJoin { source: b, path: pb }) => {
+                a.kind() == b.kind() && pa == pb
+            }
+            (
+                Self::Rename { source: a, from: fa, to: ta },
+                Self::Rename { source: b, from: fb, to: tb },
+            ) |
+            (
+                Self::Copy { source: a, from: fa, to: ta },
+                Self::Copy { source: b, from: fb, to: tb },
+            ) |
+            (
+                Self::HardLink { source: a, from: fa, to: ta },
+                Self::HardLink { source: b, from: fb, to: tb },
+            ) |
+            (
+                Self::CreateSymlink { source: a, original: fa, link: ta },
+                Self::CreateSymlink { source: b, original: fb, link: tb },
+            ) => a.kind() == b.kind() && fa == fb && ta == tb,
+            (
+                Self::Truncate { source: a, path: pa, len: la },
+                Self::Truncate { source: b, path: pb, len: lb },
+            ) |
+            (
+                Self::Allocate { source: a, path: pa, len: la },
+                Self::Allocate { source: b, path: pb, len: lb },
+            ) => a.kind() == b.kind() && pa == pb && la == lb,
+            (Self::ReadJson { source: a, path: pa }, Self::ReadJson { source: b, path: pb }) |
+            (Self::WriteJson { source: a, path: pa }, Self::WriteJson { source: b, path: pb }) => {
+                a.to_string() == b.to_string() && pa == pb
+            }
+            (
+                Self::AlreadyLocked { pid: a, path: pa },
+                Self::AlreadyLocked { pid: b, path: pb },
+            ) => a == b && pa == pb,
+            (Self::AlreadyExists { path: pa }, Self::AlreadyExists { path: pb }) |
+            (
+                Self::MissingChecksumHeader { path: pa },
+                Self::MissingChecksumHeader { path: pb },
+            ) => pa == pb,
+            (
+                Self::InvalidPath { path: pa, reason: ra },
+                Self::InvalidPath { path: pb, reason: rb },
+            ) => pa == pb && ra == rb,
+            (
+                Self::MigrateJson { new_schema_error: na, old_schema_error: oa, path: pa },
+                Self::MigrateJson { new_schema_error: nb, old_schema_error: ob, path: pb },
+            ) => na.to_string() == nb.to_string() && oa.to_string() == ob.to_string() && pa == pb,
+            (
+                Self::ReadJsonLine { source: a, path: pa, line: la },
+                Self::ReadJsonLine { source: b, path: pb, line: lb },
+            ) => a.to_string() == b.to_string() && pa == pb && la == lb,
+            (
+                Self::ChecksumMismatch { path: pa, expected: ea, actual: aa },
+                Self::ChecksumMismatch { path: pb, expected: eb, actual: ab },
+            ) => pa == pb && ea == eb && aa == ab,
+            (
+                Self::UnsupportedChecksumVersion { path: pa, version: va },
+                Self::UnsupportedChecksumVersion { path: pb, version: vb },
+            ) => pa == pb && va == vb,
+            (
+                Self::RetriesExhausted { source: a, attempts: na },
+                Self::RetriesExhausted { source: b, attempts: nb },
+            ) => a == b && na == nb,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for FsPathError {}
+
+/// Converts the error into an `io::Error` with the [`ErrorKind`] of the underlying `io::Error`
+/// and the display string of the [`FsPathError`] as message.
+///
+/// Variants without an `io::Error` are mapped to the closest [`ErrorKind`], e.g.
+/// [`ErrorKind::InvalidData`] for parse and checksum errors.
+impl From<FsPathError> for io::Error {
+    fn from(err: FsPathError) -> Self {
+        let kind = err.io_error_kind().unwrap_or_else(|| err.fallback_error_kind());
+        Self::new(kind, err.to_string())
+    }
+}
+
+fn clone_io_error(err: &io::Error) -> io::Error {
+    io::Error::new(err.kind(), err.to_string())
+}
+
+fn clone_json_error(err: &serde_json::Error) -> serde_json::Error {
+    <serde_json::Error as serde::de::Error>::custom(err)
 }
 
 /// Wrapper for [`File::open`].
@@ -222,6 +874,14 @@ pub fn read_to_string(path: impl AsRef<Path>) -> Result<String> {
     fs::read_to_string(path).map_err(|err| FsPathError::read(err, path))
 }
 
+/// Opens the file at `path` and returns an iterator over its lines.
+///
+/// Only failing to open the file is reported as [`FsPathError`], errors reading individual lines
+/// are yielded by the iterator.
+pub fn read_lines(path: impl AsRef<Path>) -> Result<impl Iterator<Item = io::Result<String>>> {
+    Ok(BufReader::new(open(path)?).lines())
+}
+
 /// Read the entire contents of a file into a bytes vector.
 ///
 /// Wrapper for `std::fs::read`
@@ -230,12 +890,69 @@ pub fn read(path: impl AsRef<Path>) -> Result<Vec<u8>> {
     fs::read(path).map_err(|err| FsPathError::read(err, path))
 }
 
+/// Read the entire contents of a file into a bytes vector.
+///
+/// Returns `Ok(None)` if the file does not exist.
+pub fn read_bytes_optional(path: impl AsRef<Path>) -> Result<Option<Vec<u8>>> {
+    let path = path.as_ref();
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(FsPathError::read(err, path)),
+    }
+}
+
 /// Wrapper for `std::fs::write`
 pub fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<()> {
     let path = path.as_ref();
     fs::write(path, contents).map_err(|err| FsPathError::write(err, path))
 }
 
+/// Appends `contents` to the file at `path`, creating the file if it does not exist.
+pub fn append_to_file(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<()> {
+    let path = path.as_ref();
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_ref()))
+        .map_err(|err| FsPathError::append(err, path))
+}
+
+/// Writes each of `lines` followed by a newline to the file at `path`, replacing its contents.
+pub fn write_lines(
+    path: impl AsRef<Path>,
+    lines: impl IntoIterator<Item = impl AsRef<str>>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let file = create_file(path)?;
+    write_lines_to(file, lines).map_err(|err| FsPathError::write(err, path))
+}
+
+/// Appends each of `lines` followed by a newline to the file at `path`, creating the file if it
+/// does not exist.
+pub fn append_lines(
+    path: impl AsRef<Path>,
+    lines: impl IntoIterator<Item = impl AsRef<str>>,
+) -> Result<()> {
+    let path = path.as_ref();
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|file| write_lines_to(file, lines))
+        .map_err(|err| FsPathError::append(err, path))
+}
+
+fn write_lines_to(file: File, lines: impl IntoIterator<Item = impl AsRef<str>>) -> io::Result<()> {
+    let mut writer = BufWriter::new(file);
+    for line in lines {
+        writer.write_all(line.as_ref().as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
 /// Wrapper for `std::fs::remove_dir_all`
 pub fn remove_dir_all(path: impl AsRef<Path>) -> Result<()> {
     let path = path.as_ref();
@@ -245,7 +962,24 @@ pub fn remove_dir_all(path: impl AsRef<Path>) -> Result<()> {
 /// Wrapper for `File::create`.
 pub fn create_file(path: impl AsRef<Path>) -> Result<fs::File> {
     let path = path.as_ref();
-    File::create(path).map_err(|err| FsPathError::create_file(err, path))
+    FsOpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open_io(path)
+        .map_err(|err| FsPathError::create_file(err, path))
+}
+
+/// Creates a new file at `path` in write mode, failing if it already exists.
+///
+/// Returns [`FsPathError::AlreadyExists`] if the file exists.
+pub fn create_new(path: impl AsRef<Path>) -> Result<fs::File> {
+    FsOpenOptions::new().write(true).create_new(true).open(path)
+}
+
+/// Opens the file at `path` in append mode, creating it if it doesn't exist.
+pub fn append(path: impl AsRef<Path>) -> Result<fs::File> {
+    FsOpenOptions::new().append(true).create(true).open(path)
 }
 
 /// Wrapper for `std::fs::remove_file`
@@ -260,12 +994,79 @@ pub fn create_dir_all(path: impl AsRef<Path>) -> Result<()> {
     fs::create_dir_all(path).map_err(|err| FsPathError::create_dir(err, path))
 }
 
+/// Creates all missing parent directories of `path`.
+///
+/// Does nothing if `path` has no parent, e.g. if it is a root or a bare file name.
+pub fn ensure_parent_dirs_exist(path: impl AsRef<Path>) -> Result<()> {
+    match path.as_ref().parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => create_dir_all(parent),
+        _ => Ok(()),
+    }
+}
+
 /// Wrapper for `std::fs::read_dir`
 pub fn read_dir(path: impl AsRef<Path>) -> Result<ReadDir> {
     let path = path.as_ref();
     fs::read_dir(path).map_err(|err| FsPathError::read_dir(err, path))
 }
 
+/// Returns the entries of the directory at `path`, sorted by file name.
+///
+/// Unlike [`read_dir`], the order of the entries is deterministic.
+pub fn read_dir_sorted(path: impl AsRef<Path>) -> Result<Vec<DirEntry>> {
+    let path = path.as_ref();
+    let mut entries = read_dir(path)?
+        .collect::<io::Result<Vec<_>>>()
+        .map_err(|err| FsPathError::read_dir(err, path))?;
+    entries.sort_by_key(|entry| entry.file_name());
+    Ok(entries)
+}
+
+/// Returns the paths of all files in the directory at `path`, sorted by file name.
+///
+/// If `ext` is set, only files with this extension are returned. Symlinks are classified by their
+/// target if `follow_symlinks` is set, and skipped otherwise.
+pub fn list_files(
+    path: impl AsRef<Path>,
+    ext: Option<&str>,
+    follow_symlinks: bool,
+) -> Result<Vec<PathBuf>> {
+    list_entries(path.as_ref(), follow_symlinks, |path, file_type| {
+        file_type.is_file() && ext.is_none_or(|ext| path.extension().is_some_and(|e| e == ext))
+    })
+}
+
+/// Returns the paths of all directories in the directory at `path`, sorted by file name.
+///
+/// Symlinks are classified by their target if `follow_symlinks` is set, and skipped otherwise.
+pub fn list_dirs(path: impl AsRef<Path>, follow_symlinks: bool) -> Result<Vec<PathBuf>> {
+    list_entries(path.as_ref(), follow_symlinks, |_, file_type| file_type.is_dir())
+}
+
+/// Returns the paths of the entries in the directory at `path` that match `filter`, sorted by
+/// file name.
+fn list_entries(
+    path: &Path,
+    follow_symlinks: bool,
+    mut filter: impl FnMut(&Path, FileType) -> bool,
+) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in read_dir_sorted(path)? {
+        let path = entry.path();
+        let mut file_type = entry.file_type().map_err(|err| FsPathError::metadata(err, &path))?;
+        if file_type.is_symlink() {
+            if !follow_symlinks {
+                continue
+            }
+            file_type = metadata(&path)?.file_type();
+        }
+        if filter(&path, file_type) {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
 /// Wrapper for `std::fs::rename`
 pub fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
     let from = from.as_ref();
@@ -273,12 +1074,105 @@ pub fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
     fs::rename(from, to).map_err(|err| FsPathError::rename(err, from, to))
 }
 
+/// Wrapper for `std::fs::read_link`
+pub fn read_symlink(path: impl AsRef<Path>) -> Result<PathBuf> {
+    let path = path.as_ref();
+    fs::read_link(path).map_err(|err| FsPathError::read_link(err, path))
+}
+
+/// Wrapper for `std::os::unix::fs::symlink`
+///
+/// Creates a symlink at `link` pointing to `original`.
+///
+/// On windows, where symlinks to files and directories are created differently, a directory
+/// symlink is created if `original` is an existing directory, and a file symlink otherwise. Like
+/// the symlink itself, a relative `original` is resolved against the parent directory of `link`.
+#[cfg(any(unix, windows))]
+pub fn create_symlink(original: impl AsRef<Path>, link: impl AsRef<Path>) -> Result<()> {
+    let original = original.as_ref();
+    let link = link.as_ref();
+
+    #[cfg(unix)]
+    let res = std::os::unix::fs::symlink(original, link);
+
+    #[cfg(windows)]
+    let res = if link.parent().unwrap_or_else(|| Path::new("")).join(original).is_dir() {
+        std::os::windows::fs::symlink_dir(original, link)
+    } else {
+        std::os::windows::fs::symlink_file(original, link)
+    };
+
+    res.map_err(|err| FsPathError::create_symlink(err, original, link))
+}
+
+/// Writes `contents` to the file at `path` and fsyncs it to disk.
+///
+/// Note: this does not fsync the parent directory, see [`fsync_dir`].
+pub fn write_sync(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<()> {
+    let path = path.as_ref();
+    let mut file = create_file(path)?;
+    file.write_all(contents.as_ref()).map_err(|err| FsPathError::write(err, path))?;
+    file.sync_all().map_err(|err| FsPathError::fsync(err, path))
+}
+
+/// Fsyncs the directory at `path`, persisting changes to its entries (e.g. a created or renamed
+/// file).
+pub fn fsync_dir(path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+
+    #[cfg(windows)]
+    let dir = {
+        use std::os::windows::fs::OpenOptionsExt;
+
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(0x02000000) // FILE_FLAG_BACKUP_SEMANTICS
+            .open(path)
+    };
+
+    #[cfg(not(windows))]
+    let dir = OpenOptions::new().read(true).open(path);
+
+    dir.map_err(|err| FsPathError::open(err, path))?
+        .sync_all()
+        .map_err(|err| FsPathError::fsync(err, path))
+}
+
+/// Wrapper for `std::fs::rename` that also fsyncs the parent directory of `to`, so the rename is
+/// durable once this returns.
+pub fn rename_sync(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
+    let to = to.as_ref();
+    rename(from, to)?;
+    if let Some(parent) = parent_dir(to) {
+        fsync_dir(parent)?;
+    }
+    Ok(())
+}
+
+/// Returns the parent directory of `path`, treating a bare file name as relative to the current
+/// directory.
+fn parent_dir(path: &Path) -> Option<&Path> {
+    path.parent().map(|parent| if parent.as_os_str().is_empty() { Path::new(".") } else { parent })
+}
+
 /// Wrapper for `std::fs::metadata`
 pub fn metadata(path: impl AsRef<Path>) -> Result<fs::Metadata> {
     let path = path.as_ref();
     fs::metadata(path).map_err(|err| FsPathError::metadata(err, path))
 }
 
+/// Returns the size of the file at `path` in bytes.
+pub fn file_size(path: impl AsRef<Path>) -> Result<u64> {
+    Ok(metadata(path)?.len())
+}
+
+/// Returns the last modification time of the file at `path`.
+pub fn modified_time(path: impl AsRef<Path>) -> Result<SystemTime> {
+    let path = path.as_ref();
+    metadata(path)?.modified().map_err(|err| FsPathError::metadata(err, path))
+}
+
 /// Reads the JSON file and deserialize it into the provided type.
 pub fn read_json_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
     // read the file into a byte array first
@@ -288,23 +1182,173 @@ pub fn read_json_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
         .map_err(|source| FsPathError::ReadJson { source, path: path.into() })
 }
 
+/// Reads the JSON file and deserialize it into the provided type.
+///
+/// Returns `Ok(None)` if the file does not exist.
+pub fn read_optional<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let Some(bytes) = read_bytes_optional(path)? else { return Ok(None) };
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|source| FsPathError::ReadJson { source, path: path.into() })
+}
+
+/// Reads the JSON file and deserialize it into the provided type.
+///
+/// Returns `T::default()` if the file does not exist.
+pub fn read_json_file_or_default<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    Ok(read_optional(path)?.unwrap_or_default())
+}
+
+/// Reads the JSON file and deserialize it into the provided type.
+///
+/// Returns the value produced by `default` if the file does not exist or can't be deserialized.
+/// Any other IO error, e.g. insufficient permissions, is propagated.
+pub fn read_json_file_or_else<T: DeserializeOwned>(
+    path: &Path,
+    default: impl FnOnce() -> T,
+) -> Result<T> {
+    match read_optional(path) {
+        Ok(Some(value)) => Ok(value),
+        Ok(None) | Err(FsPathError::ReadJson { .. }) => Ok(default()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Migrates the JSON file at `path` from the `Old` to the `New` schema.
+///
+/// If the file already matches the `New` schema it is returned as is. Otherwise it is deserialized
+/// as `Old`, converted with `migrate` and atomically rewritten in the `New` schema, leaving a copy
+/// of the original file with a `.bak` suffix next to it.
+///
+/// Returns [`FsPathError::MigrateJson`] with both deserialization errors if the file matches
+/// neither schema.
+pub fn migrate_json_file<Old, New>(path: &Path, migrate: impl FnOnce(Old) -> New) -> Result<New>
+where
+    Old: DeserializeOwned,
+    New: DeserializeOwned + Serialize,
+{
+    let bytes = read(path)?;
+    let new_schema_error = match serde_json::from_slice(&bytes) {
+        Ok(new) => return Ok(new),
+        Err(err) => err,
+    };
+    let old = serde_json::from_slice(&bytes).map_err(|old_schema_error| {
+        FsPathError::MigrateJson { new_schema_error, old_schema_error, path: path.into() }
+    })?;
+    let new = migrate(old);
+
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    copy_file(path, &backup)?;
+    write_json_file_atomically(path, &new)?;
+
+    Ok(new)
+}
+
 /// Writes the object as a JSON object.
+///
+/// The output is pretty-printed, see [`write_json_file_pretty`].
 pub fn write_json_file<T: Serialize>(path: &Path, obj: &T) -> Result<()> {
+    write_json_file_pretty(path, obj)
+}
+
+/// Writes the object as a pretty-printed JSON object, indented with two spaces.
+pub fn write_json_file_pretty<T: Serialize>(path: &Path, obj: &T) -> Result<()> {
+    write_json_file_with_options(path, obj, "  ")
+}
+
+/// Writes the object as a pretty-printed JSON object, using `indent` for each indentation level.
+pub fn write_json_file_with_options<T: Serialize>(
+    path: &Path,
+    obj: &T,
+    indent: &str,
+) -> Result<()> {
     let file = create_file(path)?;
     let mut writer = BufWriter::new(file);
-    serde_json::to_writer_pretty(&mut writer, obj)
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut writer, formatter);
+    obj.serialize(&mut serializer)
         .map_err(|source| FsPathError::WriteJson { source, path: path.into() })?;
     writer.flush().map_err(|e| FsPathError::write(e, path))
 }
 
+/// Writes every item of `iter` as a single line of JSON (newline-delimited JSON).
+///
+/// Items are serialized one at a time through a buffered writer, so the whole collection is never
+/// held in memory.
+pub fn write_json_lines<T: Serialize>(
+    path: &Path,
+    iter: impl IntoIterator<Item = T>,
+) -> Result<()> {
+    let file = create_file(path)?;
+    let mut writer = BufWriter::new(file);
+    for item in iter {
+        serde_json::to_writer(&mut writer, &item)
+            .map_err(|source| FsPathError::WriteJson { source, path: path.into() })?;
+        writer.write_all(b"\n").map_err(|e| FsPathError::write(e, path))?;
+    }
+    writer.flush().map_err(|e| FsPathError::write(e, path))
+}
+
+/// Opens a newline-delimited JSON file, returning an iterator that lazily parses one item per
+/// line.
+///
+/// Blank lines are skipped. See [`JsonLinesIter`].
+pub fn read_json_lines<T: DeserializeOwned>(path: &Path) -> Result<JsonLinesIter<T>> {
+    let file = open(path)?;
+    Ok(JsonLinesIter {
+        lines: BufReader::new(file).lines(),
+        path: path.into(),
+        line: 0,
+        _marker: PhantomData,
+    })
+}
+
+/// Iterator over the items of a newline-delimited JSON file, returned by [`read_json_lines`].
+///
+/// Yields [`FsPathError::ReadJsonLine`] with the 1-based line number for lines that fail to parse,
+/// and continues with the next line on the following call.
+#[derive(Debug)]
+pub struct JsonLinesIter<T> {
+    lines: io::Lines<BufReader<File>>,
+    path: PathBuf,
+    line: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> Iterator for JsonLinesIter<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line += 1;
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(FsPathError::read(err, &self.path))),
+            };
+            if line.trim().is_empty() {
+                continue
+            }
+            return Some(serde_json::from_str(&line).map_err(|source| FsPathError::ReadJsonLine {
+                source,
+                path: self.path.clone(),
+                line: self.line,
+            }))
+        }
+    }
+}
+
 /// Writes atomically to file.
 ///
-/// 1. Creates a temporary file with a `.tmp` extension in the same file directory.
+/// 1. Creates a temporary file with a unique name in the same file directory, see [`TempPath`].
 /// 2. Writes content with `write_fn`.
 /// 3. Fsyncs the temp file to disk.
 /// 4. Renames the temp file to the target path.
 /// 5. Fsyncs the file directory.
 ///
+/// If any of the steps 2-4 fail, the temporary file is removed and the target file is left
+/// untouched.
+///
 /// Atomic writes are hard:
 /// * <https://github.com/paradigmxyz/reth/issues/8622>
 /// * <https://users.rust-lang.org/t/how-to-write-replace-files-atomically/42821/13>
@@ -313,47 +1357,820 @@ where
     F: FnOnce(&mut File) -> std::result::Result<(), E>,
     E: Into<Box<dyn core::error::Error + Send + Sync>>,
 {
-    #[cfg(windows)]
-    use std::os::windows::fs::OpenOptionsExt;
-
-    let mut tmp_path = file_path.to_path_buf();
-    tmp_path.set_extension("tmp");
+    // The temporary file is removed when dropped on error, the target file is left untouched
+    let mut tmp = TempPath::new_in(parent_dir(file_path).unwrap_or_else(|| Path::new(".")))?;
+    let tmp_path = tmp.path().to_path_buf();
 
     // Write to the temporary file
-    let mut file =
-        File::create(&tmp_path).map_err(|err| FsPathError::create_file(err, &tmp_path))?;
-
-    write_fn(&mut file).map_err(|err| FsPathError::Write {
-        source: Error::new(ErrorKind::Other, err.into()),
+    write_fn(tmp.as_file_mut()).map_err(|err| FsPathError::Write {
+        source: Error::other(err.into()),
         path: tmp_path.clone(),
     })?;
 
     // fsync() file
-    file.sync_all().map_err(|err| FsPathError::fsync(err, &tmp_path))?;
+    tmp.as_file_mut().sync_all().map_err(|err| FsPathError::fsync(err, &tmp_path))?;
 
     // Rename file, not move
-    rename(&tmp_path, file_path)?;
+    tmp.persist(file_path)?;
 
     // fsync() directory
-    if let Some(parent) = file_path.parent() {
-        #[cfg(windows)]
-        OpenOptions::new()
-            .read(true)
-            .write(true)
-            .custom_flags(0x02000000) // FILE_FLAG_BACKUP_SEMANTICS
-            .open(parent)
-            .map_err(|err| FsPathError::open(err, parent))?
-            .sync_all()
-            .map_err(|err| FsPathError::fsync(err, parent))?;
+    if let Some(parent) = parent_dir(file_path) {
+        fsync_dir(parent)?;
+    }
 
-        #[cfg(not(windows))]
-        OpenOptions::new()
-            .read(true)
-            .open(parent)
-            .map_err(|err| FsPathError::open(err, parent))?
-            .sync_all()
-            .map_err(|err| FsPathError::fsync(err, parent))?;
+    Ok(())
+}
+
+/// Writes `contents` atomically to the file at `path`.
+///
+/// Readers of `path` observe either the previous or the new content, but never a partially written
+/// file.
+///
+/// See [`atomic_write_file`] for details.
+pub fn write_atomically(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<()> {
+    atomic_write_file(path.as_ref(), |file| file.write_all(contents.as_ref()))
+}
+
+/// Writes the string `contents` atomically to the file at `path`.
+///
+/// See [`write_atomically`] for details.
+pub fn write_string_atomically(path: impl AsRef<Path>, contents: impl AsRef<str>) -> Result<()> {
+    write_atomically(path, contents.as_ref().as_bytes())
+}
+
+/// Writes the object as a JSON object atomically.
+///
+/// The object is serialized before anything is written to disk, so a serialization error leaves
+/// the target file untouched. The output is pretty-printed, like [`write_json_file`].
+///
+/// See [`write_atomically`] for details.
+pub fn write_json_file_atomically<T: Serialize>(path: &Path, obj: &T) -> Result<()> {
+    write_json_file_pretty_atomically(path, obj, "  ")
+}
+
+/// Writes the object atomically as a pretty-printed JSON object, using `indent` for each
+/// indentation level.
+///
+/// See [`write_json_file_atomically`] for details.
+pub fn write_json_file_pretty_atomically<T: Serialize>(
+    path: &Path,
+    obj: &T,
+    indent: &str,
+) -> Result<()> {
+    let mut bytes = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, formatter);
+    obj.serialize(&mut serializer)
+        .map_err(|source| FsPathError::WriteJson { source, path: path.into() })?;
+    write_atomically(path, bytes)
+}
+
+/// Wrapper for [`std::fs::copy`].
+///
+/// Returns the number of bytes copied.
+pub fn copy_file(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<u64> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    fs::copy(from, to).map_err(|err| FsPathError::copy(err, from, to))
+}
+
+/// Options for [`copy_dir_all_with_options`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CopyDirOptions {
+    /// Whether symlinks are followed and their targets copied. If `false`, symlinks are skipped.
+    ///
+    /// Note: symlink loops are not detected.
+    pub follow_symlinks: bool,
+    /// Whether to keep copying the remaining entries if an entry fails to copy.
+    ///
+    /// If `true`, errors are collected into [`CopyDirOutcome::errors`] instead of aborting the
+    /// copy.
+    pub continue_on_error: bool,
+}
+
+/// The outcome of [`copy_dir_all_with_options`].
+#[derive(Debug, Default)]
+pub struct CopyDirOutcome {
+    /// The total number of bytes copied.
+    pub copied_bytes: u64,
+    /// The errors encountered while copying, only populated if
+    /// [`CopyDirOptions::continue_on_error`] is set.
+    pub errors: Vec<FsPathError>,
+}
+
+/// Recursively copies the directory `src` to `dst`, creating `dst` and all intermediate
+/// directories. Symlinks are skipped.
+///
+/// Returns the total number of bytes copied.
+pub fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<u64> {
+    copy_dir_all_with_progress(src, dst, |_, _| {})
+}
+
+/// Same as [`copy_dir_all`], but calls `on_file` with the source path and size of every copied
+/// file.
+pub fn copy_dir_all_with_progress(
+    src: impl AsRef<Path>,
+    dst: impl AsRef<Path>,
+    on_file: impl FnMut(&Path, u64),
+) -> Result<u64> {
+    let outcome = copy_dir_all_with_options(src, dst, CopyDirOptions::default(), on_file)?;
+    Ok(outcome.copied_bytes)
+}
+
+/// Recursively copies the directory `src` to `dst` according to the given [`CopyDirOptions`],
+/// calling `on_file` with the source path and size of every copied file.
+pub fn copy_dir_all_with_options(
+    src: impl AsRef<Path>,
+    dst: impl AsRef<Path>,
+    options: CopyDirOptions,
+    mut on_file: impl FnMut(&Path, u64),
+) -> Result<CopyDirOutcome> {
+    let mut outcome = CopyDirOutcome::default();
+    copy_dir_inner(src.as_ref(), dst.as_ref(), options, &mut on_file, &mut outcome)?;
+    Ok(outcome)
+}
+
+fn copy_dir_inner(
+    src: &Path,
+    dst: &Path,
+    options: CopyDirOptions,
+    on_file: &mut impl FnMut(&Path, u64),
+    outcome: &mut CopyDirOutcome,
+) -> Result<()> {
+    create_dir_all(dst)?;
+
+    for entry in read_dir(src)? {
+        let res = entry.map_err(|err| FsPathError::read_dir(err, src)).and_then(|entry| {
+            let from = entry.path();
+            let to = dst.join(entry.file_name());
+
+            let mut file_type =
+                entry.file_type().map_err(|err| FsPathError::metadata(err, &from))?;
+            if file_type.is_symlink() {
+                if !options.follow_symlinks {
+                    return Ok(())
+                }
+                file_type = metadata(&from)?.file_type();
+            }
+
+            if file_type.is_dir() {
+                copy_dir_inner(&from, &to, options, on_file, outcome)
+            } else {
+                let copied = copy_file(&from, &to)?;
+                outcome.copied_bytes += copied;
+                on_file(&from, copied);
+                Ok(())
+            }
+        });
+        if let Err(err) = res {
+            if !options.continue_on_error {
+                return Err(err)
+            }
+            outcome.errors.push(err);
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::ser::{Error as _, SerializeSeq, Serializer};
+
+    /// Serializes a few elements and then fails.
+    struct FailingSerialize;
+
+    impl Serialize for FailingSerialize {
+        fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(None)?;
+            seq.serialize_element(&1)?;
+            seq.serialize_element(&2)?;
+            Err(S::Error::custom("failed halfway"))
+        }
+    }
+
+    #[test]
+    fn list_dir_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        for name in ["b.json", "a.json", "c.txt", "nested/d.json"] {
+            let file = path.join(name);
+            create_dir_all(file.parent().unwrap()).unwrap();
+            write(file, b"").unwrap();
+        }
+        create_dir_all(path.join("empty")).unwrap();
+
+        let names = read_dir_sorted(path)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["a.json", "b.json", "c.txt", "empty", "nested"]);
+
+        let files = list_files(path, None, false).unwrap();
+        assert_eq!(files, [path.join("a.json"), path.join("b.json"), path.join("c.txt")]);
+        // the order is stable across calls
+        assert_eq!(list_files(path, None, false).unwrap(), files);
+
+        let json_files = list_files(path, Some("json"), false).unwrap();
+        assert_eq!(json_files, [path.join("a.json"), path.join("b.json")]);
+        assert_eq!(list_dirs(path, false).unwrap(), [path.join("empty"), path.join("nested")]);
+
+        assert!(matches!(
+            read_dir_sorted(path.join("missing")),
+            Err(FsPathError::ReadDir { path: ref missing, .. }) if missing == &path.join("missing")
+        ));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn list_dir_entries_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        write(path.join("file"), b"").unwrap();
+        create_dir_all(path.join("dir")).unwrap();
+        create_symlink(path.join("file"), path.join("file_link")).unwrap();
+        create_symlink(path.join("dir"), path.join("dir_link")).unwrap();
+
+        assert_eq!(list_files(path, None, false).unwrap(), [path.join("file")]);
+        assert_eq!(list_dirs(path, false).unwrap(), [path.join("dir")]);
+        assert_eq!(
+            list_files(path, None, true).unwrap(),
+            [path.join("file"), path.join("file_link")]
+        );
+        assert_eq!(list_dirs(path, true).unwrap(), [path.join("dir"), path.join("dir_link")]);
+    }
+
+    #[test]
+    fn write_atomically_overwrites_existing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("known-peers.json");
+
+        write(&path, b"old").unwrap();
+        write_atomically(&path, b"new").unwrap();
+
+        assert_eq!(read(&path).unwrap(), b"new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        write_string_atomically(&path, "newer").unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), "newer");
+
+        write_atomically(&path, b"newest").unwrap();
+        assert_eq!(read(&path).unwrap(), b"newest");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn write_atomically_tmp_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.tmp");
+
+        write(&path, b"old").unwrap();
+        write_atomically(&path, b"new").unwrap();
+        assert_eq!(read(&path).unwrap(), b"new");
+
+        // a failed write leaves the target untouched
+        let err = atomic_write_file(&path, |_| Err(io::Error::other("failed"))).unwrap_err();
+        assert!(matches!(err, FsPathError::Write { .. }));
+        assert_eq!(read(&path).unwrap(), b"new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn write_atomically_targets_differing_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = dir.path().join("foo.json");
+        let toml = dir.path().join("foo.toml");
+
+        // both writes are in progress at the same time
+        atomic_write_file(&json, |file| {
+            file.write_all(b"json")?;
+            write_atomically(&toml, b"toml").map_err(io::Error::from)
+        })
+        .unwrap();
+
+        assert_eq!(read(&json).unwrap(), b"json");
+        assert_eq!(read(&toml).unwrap(), b"toml");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn write_atomically_missing_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("file.json");
+
+        let err = write_atomically(&path, b"data").unwrap_err();
+        assert!(
+            matches!(err, FsPathError::Open { path: ref tmp, .. } if tmp.parent() == path.parent())
+        );
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn atomic_write_json_serialization_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("known-peers.json");
+
+        write_json_file_atomically(&path, &vec![1, 2, 3]).unwrap();
+        let err = write_json_file_atomically(&path, &FailingSerialize).unwrap_err();
+        assert!(matches!(err, FsPathError::WriteJson { .. }));
+
+        // original file is intact and no temporary file is left behind
+        assert_eq!(read_json_file::<Vec<u64>>(&path).unwrap(), vec![1, 2, 3]);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn atomic_write_file_cleans_up_on_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        write(&path, b"old").unwrap();
+
+        let err = atomic_write_file(&path, |file| {
+            file.write_all(b"partial")?;
+            Err(io::Error::other("failed halfway"))
+        })
+        .unwrap_err();
+        assert!(matches!(err, FsPathError::Write { .. }));
+
+        assert_eq!(read(&path).unwrap(), b"old");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn read_optional_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jwt.hex");
+
+        assert_eq!(read_bytes_optional(&path).unwrap(), None);
+        assert_eq!(read_optional::<Vec<u64>>(&path).unwrap(), None);
+
+        write_json_file(&path, &vec![1u64, 2]).unwrap();
+        assert!(read_bytes_optional(&path).unwrap().is_some());
+        assert_eq!(read_optional::<Vec<u64>>(&path).unwrap(), Some(vec![1, 2]));
+
+        // other errors are still propagated
+        let err = read_bytes_optional(dir.path()).unwrap_err();
+        assert!(matches!(err, FsPathError::Read { .. }));
+    }
+
+    #[test]
+    fn sync_helpers() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("checkpoint.tmp");
+        let to = dir.path().join("checkpoint");
+
+        write_sync(&from, b"data").unwrap();
+        fsync_dir(dir.path()).unwrap();
+        rename_sync(&from, &to).unwrap();
+        assert_eq!(read(&to).unwrap(), b"data");
+        assert!(!from.exists());
+
+        let missing = dir.path().join("missing");
+        let err = fsync_dir(&missing).unwrap_err();
+        assert!(matches!(err, FsPathError::Open { ref path, .. } if path == &missing));
+
+        let err = write_sync(missing.join("file"), b"data").unwrap_err();
+        assert!(
+            matches!(err, FsPathError::CreateFile { ref path, .. } if path == &missing.join("file"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("v2");
+        let link = dir.path().join("current");
+        create_dir_all(&original).unwrap();
+
+        create_symlink(&original, &link).unwrap();
+        assert_eq!(read_symlink(&link).unwrap(), original);
+
+        let err = create_symlink(&original, &link).unwrap_err();
+        assert!(matches!(err, FsPathError::CreateSymlink { .. }));
+        assert_eq!(err.path(), link);
+
+        let err = read_symlink(&original).unwrap_err();
+        assert!(matches!(err, FsPathError::ReadLink { .. }));
+    }
+
+    #[test]
+    fn create_new_already_exists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+
+        create_new(&path).unwrap().write_all(b"first").unwrap();
+        let err = create_new(&path).unwrap_err();
+        assert_eq!(err, FsPathError::AlreadyExists { path: path.clone() });
+        assert_eq!(read(&path).unwrap(), b"first");
+
+        append(&path).unwrap().write_all(b" second").unwrap();
+        assert_eq!(read(&path).unwrap(), b"first second");
+
+        let missing = dir.path().join("missing").join("file");
+        let err = FsOpenOptions::new().read(true).open(&missing).unwrap_err();
+        assert!(matches!(err, FsPathError::Open { .. }) && err.is_not_found());
+    }
+
+    #[test]
+    fn write_json_file_indentation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("genesis.json");
+
+        write_json_file_pretty(&path, &vec![1]).unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), "[\n  1\n]");
+
+        write_json_file_with_options(&path, &vec![1], "\t").unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), "[\n\t1\n]");
+        assert_eq!(read_json_file::<Vec<u64>>(&path).unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn write_json_file_atomically_indentation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reth.json");
+
+        write_json_file_atomically(&path, &vec![1]).unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), "[\n  1\n]");
+
+        write_json_file_pretty_atomically(&path, &vec![2], "\t").unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), "[\n\t2\n]");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let err = write_json_file_pretty_atomically(&path, &FailingSerialize, "\t").unwrap_err();
+        assert!(matches!(err, FsPathError::WriteJson { .. }));
+        assert_eq!(read_json_file::<Vec<u64>>(&path).unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn error_kind_helpers() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = read(dir.path().join("missing")).unwrap_err();
+        assert!(err.is_not_found());
+        assert!(!err.is_permission_denied());
+
+        let err = FsPathError::open(io::Error::from(ErrorKind::PermissionDenied), dir.path());
+        assert!(err.is_permission_denied());
+        assert!(!err.is_not_found());
+
+        let err = FsPathError::AlreadyLocked { pid: None, path: dir.path().into() };
+        assert!(!err.is_not_found());
+    }
+
+    #[test]
+    fn error_clone_and_eq() {
+        let err = FsPathError::rename(io::Error::from(ErrorKind::NotFound), "from", "to");
+        assert_eq!(err.clone(), err);
+        assert_eq!(err.clone().to_string(), err.to_string());
+        assert_ne!(err, FsPathError::rename(io::Error::from(ErrorKind::NotFound), "from", "other"));
+        assert_ne!(err, FsPathError::read(io::Error::from(ErrorKind::NotFound), "from"));
+
+        let json_err = serde_json::from_str::<u64>("oops").unwrap_err();
+        let err = FsPathError::ReadJson { source: json_err, path: "a".into() };
+        assert_eq!(err.clone(), err);
+        assert_eq!(err.clone().to_string(), err.to_string());
+    }
+
+    #[test]
+    fn error_path() {
+        let err = FsPathError::read(io::Error::from(ErrorKind::NotFound), "a");
+        assert_eq!(err.path(), Path::new("a"));
+
+        let err = FsPathError::rename(io::Error::from(ErrorKind::NotFound), "from", "to");
+        assert_eq!(err.path(), Path::new("from"));
+    }
+
+    #[test]
+    fn error_accessors_all_variants() {
+        let io_err = || io::Error::from(ErrorKind::NotFound);
+        let json_err = || serde_json::from_str::<u64>("oops").unwrap_err();
+
+        // variants carrying an `io::Error` and their secondary path
+        let io_errors = [
+            (FsPathError::write(io_err(), "a"), None),
+            (FsPathError::append(io_err(), "a"), None),
+            (FsPathError::read(io_err(), "a"), None),
+            (FsPathError::read_link(io_err(), "a"), None),
+            (FsPathError::create_file(io_err(), "a"), None),
+            (FsPathError::remove_file(io_err(), "a"), None),
+            (FsPathError::create_dir(io_err(), "a"), None),
+            (FsPathError::remove_dir(io_err(), "a"), None),
+            (FsPathError::read_dir(io_err(), "a"), None),
+            (FsPathError::open(io_err(), "a"), None),
+            (FsPathError::rename(io_err(), "a", "b"), Some("b")),
+            (FsPathError::metadata(io_err(), "a"), None),
+            (FsPathError::disk_usage(io_err(), "a"), None),
+            (FsPathError::fsync(io_err(), "a"), None),
+            (FsPathError::copy(io_err(), "a", "b"), Some("b")),
+            (FsPathError::truncate(io_err(), "a", 1), None),
+            (FsPathError::allocate(io_err(), "a", 1), None),
+            (FsPathError::hard_link(io_err(), "a", "b"), Some("b")),
+            (FsPathError::create_symlink(io_err(), "b", "a"), Some("b")),
+            (FsPathError::lock(io_err(), "a"), None),
+            (FsPathError::task_join(io_err(), "a"), None),
+            (FsPathError::retries_exhausted(FsPathError::rename(io_err(), "a", "b"), 3), Some("b")),
+        ];
+        for (err, second) in io_errors {
+            assert_eq!(err.io_error_kind(), Some(ErrorKind::NotFound), "{err:?}");
+            assert_eq!(err.source_io().map(io::Error::kind), Some(ErrorKind::NotFound));
+            assert!(err.is_not_found());
+            assert_eq!(err.path(), Path::new("a"));
+            assert_eq!(err.paths(), (Path::new("a"), second.map(Path::new)));
+
+            let message = err.to_string();
+            let io_err = io::Error::from(err);
+            assert_eq!(io_err.kind(), ErrorKind::NotFound);
+            assert_eq!(io_err.to_string(), message);
+        }
+
+        // variants without an `io::Error` and the kind they are converted to
+        let other_errors = [
+            (
+                FsPathError::ReadJson { source: json_err(), path: "a".into() },
+                ErrorKind::InvalidData,
+            ),
+            (
+                FsPathError::WriteJson { source: json_err(), path: "a".into() },
+                ErrorKind::InvalidData,
+            ),
+            (FsPathError::AlreadyLocked { pid: Some(1), path: "a".into() }, ErrorKind::WouldBlock),
+            (FsPathError::AlreadyExists { path: "a".into() }, ErrorKind::AlreadyExists),
+            (FsPathError::invalid_path("a", InvalidPathReason::Absolute), ErrorKind::InvalidInput),
+            (
+                FsPathError::MigrateJson {
+                    new_schema_error: json_err(),
+                    old_schema_error: json_err(),
+                    path: "a".into(),
+                },
+                ErrorKind::InvalidData,
+            ),
+            (
+                FsPathError::ReadJsonLine { source: json_err(), path: "a".into(), line: 1 },
+                ErrorKind::InvalidData,
+            ),
+            (
+                FsPathError::ChecksumMismatch { path: "a".into(), expected: 1, actual: 2 },
+                ErrorKind::InvalidData,
+            ),
+            (FsPathError::MissingChecksumHeader { path: "a".into() }, ErrorKind::InvalidData),
+            (
+                FsPathError::UnsupportedChecksumVersion { path: "a".into(), version: 9 },
+                ErrorKind::InvalidData,
+            ),
+            (
+                FsPathError::retries_exhausted(FsPathError::AlreadyExists { path: "a".into() }, 2),
+                ErrorKind::AlreadyExists,
+            ),
+        ];
+        for (err, kind) in other_errors {
+            assert_eq!(err.io_error_kind(), None, "{err:?}");
+            assert!(err.source_io().is_none());
+            assert!(!err.is_not_found());
+            assert_eq!(err.path(), Path::new("a"));
+            assert_eq!(err.paths(), (Path::new("a"), None));
+
+            let message = err.to_string();
+            let io_err = io::Error::from(err);
+            assert_eq!(io_err.kind(), kind);
+            assert_eq!(io_err.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn ensure_parent_dirs_exist_creates_parents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a").join("b").join("file.json");
+
+        ensure_parent_dirs_exist(&path).unwrap();
+        assert!(dir.path().join("a").join("b").is_dir());
+        assert!(!path.exists());
+
+        ensure_parent_dirs_exist("file.json").unwrap();
+        ensure_parent_dirs_exist("/").unwrap();
+    }
+
+    #[test]
+    fn read_lines_yields_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lines");
+        fs::write(&path, "a\nb\n\nc").unwrap();
+
+        let lines = read_lines(&path).unwrap().collect::<io::Result<Vec<_>>>().unwrap();
+        assert_eq!(lines, ["a", "b", "", "c"]);
+
+        let missing = dir.path().join("missing");
+        assert!(matches!(read_lines(&missing), Err(FsPathError::Open { .. })));
+    }
+
+    #[test]
+    fn write_and_append_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lines");
+
+        write_lines(&path, ["a", "b"]).unwrap();
+        append_lines(&path, vec![String::from("c")]).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a\nb\nc\n");
+
+        write_lines(&path, ["d"]).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "d\n");
+    }
+
+    #[test]
+    fn file_size_and_modified_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, [0; 42]).unwrap();
+
+        assert_eq!(file_size(&path).unwrap(), 42);
+        assert_eq!(modified_time(&path).unwrap(), fs::metadata(&path).unwrap().modified().unwrap());
+
+        let missing = dir.path().join("missing");
+        assert!(matches!(file_size(&missing), Err(FsPathError::Metadata { .. })));
+        assert!(matches!(modified_time(&missing), Err(FsPathError::Metadata { .. })));
+    }
+
+    #[test]
+    fn json_lines_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("items.jsonl");
+
+        let items = (0..5000u64).map(|i| (i, format!("item-{i}")));
+        write_json_lines(&path, items.clone()).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().ends_with('\n'));
+
+        let read = read_json_lines::<(u64, String)>(&path).unwrap();
+        assert!(read.map(Result::unwrap).eq(items));
+    }
+
+    #[test]
+    fn json_lines_reports_corrupt_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("items.jsonl");
+        fs::write(&path, "1\n2\n{oops\n4\n\n").unwrap();
+
+        let mut iter = read_json_lines::<u64>(&path).unwrap();
+        assert_eq!(iter.next().unwrap().unwrap(), 1);
+        assert_eq!(iter.next().unwrap().unwrap(), 2);
+        let err = iter.next().unwrap().unwrap_err();
+        assert!(
+            matches!(err, FsPathError::ReadJsonLine { line: 3, path: ref p, .. } if p == &path)
+        );
+        assert_eq!(iter.next().unwrap().unwrap(), 4);
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn copy_file_reports_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("from");
+        let to = dir.path().join("to");
+        fs::write(&from, b"hello").unwrap();
+
+        assert_eq!(copy_file(&from, &to).unwrap(), 5);
+        assert_eq!(fs::read(&to).unwrap(), b"hello");
+
+        let missing = dir.path().join("missing");
+        let err = copy_file(&missing, &to).unwrap_err();
+        assert!(matches!(err, FsPathError::Copy { ref from, .. } if from == &missing));
+    }
+
+    #[test]
+    fn copy_dir_all_nested() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        let dst = dir.path().join("dst");
+        create_dir_all(src.join("a").join("b")).unwrap();
+        write(src.join("root"), b"12").unwrap();
+        write(src.join("a").join("b").join("leaf"), b"345").unwrap();
+
+        let mut reported = Vec::new();
+        let copied = copy_dir_all_with_progress(&src, &dst, |path, size| {
+            reported.push((path.to_path_buf(), size))
+        })
+        .unwrap();
+        reported.sort();
+
+        assert_eq!(copied, 5);
+        assert_eq!(
+            reported,
+            vec![(src.join("a").join("b").join("leaf"), 3), (src.join("root"), 2)]
+        );
+        assert_eq!(read(dst.join("root")).unwrap(), b"12");
+        assert_eq!(read(dst.join("a").join("b").join("leaf")).unwrap(), b"345");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_dir_all_symlinks_and_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        create_dir_all(&src).unwrap();
+        write(dir.path().join("target"), b"target").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("target"), src.join("link")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("missing"), src.join("dangling")).unwrap();
+        write(src.join("file"), b"file").unwrap();
+
+        // symlinks are skipped by default
+        let dst = dir.path().join("skip");
+        assert_eq!(copy_dir_all(&src, &dst).unwrap(), 4);
+        assert!(!dst.join("link").exists());
+
+        // following the dangling symlink fails
+        let options = CopyDirOptions { follow_symlinks: true, continue_on_error: false };
+        let dst = dir.path().join("abort");
+        assert!(copy_dir_all_with_options(&src, &dst, options, |_, _| {}).is_err());
+
+        // unless errors are collected
+        let options = CopyDirOptions { follow_symlinks: true, continue_on_error: true };
+        let dst = dir.path().join("follow");
+        let outcome = copy_dir_all_with_options(&src, &dst, options, |_, _| {}).unwrap();
+        assert_eq!(outcome.copied_bytes, 10);
+        assert_eq!(outcome.errors.len(), 1);
+        assert!(
+            matches!(outcome.errors[0], FsPathError::Metadata { ref path, .. } if path == &src.join("dangling"))
+        );
+        assert_eq!(read(dst.join("link")).unwrap(), b"target");
+    }
+
+    #[test]
+    fn read_json_file_or_default_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trusted-peers.json");
+
+        assert_eq!(read_json_file_or_default::<Vec<u64>>(&path).unwrap(), Vec::<u64>::new());
+
+        write(&path, b"not json").unwrap();
+        let err = read_json_file_or_default::<Vec<u64>>(&path).unwrap_err();
+        assert!(matches!(err, FsPathError::ReadJson { path: ref p, .. } if p == &path));
+    }
+
+    #[test]
+    fn read_json_file_or_else_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("peers.json");
+
+        // missing file
+        assert_eq!(read_json_file_or_else(&path, || vec![7u64]).unwrap(), vec![7]);
+
+        // corrupt file
+        write(&path, b"{").unwrap();
+        assert_eq!(read_json_file_or_else(&path, || vec![7u64]).unwrap(), vec![7]);
+
+        // valid file
+        write_json_file(&path, &vec![1u64]).unwrap();
+        assert_eq!(read_json_file_or_else(&path, || vec![7u64]).unwrap(), vec![1]);
+
+        // other io errors are propagated
+        let err = read_json_file_or_else(dir.path(), || vec![7u64]).unwrap_err();
+        assert!(matches!(err, FsPathError::Read { .. }));
+    }
+
+    #[test]
+    fn migrate_json_file_schemas() {
+        // the previous schema is a plain list of peers, the current one is versioned
+        type Old = Vec<String>;
+        type New = (u64, Vec<String>);
+
+        let migrate = |old: Old| (2, old);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("peers.json");
+        let backup = dir.path().join("peers.json.bak");
+        let expected: New = (2, vec!["a".to_string()]);
+
+        // missing file
+        let err = migrate_json_file(&path, migrate).unwrap_err();
+        assert!(matches!(err, FsPathError::Read { .. }));
+
+        // old schema is migrated and backed up
+        write(&path, br#"["a"]"#).unwrap();
+        assert_eq!(migrate_json_file(&path, migrate).unwrap(), expected);
+        assert_eq!(read_json_file::<New>(&path).unwrap(), expected);
+        assert_eq!(read(&backup).unwrap(), br#"["a"]"#);
+
+        // new schema is left untouched
+        remove_file(&backup).unwrap();
+        assert_eq!(migrate_json_file(&path, migrate).unwrap(), expected);
+        assert!(!backup.exists());
+
+        // corrupt file reports both schemas
+        write(&path, br#"{"peers":1}"#).unwrap();
+        let err = migrate_json_file(&path, migrate).unwrap_err();
+        assert!(matches!(err, FsPathError::MigrateJson { .. }));
+        assert_eq!(read(&path).unwrap(), br#"{"peers":1}"#);
+    }
+
+    #[test]
+    fn append_to_file_keeps_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+
+        append_to_file(&path, b"a\n").unwrap();
+        append_to_file(&path, b"b\n").unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), "a\nb\n");
+
+        let missing = dir.path().join("missing").join("audit.log");
+        let err = append_to_file(&missing, b"c").unwrap_err();
+        assert!(matches!(err, FsPathError::Append { ref path, .. } if path == &missing));
+    }
+}
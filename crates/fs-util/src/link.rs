@@ -0,0 +1,167 @@
+//! Hard link and symlink creation.
+
+use crate::{create_new, open, FsPathError, Result};
+use std::{fs, io, path::Path};
+
+/// The outcome of [`link_or_copy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkOutcome {
+    /// The file was hard linked.
+    Linked,
+    /// The file is on another filesystem and was copied.
+    Copied,
+}
+
+/// Wrapper for `std::fs::hard_link`
+///
+/// Creates a hard link at `to` pointing to the file at `from`. Fails if `to` already exists.
+pub fn hard_link(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    fs::hard_link(from, to).map_err(|err| FsPathError::hard_link(err, from, to))
+}
+
+/// Creates a hard link at `to` pointing to the file at `from`, or copies the file if `to` is on
+/// another filesystem.
+///
+/// Fails if `to` already exists, the existing file is never overwritten. Errors other than
+/// [`FsPathError::is_cross_device`] are returned without falling back to a copy.
+pub fn link_or_copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<LinkOutcome> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    match hard_link(from, to) {
+        Ok(()) => Ok(LinkOutcome::Linked),
+        Err(err) if err.is_cross_device() => {
+            copy_new(from, to)?;
+            Ok(LinkOutcome::Copied)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Copies the file at `from` to the new file `to`, failing if `to` already exists.
+fn copy_new(from: &Path, to: &Path) -> Result<()> {
+    let mut src = open(from)?;
+    let mut dst = create_new(to)?;
+    if let Err(err) = io::copy(&mut src, &mut dst) {
+        let _ = fs::remove_file(to);
+        return Err(FsPathError::copy(err, from, to))
+    }
+    // copy the permissions like `std::fs::copy`
+    let permissions = src.metadata().map_err(|err| FsPathError::metadata(err, from))?;
+    dst.set_permissions(permissions.permissions()).map_err(|err| FsPathError::copy(err, from, to))
+}
+
+/// Returns `true` if `err` was returned because the paths of a link are on different filesystems.
+pub(crate) fn is_cross_device(err: &io::Error) -> bool {
+    err.raw_os_error() == Some(sys::CROSS_DEVICE_ERROR)
+}
+
+#[cfg(unix)]
+mod sys {
+    pub(super) const CROSS_DEVICE_ERROR: i32 = libc::EXDEV;
+}
+
+#[cfg(windows)]
+mod sys {
+    use windows_sys::Win32::Foundation::ERROR_NOT_SAME_DEVICE;
+
+    pub(super) const CROSS_DEVICE_ERROR: i32 = ERROR_NOT_SAME_DEVICE as i32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_symlink, read, write};
+
+    #[test]
+    fn link_within_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("from");
+        write(&from, b"hello").unwrap();
+
+        let to = dir.path().join("to");
+        assert_eq!(link_or_copy(&from, &to), Ok(LinkOutcome::Linked));
+        assert_eq!(read(&to).unwrap(), b"hello");
+
+        // the link shares the contents with the original file
+        write(&from, b"changed").unwrap();
+        assert_eq!(read(&to).unwrap(), b"changed");
+
+        let link = dir.path().join("link");
+        create_symlink(&from, &link).unwrap();
+        assert_eq!(fs::read_link(&link).unwrap(), from);
+        assert_eq!(read(&link).unwrap(), b"changed");
+    }
+
+    #[test]
+    fn link_existing_destination() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("from");
+        let to = dir.path().join("to");
+        write(&from, b"new").unwrap();
+        write(&to, b"old").unwrap();
+
+        let err = link_or_copy(&from, &to).unwrap_err();
+        assert_eq!(
+            err,
+            FsPathError::hard_link(io::ErrorKind::AlreadyExists.into(), from.clone(), to.clone())
+        );
+        assert_eq!(err.path(), from);
+        assert_eq!(read(&to).unwrap(), b"old");
+
+        assert!(matches!(
+            create_symlink(&from, &to),
+            Err(FsPathError::CreateSymlink { ref original, ref link, .. })
+                if original == &from && link == &to
+        ));
+        assert_eq!(read(&to).unwrap(), b"old");
+    }
+
+    #[test]
+    fn copy_new_does_not_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("from");
+        let to = dir.path().join("to");
+        write(&from, b"hello").unwrap();
+
+        copy_new(&from, &to).unwrap();
+        assert_eq!(read(&to).unwrap(), b"hello");
+        assert_eq!(copy_new(&from, &to), Err(FsPathError::AlreadyExists { path: to }));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn link_across_filesystems() {
+        use std::os::unix::fs::MetadataExt;
+
+        // `/dev/shm` is usually a tmpfs, skip the test if it isn't on another filesystem
+        let dir = tempfile::tempdir().unwrap();
+        let Ok(other_dir) = tempfile::tempdir_in("/dev/shm") else { return };
+        if fs::metadata(dir.path()).unwrap().dev() == fs::metadata(other_dir.path()).unwrap().dev()
+        {
+            return
+        }
+
+        let from = dir.path().join("from");
+        let to = other_dir.path().join("to");
+        write(&from, b"hello").unwrap();
+        assert!(hard_link(&from, &to).unwrap_err().is_cross_device());
+        assert_eq!(link_or_copy(&from, &to), Ok(LinkOutcome::Copied));
+        assert_eq!(read(&to).unwrap(), b"hello");
+        // the copied file is not overwritten
+        write(&from, b"changed").unwrap();
+        assert!(link_or_copy(&from, &to).is_err());
+        assert_eq!(read(&to).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn cross_device_error() {
+        let (from, to) = (Path::new("from"), Path::new("to"));
+        let cross_device = io::Error::from_raw_os_error(sys::CROSS_DEVICE_ERROR);
+        assert!(FsPathError::hard_link(cross_device, from, to).is_cross_device());
+
+        let exists = io::Error::from(io::ErrorKind::AlreadyExists);
+        assert!(!FsPathError::hard_link(exists, from, to).is_cross_device());
+        let not_found = io::Error::from(io::ErrorKind::NotFound);
+        assert!(!FsPathError::hard_link(not_found, from, to).is_cross_device());
+    }
+}
@@ -0,0 +1,235 @@
+//! Advisory file locking.
+
+use crate::{FsPathError, Result};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Interval between attempts in [`FileLock::acquire_blocking`].
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// An exclusive advisory lock on a file, e.g. to guarantee that only one process uses a datadir.
+///
+/// The lock is taken with `flock` on unix and `LockFileEx` on windows, and the PID of the holder
+/// is written into the file so that contenders can report who holds it. The lock is released and
+/// the file is removed (best-effort) when the guard is dropped.
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Tries to acquire the lock on `path` without blocking, creating the file if necessary.
+    ///
+    /// Returns [`FsPathError::AlreadyLocked`] if another handle holds the lock.
+    pub fn try_acquire(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        loop {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)
+                .map_err(|err| FsPathError::open(err, path))?;
+
+            match sys::try_lock(&file) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return Err(FsPathError::AlreadyLocked {
+                        pid: read_pid(path),
+                        path: path.into(),
+                    })
+                }
+                Err(err) => return Err(FsPathError::lock(err, path)),
+            }
+
+            // The previous holder may have removed the file between our open and lock, in which
+            // case we locked an orphaned file and have to start over.
+            if !sys::is_same_file(&file, path) {
+                continue
+            }
+
+            let lock = Self { file, path: path.into() };
+            lock.write_pid()?;
+            return Ok(lock)
+        }
+    }
+
+    /// Acquires the lock on `path`, retrying until it becomes available or `timeout` elapses.
+    ///
+    /// Returns [`FsPathError::AlreadyLocked`] if the lock is still held after `timeout`.
+    pub fn acquire_blocking(path: impl AsRef<Path>, timeout: Duration) -> Result<Self> {
+        let path = path.as_ref();
+        let deadline = Instant::now() + timeout;
+        loop {
+            match Self::try_acquire(path) {
+                Err(FsPathError::AlreadyLocked { .. }) if Instant::now() < deadline => {
+                    std::thread::sleep(
+                        RETRY_INTERVAL.min(deadline.saturating_duration_since(Instant::now())),
+                    );
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Returns the path of the lock file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn write_pid(&self) -> Result<()> {
+        let mut file = &self.file;
+        file.set_len(0)
+            .and_then(|_| file.write_all(std::process::id().to_string().as_bytes()))
+            .and_then(|_| file.sync_all())
+            .map_err(|err| FsPathError::write(err, &self.path))
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // Remove the file while still holding the lock, so that contenders never observe an
+        // unlocked file at this path.
+        let _ = fs::remove_file(&self.path);
+        let _ = sys::unlock(&self.file);
+    }
+}
+
+/// Reads the PID written by the current holder of the lock file, if any.
+fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::{
+        fs::{self, File},
+        io,
+        os::unix::{fs::MetadataExt, io::AsRawFd},
+        path::Path,
+    };
+
+    /// Returns `Ok(false)` if the file is locked by another handle.
+    pub(super) fn try_lock(file: &File) -> io::Result<bool> {
+        // SAFETY: the descriptor is valid for the lifetime of `file`.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+            return Ok(true)
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() == io::ErrorKind::WouldBlock {
+            Ok(false)
+        } else {
+            Err(err)
+        }
+    }
+
+    pub(super) fn unlock(file: &File) -> io::Result<()> {
+        // SAFETY: the descriptor is valid for the lifetime of `file`.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_UN) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    pub(super) fn is_same_file(file: &File, path: &Path) -> bool {
+        match (file.metadata(), fs::metadata(path)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::{fs::File, io, os::windows::io::AsRawHandle, path::Path};
+    use windows_sys::Win32::{
+        Foundation::ERROR_LOCK_VIOLATION,
+        Storage::FileSystem::{
+            LockFileEx, UnlockFile, LOCKFILE_EXCLUSIVE_LOCK, LOCKFILE_FAIL_IMMEDIATELY,
+        },
+        System::IO::OVERLAPPED,
+    };
+
+    /// Returns `Ok(false)` if the file is locked by another handle.
+    pub(super) fn try_lock(file: &File) -> io::Result<bool> {
+        // SAFETY: the handle is valid for the lifetime of `file` and `overlapped` outlives the
+        // synchronous call.
+        let res = unsafe {
+            let mut overlapped: OVERLAPPED = std::mem::zeroed();
+            LockFileEx(
+                file.as_raw_handle() as _,
+                LOCKFILE_EXCLUSIVE_LOCK | LOCKFILE_FAIL_IMMEDIATELY,
+                0,
+                u32::MAX,
+                u32::MAX,
+                &raw mut overlapped,
+            )
+        };
+        if res != 0 {
+            return Ok(true)
+        }
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(ERROR_LOCK_VIOLATION as i32) {
+            Ok(false)
+        } else {
+            Err(err)
+        }
+    }
+
+    pub(super) fn unlock(file: &File) -> io::Result<()> {
+        // SAFETY: the handle is valid for the lifetime of `file`.
+        if unsafe { UnlockFile(file.as_raw_handle() as _, 0, 0, u32::MAX, u32::MAX) } != 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    /// A file that is still open can only be marked for deletion on windows, and can't be reopened
+    /// until the last handle is closed, so the path can't be swapped out from under a locked
+    /// handle.
+    pub(super) const fn is_same_file(_file: &File, _path: &Path) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_is_exclusive_until_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lock");
+
+        let lock = FileLock::try_acquire(&path).unwrap();
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+
+        let err = FileLock::try_acquire(&path).unwrap_err();
+        assert!(matches!(
+            err,
+            FsPathError::AlreadyLocked { pid: Some(pid), .. } if pid == std::process::id()
+        ));
+
+        drop(lock);
+        assert!(!path.exists());
+        FileLock::try_acquire(&path).unwrap();
+    }
+
+    #[test]
+    fn acquire_blocking_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lock");
+
+        let _lock = FileLock::try_acquire(&path).unwrap();
+        let err = FileLock::acquire_blocking(&path, Duration::from_millis(100)).unwrap_err();
+        assert!(matches!(err, FsPathError::AlreadyLocked { .. }));
+    }
+}
@@ -0,0 +1,90 @@
+//! Path-aware wrapper for [`OpenOptions`].
+
+use crate::{FsPathError, Result};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+/// Wrapper for [`OpenOptions`] that attaches the path to errors.
+///
+/// Mirrors the [`OpenOptions`] builder, with [`FsOpenOptions::open`] returning an [`FsPathError`].
+#[derive(Debug, Clone)]
+pub struct FsOpenOptions(OpenOptions);
+
+impl FsOpenOptions {
+    /// Creates a blank set of options, see [`OpenOptions::new`].
+    pub fn new() -> Self {
+        Self(OpenOptions::new())
+    }
+
+    /// Sets the option for read access, see [`OpenOptions::read`].
+    pub fn read(&mut self, read: bool) -> &mut Self {
+        self.0.read(read);
+        self
+    }
+
+    /// Sets the option for write access, see [`OpenOptions::write`].
+    pub fn write(&mut self, write: bool) -> &mut Self {
+        self.0.write(write);
+        self
+    }
+
+    /// Sets the option for append mode, see [`OpenOptions::append`].
+    pub fn append(&mut self, append: bool) -> &mut Self {
+        self.0.append(append);
+        self
+    }
+
+    /// Sets the option for truncating an existing file, see [`OpenOptions::truncate`].
+    pub fn truncate(&mut self, truncate: bool) -> &mut Self {
+        self.0.truncate(truncate);
+        self
+    }
+
+    /// Sets the option to create the file if it doesn't exist, see [`OpenOptions::create`].
+    pub fn create(&mut self, create: bool) -> &mut Self {
+        self.0.create(create);
+        self
+    }
+
+    /// Sets the option to create a new file, failing if it already exists, see
+    /// [`OpenOptions::create_new`].
+    pub fn create_new(&mut self, create_new: bool) -> &mut Self {
+        self.0.create_new(create_new);
+        self
+    }
+
+    /// Opens the file at `path` with the configured options.
+    ///
+    /// Returns [`FsPathError::AlreadyExists`] if [`FsOpenOptions::create_new`] is set and the file
+    /// exists, and [`FsPathError::Open`] for any other failure.
+    pub fn open(&self, path: impl AsRef<Path>) -> Result<File> {
+        let path = path.as_ref();
+        self.open_io(path).map_err(|err| {
+            if err.kind() == ErrorKind::AlreadyExists {
+                FsPathError::AlreadyExists { path: path.into() }
+            } else {
+                FsPathError::open(err, path)
+            }
+        })
+    }
+
+    /// Opens the file at `path`, leaving the error mapping to the caller.
+    pub(crate) fn open_io(&self, path: &Path) -> io::Result<File> {
+        self.0.open(path)
+    }
+}
+
+impl Default for FsOpenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<OpenOptions> for FsOpenOptions {
+    fn from(options: OpenOptions) -> Self {
+        Self(options)
+    }
+}
@@ -0,0 +1,206 @@
+//! Retrying wrappers for transient IO errors, e.g. on network filesystems.
+
+use crate::{remove_file, rename, write, FsPathError, Result};
+use std::{io::ErrorKind, path::Path, thread, time::Duration};
+
+/// The delay between the attempts of a [`RetryPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    /// The same delay before every retry.
+    Fixed(Duration),
+    /// A delay that starts at `initial` and doubles before every retry, up to `max`.
+    Exponential {
+        /// The delay before the first retry.
+        initial: Duration,
+        /// The maximum delay.
+        max: Duration,
+    },
+}
+
+impl Backoff {
+    /// Returns the delay before the retry that follows the given failed attempt, starting at 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        match *self {
+            Self::Fixed(delay) => delay,
+            Self::Exponential { initial, max } => initial
+                .checked_mul(1 << attempt.saturating_sub(1).min(31))
+                .map_or(max, |delay| delay.min(max)),
+        }
+    }
+}
+
+/// Options for [`with_retry`].
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// The delay between the attempts.
+    pub backoff: Backoff,
+    /// Whether an error with the given kind is transient and the operation should be retried.
+    pub is_retryable: fn(ErrorKind) -> bool,
+}
+
+impl RetryPolicy {
+    /// Returns `true` if the operation should be retried after the given error.
+    ///
+    /// Errors that don't carry an `io::Error` are never retried.
+    pub fn should_retry(&self, err: &FsPathError) -> bool {
+        err.source_io().is_some_and(|source| (self.is_retryable)(source.kind()))
+    }
+}
+
+/// Five attempts with an exponential backoff from 10ms up to 1s, retrying
+/// [`default_retryable`] errors.
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            backoff: Backoff::Exponential {
+                initial: Duration::from_millis(10),
+                max: Duration::from_secs(1),
+            },
+            is_retryable: default_retryable,
+        }
+    }
+}
+
+/// Returns `true` for the error kinds that are retried by default: [`ErrorKind::Interrupted`],
+/// [`ErrorKind::WouldBlock`] and [`ErrorKind::TimedOut`].
+pub fn default_retryable(kind: ErrorKind) -> bool {
+    matches!(kind, ErrorKind::Interrupted | ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+/// Runs `op` until it succeeds, retrying it according to the given [`RetryPolicy`].
+///
+/// Errors that are not retryable, see [`RetryPolicy::should_retry`], are returned immediately. If
+/// the operation still fails after [`RetryPolicy::max_attempts`], the last error is returned
+/// wrapped in [`FsPathError::RetriesExhausted`].
+pub fn with_retry<T>(policy: RetryPolicy, mut op: impl FnMut() -> Result<T>) -> Result<T> {
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Ok(value) => return Ok(value),
+            Err(err) if !policy.should_retry(&err) => return Err(err),
+            Err(err) if attempt >= policy.max_attempts => {
+                return Err(FsPathError::retries_exhausted(err, attempt))
+            }
+            Err(_) => {
+                thread::sleep(policy.backoff.delay(attempt));
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Same as [`crate::write`], but retries transient errors according to the given [`RetryPolicy`].
+pub fn write_retry(
+    path: impl AsRef<Path>,
+    contents: impl AsRef<[u8]>,
+    policy: RetryPolicy,
+) -> Result<()> {
+    let (path, contents) = (path.as_ref(), contents.as_ref());
+    with_retry(policy, || write(path, contents))
+}
+
+/// Same as [`crate::rename`], but retries transient errors according to the given [`RetryPolicy`].
+pub fn rename_retry(
+    from: impl AsRef<Path>,
+    to: impl AsRef<Path>,
+    policy: RetryPolicy,
+) -> Result<()> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    with_retry(policy, || rename(from, to))
+}
+
+/// Same as [`crate::remove_file`], but retries transient errors according to the given
+/// [`RetryPolicy`].
+pub fn remove_file_retry(path: impl AsRef<Path>, policy: RetryPolicy) -> Result<()> {
+    let path = path.as_ref();
+    with_retry(policy, || remove_file(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy { max_attempts, backoff: Backoff::Fixed(Duration::ZERO), ..Default::default() }
+    }
+
+    /// Returns an operation that fails with `kind` `failures` times before succeeding, and the
+    /// number of attempts made.
+    fn flaky(kind: ErrorKind, failures: u32) -> impl FnMut() -> Result<u32> {
+        let mut attempts = 0;
+        move || {
+            attempts += 1;
+            if attempts <= failures {
+                Err(FsPathError::write(io::Error::from(kind), "flaky"))
+            } else {
+                Ok(attempts)
+            }
+        }
+    }
+
+    #[test]
+    fn retries_transient_errors() {
+        assert_eq!(with_retry(policy(3), flaky(ErrorKind::Interrupted, 0)), Ok(1));
+        assert_eq!(with_retry(policy(3), flaky(ErrorKind::Interrupted, 2)), Ok(3));
+        assert_eq!(with_retry(policy(3), flaky(ErrorKind::WouldBlock, 2)), Ok(3));
+        assert_eq!(with_retry(policy(3), flaky(ErrorKind::TimedOut, 2)), Ok(3));
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let err = with_retry(policy(3), flaky(ErrorKind::Interrupted, 3)).unwrap_err();
+        let expected = FsPathError::write(io::Error::from(ErrorKind::Interrupted), "flaky");
+        assert_eq!(err, FsPathError::retries_exhausted(expected, 3));
+        assert_eq!(err.path(), Path::new("flaky"));
+        assert!(err.to_string().contains("after 3 attempts"), "{err}");
+
+        // a single attempt is never retried
+        let err = with_retry(policy(1), flaky(ErrorKind::Interrupted, 1)).unwrap_err();
+        assert!(matches!(err, FsPathError::RetriesExhausted { attempts: 1, .. }));
+    }
+
+    #[test]
+    fn other_errors_are_not_retried() {
+        let mut op = flaky(ErrorKind::NotFound, 1);
+        let err = with_retry(policy(3), &mut op).unwrap_err();
+        assert_eq!(err, FsPathError::write(io::Error::from(ErrorKind::NotFound), "flaky"));
+        assert!(err.is_not_found());
+        // the operation was attempted once
+        assert_eq!(op(), Ok(2));
+
+        let custom = RetryPolicy { is_retryable: |kind| kind == ErrorKind::NotFound, ..policy(3) };
+        assert_eq!(with_retry(custom, flaky(ErrorKind::NotFound, 2)), Ok(3));
+        assert!(with_retry(custom, flaky(ErrorKind::Interrupted, 1)).is_err());
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let backoff = Backoff::Exponential {
+            initial: Duration::from_millis(10),
+            max: Duration::from_millis(100),
+        };
+        let delays = (1..=6).map(|attempt| backoff.delay(attempt)).collect::<Vec<_>>();
+        assert_eq!(delays, [10, 20, 40, 80, 100, 100].map(Duration::from_millis));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_millis(100));
+        assert_eq!(Backoff::Fixed(Duration::from_millis(5)).delay(10), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn retrying_wrappers() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+
+        write_retry(&a, b"data", policy(3)).unwrap();
+        rename_retry(&a, &b, policy(3)).unwrap();
+        assert_eq!(crate::read(&b).unwrap(), b"data");
+        remove_file_retry(&b, policy(3)).unwrap();
+
+        // errors keep their path context
+        assert_eq!(remove_file_retry(&b, policy(3)).unwrap_err(), remove_file(&b).unwrap_err());
+        assert_eq!(rename_retry(&a, &b, policy(3)).unwrap_err(), rename(&a, &b).unwrap_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! Joining and sanitizing untrusted paths.
+
+use crate::{FsPathError, Result};
+use std::path::{Component, Path, PathBuf};
+
+/// The reason an untrusted path was rejected by [`safe_join`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidPathReason {
+    /// The path has no normal components, e.g. it is empty or `.`.
+    #[error("path is empty")]
+    Empty,
+    /// The path is absolute.
+    #[error("path is absolute")]
+    Absolute,
+    /// The path contains a `..` segment.
+    #[error("path contains a parent directory segment")]
+    ParentDir,
+    /// The path has a windows drive or UNC prefix, e.g. `C:` or `\\server\share`.
+    #[error("path has a drive or UNC prefix")]
+    Prefix,
+}
+
+/// Joins the untrusted relative path to `base`, so that the result can't escape `base`.
+///
+/// Fails with [`FsPathError::InvalidPath`] if `untrusted` is absolute, contains a `..` segment,
+/// has a windows drive or UNC prefix, or is empty. `.` segments are skipped.
+///
+/// Note: the check is lexical, symlinks inside `base` are not resolved.
+pub fn safe_join(base: &Path, untrusted: impl AsRef<Path>) -> Result<PathBuf> {
+    let untrusted = untrusted.as_ref();
+    let invalid = |reason| FsPathError::invalid_path(untrusted, reason);
+
+    let mut joined = base.to_path_buf();
+    let mut is_empty = true;
+    for component in untrusted.components() {
+        match component {
+            Component::Normal(segment) => {
+                joined.push(segment);
+                is_empty = false;
+            }
+            Component::CurDir => {}
+            Component::ParentDir => return Err(invalid(InvalidPathReason::ParentDir)),
+            Component::RootDir => return Err(invalid(InvalidPathReason::Absolute)),
+            Component::Prefix(_) => return Err(invalid(InvalidPathReason::Prefix)),
+        }
+    }
+    if is_empty {
+        return Err(invalid(InvalidPathReason::Empty))
+    }
+    Ok(joined)
+}
+
+/// Turns the untrusted `name` into a single file name by removing all path separators and
+/// control characters.
+///
+/// Both `/` and `\` are removed on all platforms, so the result is the same everywhere. If the
+/// result would be `.` or `..`, an empty string is returned instead, which [`safe_join`] rejects.
+pub fn sanitize_file_name(name: &str) -> String {
+    let sanitized =
+        name.chars().filter(|c| !matches!(c, '/' | '\\') && !c.is_control()).collect::<String>();
+    if matches!(sanitized.as_str(), "." | "..") {
+        return String::new()
+    }
+    sanitized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reason(base: &Path, untrusted: &str) -> Option<InvalidPathReason> {
+        match safe_join(base, untrusted) {
+            Ok(_) => None,
+            Err(FsPathError::InvalidPath { path, reason }) => {
+                assert_eq!(path, Path::new(untrusted));
+                Some(reason)
+            }
+            Err(err) => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    #[test]
+    fn join_relative_paths() {
+        let base = Path::new("datadir");
+        assert_eq!(safe_join(base, "mainnet").unwrap(), base.join("mainnet"));
+        assert_eq!(safe_join(base, "mainnet/db").unwrap(), base.join("mainnet").join("db"));
+        assert_eq!(safe_join(base, "./mainnet/./db/").unwrap(), base.join("mainnet").join("db"));
+        assert_eq!(safe_join(base, "..hidden").unwrap(), base.join("..hidden"));
+        assert_eq!(safe_join(base, "链/chaîne").unwrap(), base.join("链").join("chaîne"));
+        assert_eq!(safe_join(Path::new(""), "a").unwrap(), Path::new("a"));
+    }
+
+    #[test]
+    fn reject_escaping_paths() {
+        let base = Path::new("datadir");
+        assert_eq!(reason(base, ".."), Some(InvalidPathReason::ParentDir));
+        assert_eq!(reason(base, "../etc"), Some(InvalidPathReason::ParentDir));
+        assert_eq!(reason(base, "a/../../etc"), Some(InvalidPathReason::ParentDir));
+        // even if the path would stay inside the base
+        assert_eq!(reason(base, "a/../b"), Some(InvalidPathReason::ParentDir));
+        assert_eq!(reason(base, "/etc/passwd"), Some(InvalidPathReason::Absolute));
+        assert_eq!(reason(base, ""), Some(InvalidPathReason::Empty));
+        assert_eq!(reason(base, "."), Some(InvalidPathReason::Empty));
+        assert_eq!(reason(base, "./."), Some(InvalidPathReason::Empty));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn backslashes_are_file_names_on_unix() {
+        let base = Path::new("datadir");
+        assert_eq!(safe_join(base, "..\\etc").unwrap(), base.join("..\\etc"));
+        assert_eq!(safe_join(base, "C:\\etc").unwrap(), base.join("C:\\etc"));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn reject_windows_prefixes() {
+        let base = Path::new("datadir");
+        assert_eq!(reason(base, "C:\\Windows"), Some(InvalidPathReason::Prefix));
+        assert_eq!(reason(base, "C:relative"), Some(InvalidPathReason::Prefix));
+        assert_eq!(reason(base, "\\\\server\\share\\file"), Some(InvalidPathReason::Prefix));
+        assert_eq!(reason(base, "\\\\?\\C:\\file"), Some(InvalidPathReason::Prefix));
+        assert_eq!(reason(base, "\\Windows"), Some(InvalidPathReason::Absolute));
+        assert_eq!(reason(base, "..\\etc"), Some(InvalidPathReason::ParentDir));
+        assert_eq!(safe_join(base, "a\\b").unwrap(), base.join("a").join("b"));
+    }
+
+    #[test]
+    fn sanitize_names() {
+        assert_eq!(sanitize_file_name("mainnet"), "mainnet");
+        assert_eq!(sanitize_file_name("../../etc/passwd"), "....etcpasswd");
+        assert_eq!(sanitize_file_name("..\\..\\boot.ini"), "....boot.ini");
+        assert_eq!(sanitize_file_name("a\nb\tc\0d\u{7f}e\u{85}f"), "abcdef");
+        assert_eq!(sanitize_file_name("链-chaîne 🦀"), "链-chaîne 🦀");
+        assert_eq!(sanitize_file_name(""), "");
+        assert_eq!(sanitize_file_name("."), "");
+        assert_eq!(sanitize_file_name(".."), "");
+        assert_eq!(sanitize_file_name("/../"), "");
+        assert_eq!(sanitize_file_name("..."), "...");
+
+        // sanitized names can always be joined, unless they are empty
+        let base = Path::new("datadir");
+        for name in ["../x", "/", "a/b\\c", "\u{1b}[31m", "名前"] {
+            let sanitized = sanitize_file_name(name);
+            match safe_join(base, &sanitized) {
+                Ok(path) => assert_eq!(path, base.join(&sanitized)),
+                Err(err) => {
+                    assert!(sanitized.is_empty());
+                    assert_eq!(err, FsPathError::invalid_path("", InvalidPathReason::Empty));
+                }
+            }
+        }
+    }
+}
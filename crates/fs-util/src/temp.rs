@@ -0,0 +1,179 @@
+//! Temporary files that are removed unless persisted.
+
+use crate::{rename, FsOpenOptions, FsPathError, Result};
+use std::{
+    collections::hash_map::RandomState,
+    ffi::OsString,
+    fs::{self, File},
+    hash::{BuildHasher, Hasher},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Number of names tried by [`TempPath::new_in`] before giving up.
+const MAX_ATTEMPTS: usize = 16;
+
+/// A temporary file in a given directory that is removed when dropped, unless it was moved to its
+/// final destination with [`TempPath::persist`].
+///
+/// The temporary file should be created in the directory of the destination, so that persisting
+/// it is an atomic rename on the same filesystem.
+#[derive(Debug)]
+pub struct TempPath {
+    file: File,
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl TempPath {
+    /// Creates a new temporary file with a unique name in `dir`.
+    ///
+    /// If a file with the generated name already exists, another name is tried. Returns
+    /// [`FsPathError::AlreadyExists`] if no unused name was found.
+    pub fn new_in(dir: impl AsRef<Path>) -> Result<Self> {
+        Self::new_in_with(dir.as_ref(), temp_file_name)
+    }
+
+    fn new_in_with(dir: &Path, mut file_name: impl FnMut() -> OsString) -> Result<Self> {
+        let mut attempts = 0;
+        loop {
+            let path = dir.join(file_name());
+            match FsOpenOptions::new().read(true).write(true).create_new(true).open(&path) {
+                Ok(file) => return Ok(Self { file, path, persisted: false }),
+                Err(FsPathError::AlreadyExists { .. }) if attempts + 1 < MAX_ATTEMPTS => {
+                    attempts += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Returns the path of the temporary file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a mutable reference to the temporary file, e.g. to write to it.
+    pub fn as_file_mut(&mut self) -> &mut File {
+        &mut self.file
+    }
+
+    /// Renames the temporary file to `to`, replacing the file at `to` if it exists.
+    ///
+    /// The rename fails with [`FsPathError::Rename`] if `to` is on a different filesystem, the
+    /// file is never copied. On failure, the temporary file is removed.
+    ///
+    /// Note: this does not fsync the file or the directory, see [`crate::rename_sync`].
+    pub fn persist(mut self, to: impl AsRef<Path>) -> Result<()> {
+        rename(&self.path, to)?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Generates a hidden file name that is unique with high probability.
+fn temp_file_name() -> OsString {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    // `RandomState` is randomly seeded, which makes names unpredictable without depending on an
+    // rng
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    format!(".tmp{:016x}", hasher.finish()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn temp_path_removed_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut temp = TempPath::new_in(dir.path()).unwrap();
+        temp.as_file_mut().write_all(b"hello").unwrap();
+        let path = temp.path().to_path_buf();
+        assert_eq!(path.parent(), Some(dir.path()));
+        assert_eq!(fs::read(&path).unwrap(), b"hello");
+
+        drop(temp);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn temp_path_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        fs::write(&target, b"old").unwrap();
+
+        let mut temp = TempPath::new_in(dir.path()).unwrap();
+        temp.as_file_mut().write_all(b"new").unwrap();
+        let path = temp.path().to_path_buf();
+        temp.persist(&target).unwrap();
+
+        assert!(!path.exists());
+        assert_eq!(fs::read(&target).unwrap(), b"new");
+    }
+
+    #[test]
+    fn temp_path_name_collision() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("taken"), b"existing").unwrap();
+
+        // the taken name is skipped
+        let mut names = ["taken", "free"].into_iter();
+        let temp = TempPath::new_in_with(dir.path(), || names.next().unwrap().into()).unwrap();
+        assert_eq!(temp.path(), dir.path().join("free"));
+        assert_eq!(fs::read(dir.path().join("taken")).unwrap(), b"existing");
+
+        // gives up after `MAX_ATTEMPTS` taken names
+        let mut attempts = 0;
+        let err = TempPath::new_in_with(dir.path(), || {
+            attempts += 1;
+            "taken".into()
+        })
+        .unwrap_err();
+        assert_eq!(err, FsPathError::AlreadyExists { path: dir.path().join("taken") });
+        assert_eq!(attempts, MAX_ATTEMPTS);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn temp_path_persist_across_filesystems() {
+        use std::os::unix::fs::MetadataExt;
+
+        // `/dev/shm` is usually a tmpfs, skip the test if it isn't on another filesystem
+        let dir = tempfile::tempdir().unwrap();
+        let Ok(other_dir) = tempfile::tempdir_in("/dev/shm") else { return };
+        if fs::metadata(dir.path()).unwrap().dev() == fs::metadata(other_dir.path()).unwrap().dev()
+        {
+            return
+        }
+
+        let temp = TempPath::new_in(dir.path()).unwrap();
+        let path = temp.path().to_path_buf();
+        let target = other_dir.path().join("target");
+        let err = temp.persist(&target).unwrap_err();
+
+        match err {
+            FsPathError::Rename { source, from, to } => {
+                assert_eq!(source.raw_os_error(), Some(libc::EXDEV));
+                assert_eq!(from, path);
+                assert_eq!(to, target);
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+        assert!(!target.exists());
+        assert!(!path.exists());
+    }
+}
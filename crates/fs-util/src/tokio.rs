@@ -0,0 +1,132 @@
+//! Async wrappers that run the blocking fs helpers on tokio's blocking thread pool.
+//!
+//! Each function returns the same errors as its blocking counterpart. If the blocking task
+//! panics or is cancelled, [`FsPathError::TaskJoin`] is returned instead.
+
+use crate::{FsPathError, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Runs `f` with `path` on the blocking thread pool.
+async fn spawn_blocking<T, F>(path: PathBuf, f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(PathBuf) -> Result<T> + Send + 'static,
+{
+    let task_path = path.clone();
+    ::tokio::task::spawn_blocking(move || f(task_path))
+        .await
+        .map_err(|err| FsPathError::task_join(err.into(), path))?
+}
+
+/// Async wrapper for [`crate::read_to_string`].
+pub async fn read_to_string(path: impl AsRef<Path>) -> Result<String> {
+    spawn_blocking(path.as_ref().to_path_buf(), crate::read_to_string).await
+}
+
+/// Async wrapper for [`crate::read`].
+pub async fn read(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    spawn_blocking(path.as_ref().to_path_buf(), crate::read).await
+}
+
+/// Async wrapper for [`crate::write`].
+pub async fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<()> {
+    let contents = contents.as_ref().to_vec();
+    spawn_blocking(path.as_ref().to_path_buf(), move |path| crate::write(path, contents)).await
+}
+
+/// Async wrapper for [`crate::read_json_file`].
+pub async fn read_json_file<T: DeserializeOwned + Send + 'static>(
+    path: impl AsRef<Path>,
+) -> Result<T> {
+    spawn_blocking(path.as_ref().to_path_buf(), move |path| crate::read_json_file(&path)).await
+}
+
+/// Async wrapper for [`crate::write_json_file`].
+///
+/// Takes the object by value, because it is serialized on the blocking thread pool.
+pub async fn write_json_file<T: Serialize + Send + 'static>(
+    path: impl AsRef<Path>,
+    obj: T,
+) -> Result<()> {
+    spawn_blocking(path.as_ref().to_path_buf(), move |path| crate::write_json_file(&path, &obj))
+        .await
+}
+
+/// Async wrapper for [`crate::rename`].
+///
+/// A [`FsPathError::TaskJoin`] error carries the original path.
+pub async fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
+    let to = to.as_ref().to_path_buf();
+    spawn_blocking(from.as_ref().to_path_buf(), move |from| crate::rename(from, to)).await
+}
+
+/// Async wrapper for [`crate::create_dir_all`].
+pub async fn create_dir_all(path: impl AsRef<Path>) -> Result<()> {
+    spawn_blocking(path.as_ref().to_path_buf(), crate::create_dir_all).await
+}
+
+/// Async wrapper for [`crate::remove_file`].
+pub async fn remove_file(path: impl AsRef<Path>) -> Result<()> {
+    spawn_blocking(path.as_ref().to_path_buf(), crate::remove_file).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    type Config = BTreeMap<String, Vec<u64>>;
+
+    #[tokio::test]
+    async fn json_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("config.json");
+        create_dir_all(path.parent().unwrap()).await.unwrap();
+
+        let config = Config::from([("a".to_string(), vec![1, 2]), ("b".to_string(), vec![])]);
+        write_json_file(&path, config.clone()).await.unwrap();
+        assert_eq!(read_json_file::<Config>(&path).await.unwrap(), config);
+        assert_eq!(read_to_string(&path).await.unwrap(), crate::read_to_string(&path).unwrap());
+
+        let renamed = dir.path().join("renamed.json");
+        rename(&path, &renamed).await.unwrap();
+        assert_eq!(read_json_file::<Config>(&renamed).await.unwrap(), config);
+
+        remove_file(&renamed).await.unwrap();
+        let err = read_json_file::<Config>(&renamed).await.unwrap_err();
+        assert!(err.is_not_found());
+        assert_eq!(err, crate::read_json_file::<Config>(&renamed).unwrap_err());
+    }
+
+    #[tokio::test]
+    async fn errors_match_blocking() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        write(&path, b"not json").await.unwrap();
+        assert_eq!(read(&path).await.unwrap(), b"not json");
+
+        let err = read_json_file::<Config>(&path).await.unwrap_err();
+        assert!(matches!(err, FsPathError::ReadJson { .. }));
+        assert_eq!(err, crate::read_json_file::<Config>(&path).unwrap_err());
+
+        let missing = dir.path().join("missing");
+        assert_eq!(
+            rename(&missing, &path).await.unwrap_err(),
+            crate::rename(&missing, &path).unwrap_err()
+        );
+        assert_eq!(
+            remove_file(&missing).await.unwrap_err(),
+            crate::remove_file(&missing).unwrap_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn panicking_task_is_task_join_error() {
+        let path = PathBuf::from("panicking");
+        let err =
+            spawn_blocking::<(), _>(path.clone(), |_| panic!("task panicked")).await.unwrap_err();
+        assert!(matches!(err, FsPathError::TaskJoin { .. }));
+        assert_eq!(err.path(), path);
+    }
+}
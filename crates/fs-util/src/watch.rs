@@ -0,0 +1,277 @@
+//! Polling based file change notifications.
+
+use crate::{FsPathError, Result};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Watches `path` for changes by polling its metadata every `interval` on a background thread.
+///
+/// A change of the modification time or length of the file is reported as a change, as well as
+/// the file being deleted or (re)created. Changes in between two calls of
+/// [`FileWatcher::try_changed`] or [`FileWatcher::wait_changed`] are coalesced into one.
+///
+/// The file does not need to exist when the watcher is created.
+pub fn watch_file(path: impl AsRef<Path>, interval: Duration) -> FileWatcher {
+    let path = path.as_ref().to_path_buf();
+    let shared = Arc::new(Shared::default());
+
+    let mut last = match Fingerprint::read(&path) {
+        Ok(fingerprint) => Some(fingerprint),
+        Err(err) => {
+            shared.lock().last_error = Some(err);
+            None
+        }
+    };
+
+    let thread = {
+        let shared = shared.clone();
+        let path = path.clone();
+        thread::Builder::new()
+            .name("fs-watch".to_string())
+            .spawn(move || loop {
+                let state = shared.lock();
+                let (state, _) = shared
+                    .cond
+                    .wait_timeout_while(state, interval, |state| !state.stop)
+                    .unwrap_or_else(PoisonError::into_inner);
+                if state.stop {
+                    return
+                }
+                drop(state);
+
+                let current = Fingerprint::read(&path);
+                let mut state = shared.lock();
+                match current {
+                    Ok(current) => {
+                        if last.replace(current) != Some(current) {
+                            state.changed = true;
+                        }
+                        state.last_error = None;
+                    }
+                    // a failed poll keeps the last known state, so that changes across errors
+                    // are still detected
+                    Err(err) => state.last_error = Some(err),
+                }
+                drop(state);
+                shared.cond.notify_all();
+            })
+            .expect("failed to spawn file watcher thread")
+    };
+
+    FileWatcher { path, shared, thread: Some(thread) }
+}
+
+/// A handle to a file watched by [`watch_file`].
+///
+/// The background thread is stopped when the watcher is dropped.
+#[derive(Debug)]
+pub struct FileWatcher {
+    path: PathBuf,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FileWatcher {
+    /// Returns whether the file changed since the last call, without blocking.
+    ///
+    /// Pending changes are reported first. Otherwise, if the most recent poll failed, its error is
+    /// returned, see [`Self::last_error`].
+    pub fn try_changed(&self) -> Result<bool> {
+        self.shared.lock().take_changed()
+    }
+
+    /// Blocks until the file changes, a poll fails or `timeout` elapses.
+    ///
+    /// Returns `Ok(false)` if the file did not change within `timeout`. See [`Self::try_changed`]
+    /// for details.
+    pub fn wait_changed(&self, timeout: Duration) -> Result<bool> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.lock();
+        loop {
+            if state.changed || state.last_error.is_some() {
+                return state.take_changed()
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(false)
+            }
+            state = self
+                .shared
+                .cond
+                .wait_timeout(state, remaining)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    /// Returns the error of the most recent poll, if it failed.
+    ///
+    /// This is a [`FsPathError::Metadata`] error and is cleared once a poll succeeds again. A
+    /// missing file is not an error.
+    pub fn last_error(&self) -> Option<FsPathError> {
+        self.shared.lock().last_error.clone()
+    }
+
+    /// Returns the path of the watched file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.shared.lock().stop = true;
+        self.shared.cond.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// State shared between a [`FileWatcher`] and its background thread.
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<State>,
+    cond: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Whether a change was detected that was not reported yet.
+    changed: bool,
+    /// The error of the most recent poll, if it failed.
+    last_error: Option<FsPathError>,
+    /// Set on drop of the [`FileWatcher`] to stop the background thread.
+    stop: bool,
+}
+
+impl State {
+    fn take_changed(&mut self) -> Result<bool> {
+        if std::mem::take(&mut self.changed) {
+            return Ok(true)
+        }
+        match &self.last_error {
+            Some(err) => Err(err.clone()),
+            None => Ok(false),
+        }
+    }
+}
+
+/// The metadata of a file that is compared to detect changes, `None` if the file does not exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fingerprint(Option<FileMeta>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileMeta {
+    modified: Option<SystemTime>,
+    len: u64,
+    /// Detects a file that was replaced within the resolution of the modification time.
+    #[cfg(unix)]
+    ino: u64,
+}
+
+impl Fingerprint {
+    fn read(path: &Path) -> Result<Self> {
+        match fs::metadata(path) {
+            Ok(metadata) => Ok(Self(Some(FileMeta {
+                modified: metadata.modified().ok(),
+                len: metadata.len(),
+                #[cfg(unix)]
+                ino: std::os::unix::fs::MetadataExt::ino(&metadata),
+            }))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self(None)),
+            Err(err) => Err(FsPathError::metadata(err, path)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(10);
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn detects_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, b"a").unwrap();
+
+        let watcher = watch_file(&path, INTERVAL);
+        assert_eq!(watcher.path(), path);
+        assert!(!watcher.try_changed().unwrap());
+        assert!(!watcher.wait_changed(INTERVAL * 5).unwrap());
+
+        fs::write(&path, b"ab").unwrap();
+        assert!(watcher.wait_changed(TIMEOUT).unwrap());
+        // the change is only reported once
+        assert!(!watcher.try_changed().unwrap());
+        assert!(watcher.last_error().is_none());
+    }
+
+    #[test]
+    fn detects_delete_and_recreate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+
+        let watcher = watch_file(&path, INTERVAL);
+        fs::write(&path, b"a").unwrap();
+        assert!(watcher.wait_changed(TIMEOUT).unwrap());
+
+        fs::remove_file(&path).unwrap();
+        assert!(watcher.wait_changed(TIMEOUT).unwrap());
+
+        fs::write(&path, b"a").unwrap();
+        assert!(watcher.wait_changed(TIMEOUT).unwrap());
+        assert!(watcher.last_error().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn surfaces_poll_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let parent = dir.path().join("parent");
+        let file = parent.join("file");
+
+        let watcher = watch_file(&file, INTERVAL);
+        assert!(watcher.last_error().is_none());
+
+        // a file in place of the parent directory fails with `NotADirectory`
+        fs::write(&parent, b"").unwrap();
+        let err = watcher.wait_changed(TIMEOUT).unwrap_err();
+        assert!(matches!(err, FsPathError::Metadata { ref path, .. } if path == &file));
+        assert!(matches!(watcher.last_error(), Some(FsPathError::Metadata { .. })));
+
+        // the watcher recovers once the path is valid again
+        fs::remove_file(&parent).unwrap();
+        fs::create_dir(&parent).unwrap();
+        fs::write(&file, b"a").unwrap();
+        let deadline = Instant::now() + TIMEOUT;
+        while watcher.try_changed().is_err() && Instant::now() < deadline {
+            thread::sleep(INTERVAL);
+        }
+        assert!(watcher.last_error().is_none());
+    }
+
+    #[test]
+    fn drop_stops_thread_promptly() {
+        let dir = tempfile::tempdir().unwrap();
+        let watcher = watch_file(dir.path().join("file"), Duration::from_secs(3600));
+
+        let start = Instant::now();
+        drop(watcher);
+        assert!(start.elapsed() < TIMEOUT);
+    }
+}
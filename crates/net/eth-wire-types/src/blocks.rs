@@ -5,8 +5,9 @@ use crate::HeadersDirection;
 use alloc::vec::Vec;
 use alloy_eips::BlockHashOrNumber;
 use alloy_primitives::B256;
-use alloy_rlp::{RlpDecodable, RlpDecodableWrapper, RlpEncodable, RlpEncodableWrapper};
+use alloy_rlp::{Header, RlpDecodable, RlpDecodableWrapper, RlpEncodable, RlpEncodableWrapper};
 use reth_codecs_derive::{add_arbitrary_tests, generate_tests};
+use reth_primitives_traits::{block::error::BodyDecodeError, BlockBody, BodyDecodeLimits};
 
 /// A request for a peer to return block headers starting at the requested block.
 /// The peer must return at most [`limit`](#structfield.limit) headers.
@@ -89,6 +90,31 @@ impl<B> From<Vec<B>> for BlockBodies<B> {
     }
 }
 
+impl<B: BlockBody> BlockBodies<B> {
+    /// Decodes the block bodies from untrusted RLP, checking each body against the given limits
+    /// before it is decoded.
+    ///
+    /// See also [`BlockBody::decode_with_limits`].
+    pub fn decode_with_limits(
+        buf: &mut &[u8],
+        limits: &BodyDecodeLimits,
+    ) -> Result<Self, BodyDecodeError> {
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(alloy_rlp::Error::UnexpectedString.into())
+        }
+
+        let (mut payload, rest) = buf.split_at(header.payload_length);
+        let mut bodies = Vec::new();
+        while !payload.is_empty() {
+            bodies.push(B::decode_with_limits(&mut payload, limits)?);
+        }
+        *buf = rest;
+
+        Ok(Self(bodies))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -100,6 +126,7 @@ mod tests {
     use alloy_primitives::{hex, PrimitiveSignature as Signature, TxKind, U256};
     use alloy_rlp::{Decodable, Encodable};
     use reth_ethereum_primitives::{BlockBody, Transaction, TransactionSigned};
+    use reth_primitives_traits::{block::error::BodyDecodeError, BodyDecodeLimits};
     use std::str::FromStr;
 
     #[test]
@@ -480,6 +507,31 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn decode_block_bodies_with_limits() {
+        let tx = TransactionSigned::new_unhashed(
+            Transaction::Legacy(TxLegacy::default()),
+            Signature::test_signature(),
+        );
+        let body = BlockBody { transactions: vec![tx.clone(), tx], ..Default::default() };
+        let bodies = BlockBodies(vec![body, BlockBody::default()]);
+        let mut encoded = Vec::new();
+        bodies.encode(&mut encoded);
+        encoded.push(0x01);
+
+        let mut buf = &encoded[..];
+        let decoded =
+            BlockBodies::<BlockBody>::decode_with_limits(&mut buf, &Default::default()).unwrap();
+        assert_eq!(decoded, bodies);
+        assert_eq!(buf, [0x01]);
+
+        let limits = BodyDecodeLimits { max_transactions: 1, ..Default::default() };
+        assert_eq!(
+            BlockBodies::<BlockBody>::decode_with_limits(&mut &encoded[..], &limits),
+            Err(BodyDecodeError::TooManyTransactions { limit: 1 })
+        );
+    }
+
     #[test]
     fn empty_block_bodies_rlp() {
         let body = BlockBodies::default();
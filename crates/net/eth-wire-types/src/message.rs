@@ -16,6 +16,7 @@ use alloc::{boxed::Box, sync::Arc};
 use alloy_primitives::bytes::{Buf, BufMut};
 use alloy_rlp::{length_of_length, Decodable, Encodable, Header};
 use core::fmt::Debug;
+use reth_primitives_traits::{block::error::BodyDecodeError, BodyDecodeLimits};
 
 /// [`MAX_MESSAGE_SIZE`] is the maximum cap on the size of a protocol message.
 // https://github.com/ethereum/go-ethereum/blob/30602163d5d8321fbc68afdcbbaf2362b2641bde/eth/protocols/eth/protocol.go#L50
@@ -30,6 +31,18 @@ pub enum MessageError {
     /// Thrown when rlp decoding a message message failed.
     #[error("RLP error: {0}")]
     RlpError(#[from] alloy_rlp::Error),
+    /// Thrown when a received block body exceeds the [`BodyDecodeLimits`].
+    #[error("block body limit exceeded: {0}")]
+    BlockBodyLimit(BodyDecodeError),
+}
+
+impl From<BodyDecodeError> for MessageError {
+    fn from(err: BodyDecodeError) -> Self {
+        match err {
+            BodyDecodeError::Rlp(err) => Self::RlpError(err),
+            err => Self::BlockBodyLimit(err),
+        }
+    }
 }
 
 /// An `eth` protocol message, containing a message ID and payload.
@@ -80,7 +93,11 @@ impl<N: NetworkPrimitives> ProtocolMessage<N> {
             EthMessageID::GetBlockHeaders => EthMessage::GetBlockHeaders(RequestPair::decode(buf)?),
             EthMessageID::BlockHeaders => EthMessage::BlockHeaders(RequestPair::decode(buf)?),
             EthMessageID::GetBlockBodies => EthMessage::GetBlockBodies(RequestPair::decode(buf)?),
-            EthMessageID::BlockBodies => EthMessage::BlockBodies(RequestPair::decode(buf)?),
+            EthMessageID::BlockBodies => {
+                EthMessage::BlockBodies(RequestPair::decode_with(buf, |buf| {
+                    BlockBodies::decode_with_limits(buf, &BodyDecodeLimits::default())
+                })?)
+            }
             EthMessageID::GetPooledTransactions => {
                 EthMessage::GetPooledTransactions(RequestPair::decode(buf)?)
             }
@@ -483,6 +500,34 @@ pub struct RequestPair<T> {
     pub message: T,
 }
 
+impl<T> RequestPair<T> {
+    /// Decodes a request pair from RLP, decoding the message with the given function.
+    ///
+    /// See also the [`Decodable`] implementation.
+    pub fn decode_with<E>(
+        buf: &mut &[u8],
+        decode: impl FnOnce(&mut &[u8]) -> Result<T, E>,
+    ) -> Result<Self, E>
+    where
+        E: From<alloy_rlp::Error>,
+    {
+        let header = Header::decode(buf)?;
+
+        let initial_length = buf.len();
+        let request_id = u64::decode(buf)?;
+        let message = decode(buf)?;
+
+        // Check that the buffer consumed exactly payload_length bytes after decoding the
+        // RequestPair
+        let consumed_len = initial_length - buf.len();
+        if consumed_len != header.payload_length {
+            return Err(alloy_rlp::Error::UnexpectedLength.into())
+        }
+
+        Ok(Self { request_id, message })
+    }
+}
+
 /// Allows messages with request ids to be serialized into RLP bytes.
 impl<T> Encodable for RequestPair<T>
 where
@@ -512,20 +557,7 @@ where
     T: Decodable,
 {
     fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
-        let header = Header::decode(buf)?;
-
-        let initial_length = buf.len();
-        let request_id = u64::decode(buf)?;
-        let message = T::decode(buf)?;
-
-        // Check that the buffer consumed exactly payload_length bytes after decoding the
-        // RequestPair
-        let consumed_len = initial_length - buf.len();
-        if consumed_len != header.payload_length {
-            return Err(alloy_rlp::Error::UnexpectedLength)
-        }
-
-        Ok(Self { request_id, message })
+        Self::decode_with(buf, T::decode)
     }
 }
 
@@ -537,8 +569,9 @@ mod tests {
         GetNodeData, NodeData, ProtocolMessage,
     };
     use alloy_primitives::hex;
-    use alloy_rlp::{Decodable, Encodable, Error};
+    use alloy_rlp::{Decodable, Encodable, Error, Header};
     use reth_ethereum_primitives::BlockBody;
+    use reth_primitives_traits::{block::error::BodyDecodeError, BodyDecodeLimits};
 
     fn encode<T: Encodable>(value: T) -> Vec<u8> {
         let mut buf = vec![];
@@ -661,4 +694,31 @@ mod tests {
         .unwrap_err();
         assert!(matches!(msg, MessageError::RlpError(alloy_rlp::Error::InputTooShort)));
     }
+
+    #[test]
+    fn decode_block_bodies_message_exceeding_limits() {
+        let list = |payload: Vec<u8>| {
+            let mut out = Vec::new();
+            Header { list: true, payload_length: payload.len() }.encode(&mut out);
+            out.extend(payload);
+            out
+        };
+
+        // a body with one transaction more than the default limit, which are checked before the
+        // (invalid) transactions are decoded
+        let limit = BodyDecodeLimits::default().max_transactions;
+        let bodies = list(list(list(vec![0x01; limit + 1])));
+        let mut buf = vec![EthMessageID::BlockBodies as u8];
+        buf.extend(list([encode(1u64), bodies].concat()));
+
+        let err = ProtocolMessage::<EthNetworkPrimitives>::decode_message(
+            EthVersion::Eth68,
+            &mut &buf[..],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            MessageError::BlockBodyLimit(BodyDecodeError::TooManyTransactions { limit: l }) if l == limit
+        ));
+    }
 }
@@ -423,7 +423,7 @@ impl<N: NetworkPrimitives> NetworkManager<N> {
     /// `persistent_peers_file`.
     pub fn write_peers_to_file(&self, persistent_peers_file: &Path) -> Result<(), FsPathError> {
         let known_peers = self.all_peers().collect::<Vec<_>>();
-        persistent_peers_file.parent().map(fs::create_dir_all).transpose()?;
+        fs::ensure_parent_dirs_exist(persistent_peers_file)?;
         reth_fs_util::write_json_file(persistent_peers_file, &known_peers)?;
         Ok(())
     }
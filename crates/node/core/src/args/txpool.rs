@@ -10,8 +10,9 @@ use reth_transaction_pool::{
     validate::DEFAULT_MAX_TX_INPUT_BYTES,
     LocalTransactionConfig, PoolConfig, PriceBumpConfig, SubPoolLimit, DEFAULT_PRICE_BUMP,
     DEFAULT_TXPOOL_ADDITIONAL_VALIDATION_TASKS, MAX_NEW_PENDING_TXS_NOTIFICATIONS,
-    REPLACE_BLOB_PRICE_BUMP, TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER,
-    TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT, TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
+    REPLACE_BLOB_PRICE_BUMP, TXPOOL_BLOB_SIZE_LIMIT_MB_DEFAULT,
+    TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER, TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT,
+    TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
 };
 /// Parameters for debugging purposes
 #[derive(Debug, Clone, Args, PartialEq, Eq)]
@@ -38,6 +39,10 @@ pub struct TxPoolArgs {
     #[arg(long = "txpool.queued-max-size", alias = "txpool.queued_max_size", default_value_t = TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT)]
     pub queued_max_size: usize,
 
+    /// Max size of the blob sidecars of the blob sub-pool in megabytes.
+    #[arg(long = "txpool.blob-size-limit", alias = "txpool.blob_size_limit", default_value_t = TXPOOL_BLOB_SIZE_LIMIT_MB_DEFAULT)]
+    pub blob_size_limit: usize,
+
     /// Max number of executable transaction slots guaranteed per account
     #[arg(long = "txpool.max-account-slots", alias = "txpool.max_account_slots", default_value_t = TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER)]
     pub max_account_slots: usize,
@@ -102,6 +107,7 @@ impl Default for TxPoolArgs {
             basefee_max_size: TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT,
             queued_max_count: TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
             queued_max_size: TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT,
+            blob_size_limit: TXPOOL_BLOB_SIZE_LIMIT_MB_DEFAULT,
             max_account_slots: TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER,
             price_bump: DEFAULT_PRICE_BUMP,
             minimal_protocol_basefee: MIN_PROTOCOL_BASE_FEE,
@@ -145,6 +151,7 @@ impl RethTransactionPoolConfig for TxPoolArgs {
                 max_txs: self.queued_max_count,
                 max_size: self.queued_max_size.saturating_mul(1024 * 1024),
             },
+            blob_size_limit: self.blob_size_limit.saturating_mul(1024 * 1024),
             max_account_slots: self.max_account_slots,
             price_bumps: PriceBumpConfig {
                 default_price_bump: self.price_bump,
@@ -188,4 +195,16 @@ mod tests {
         .args;
         assert_eq!(args.locals, vec![Address::ZERO]);
     }
+
+    #[test]
+    fn txpool_parse_blob_size_limit() {
+        let config = CommandParser::<TxPoolArgs>::parse_from(["reth"]).args.pool_config();
+        assert_eq!(config.blob_size_limit, PoolConfig::default().blob_size_limit);
+
+        let args =
+            CommandParser::<TxPoolArgs>::parse_from(["reth", "--txpool.blob-size-limit", "100"])
+                .args;
+        assert_eq!(args.blob_size_limit, 100);
+        assert_eq!(args.pool_config().blob_size_limit, 100 * 1024 * 1024);
+    }
 }
@@ -175,6 +175,10 @@ impl PoolTransaction for OpPooledTransaction {
         self.inner.transaction.input().len()
     }
 
+    fn blob_count(&self) -> usize {
+        0
+    }
+
     fn tx_type(&self) -> u8 {
         self.inner.transaction.ty()
     }
@@ -193,10 +197,6 @@ impl EthPoolTransaction for OpPooledTransaction {
         EthBlobTransactionSidecar::None
     }
 
-    fn blob_count(&self) -> usize {
-        0
-    }
-
     fn try_into_pooled_eip4844(
         self,
         _sidecar: Arc<BlobTransactionSidecar>,
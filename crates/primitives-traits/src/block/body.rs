@@ -1,12 +1,12 @@
 //! Block body abstraction.
 
 use crate::{
-    transaction::signed::RecoveryError, BlockHeader, FullSignedTx, InMemorySize, MaybeSerde,
-    MaybeSerdeBincodeCompat, SignedTransaction,
+    block::error::BodyDecodeError, transaction::signed::RecoveryError, BlockHeader, FullSignedTx,
+    InMemorySize, MaybeSerde, MaybeSerdeBincodeCompat, SignedTransaction,
 };
 use alloc::{fmt, vec::Vec};
 use alloy_consensus::{Transaction, Typed2718};
-use alloy_eips::{eip2718::Encodable2718, eip4895::Withdrawals};
+use alloy_eips::{eip2718::Encodable2718, eip4844::MAX_DATA_GAS_PER_BLOCK, eip4895::Withdrawals};
 use alloy_primitives::{Address, Bytes, B256};
 
 /// Helper trait that unifies all behaviour required by transaction to support full node operations.
@@ -39,6 +39,10 @@ pub trait BlockBody:
     /// Ommer header type.
     type OmmerHeader: BlockHeader;
 
+    /// The maximum total blob gas of all EIP-4844 transactions in the block body, see
+    /// [`Self::blob_gas_used`].
+    const MAX_BLOB_GAS_PER_BLOCK: u64 = MAX_DATA_GAS_PER_BLOCK;
+
     /// Returns reference to transactions in the block.
     fn transactions(&self) -> &[Self::Transaction];
 
@@ -54,6 +58,13 @@ pub trait BlockBody:
         self.transactions_iter().find(|tx| tx.tx_hash() == hash)
     }
 
+    /// Returns the index of the transaction with the matching hash.
+    ///
+    /// This is a convenience function for `transactions_iter().position()`
+    fn transaction_index_by_hash(&self, hash: &B256) -> Option<usize> {
+        self.transactions_iter().position(|tx| tx.tx_hash() == hash)
+    }
+
     /// Clones the transactions in the block.
     ///
     /// This is a convenience function for `transactions().to_vec()`
@@ -71,9 +82,42 @@ pub trait BlockBody:
         self.transactions().len()
     }
 
+    /// Returns `true` if the block body contains no transactions.
+    ///
+    /// Note: this does not take ommers and withdrawals into account.
+    fn is_empty(&self) -> bool {
+        self.transactions().is_empty()
+    }
+
     /// Consume the block body and return a [`Vec`] of transactions.
     fn into_transactions(self) -> Vec<Self::Transaction>;
 
+    /// Decodes a block body from untrusted RLP, e.g. received from a peer, failing early if it
+    /// exceeds the given [`BodyDecodeLimits`].
+    ///
+    /// The default implementation checks the encoding with [`BodyDecodeLimits::check`] before
+    /// decoding it, so that an oversized body is rejected before anything is allocated. Bodies
+    /// with a different layout than [`alloy_consensus::BlockBody`] should override this.
+    fn decode_with_limits(
+        buf: &mut &[u8],
+        limits: &BodyDecodeLimits,
+    ) -> Result<Self, BodyDecodeError> {
+        limits.check(buf)?;
+        Ok(<Self as alloy_rlp::Decodable>::decode(buf)?)
+    }
+
+    /// Returns the RLP encoded length of the block body, without encoding it.
+    ///
+    /// Note: unlike [`InMemorySize::size`], this is the size on the wire.
+    fn encoded_size(&self) -> usize {
+        alloy_rlp::Encodable::length(self)
+    }
+
+    /// Returns the sum of the gas limits of all transactions in the block body.
+    fn total_gas_limit(&self) -> u64 {
+        self.transactions_iter().map(|tx| tx.gas_limit()).sum()
+    }
+
     /// Returns `true` if the block body contains a transaction of the given type.
     fn contains_transaction_type(&self, tx_type: u8) -> bool {
         self.transactions_iter().any(|tx| tx.is_type(tx_type))
@@ -85,6 +129,8 @@ pub trait BlockBody:
     }
 
     /// Returns block withdrawals if any.
+    ///
+    /// This is `None` for blocks before Shanghai, and `Some` (possibly empty) after.
     fn withdrawals(&self) -> Option<&Withdrawals>;
 
     /// Calculate the withdrawals root for the block body.
@@ -106,16 +152,49 @@ pub trait BlockBody:
         self.ommers().map(alloy_consensus::proofs::calculate_ommers_root)
     }
 
+    /// Returns an iterator over all EIP-4844 transactions in the block body.
+    fn blob_transactions_iter(&self) -> impl Iterator<Item = &Self::Transaction> + '_ {
+        self.transactions_iter().filter(|tx| tx.is_eip4844())
+    }
+
+    /// Returns all EIP-4844 transactions in the block body.
+    ///
+    /// This is a convenience function for `blob_transactions_iter().collect()`
+    fn blob_transactions(&self) -> Vec<&Self::Transaction> {
+        self.blob_transactions_iter().collect()
+    }
+
     /// Calculates the total blob gas used by _all_ EIP-4844 transactions in the block.
     fn blob_gas_used(&self) -> u64 {
         self.transactions_iter().filter_map(|tx| tx.blob_gas_used()).sum()
     }
 
+    /// Returns `true` if the total blob gas used by the block body exceeds
+    /// [`Self::MAX_BLOB_GAS_PER_BLOCK`].
+    fn exceeds_max_blob_gas(&self) -> bool {
+        self.blob_gas_used() > Self::MAX_BLOB_GAS_PER_BLOCK
+    }
+
     /// Returns an iterator over all blob versioned hashes in the block body.
     fn blob_versioned_hashes_iter(&self) -> impl Iterator<Item = &B256> + '_ {
         self.transactions_iter().filter_map(|tx| tx.blob_versioned_hashes()).flatten()
     }
 
+    /// Returns the number of blobs of _all_ EIP-4844 transactions in the block.
+    ///
+    /// The sidecars are not part of the block body, so this counts the blob versioned hashes, one
+    /// per blob.
+    fn num_blobs(&self) -> usize {
+        self.blob_versioned_hashes_iter().count()
+    }
+
+    /// Returns all blob versioned hashes in the block body, in transaction order.
+    ///
+    /// This is a convenience function for `blob_versioned_hashes_iter().collect()`
+    fn blob_versioned_hashes(&self) -> Vec<&B256> {
+        self.blob_versioned_hashes_iter().collect()
+    }
+
     /// Returns an iterator over the encoded 2718 transactions.
     ///
     /// This is also known as `raw transactions`.
@@ -165,6 +244,17 @@ pub trait BlockBody:
         crate::transaction::recover::recover_signers_unchecked(self.transactions())
     }
 
+    /// Returns an iterator over the transactions in the block body paired with the given senders,
+    /// e.g. as returned by [`Self::recover_signers`].
+    ///
+    /// Returns `None` if the number of senders doesn't match the number of transactions.
+    fn transactions_with_senders<'a>(
+        &'a self,
+        senders: &'a [Address],
+    ) -> Option<impl Iterator<Item = (&'a Self::Transaction, &'a Address)> + 'a> {
+        (self.transaction_count() == senders.len()).then(|| self.transactions_iter().zip(senders))
+    }
+
     /// Recover signer addresses for all transactions in the block body _without ensuring that the
     /// signature has a low `s` value_.
     ///
@@ -202,6 +292,102 @@ where
     }
 }
 
+/// Limits for decoding an untrusted block body, see [`BlockBody::decode_with_limits`].
+///
+/// The [`Default`] limits are generous enough for any valid block body received over the network,
+/// whose messages are capped at 10 MiB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyDecodeLimits {
+    /// Maximum number of transactions in the block body.
+    pub max_transactions: usize,
+    /// Maximum size of a single encoded transaction.
+    pub max_tx_size: usize,
+    /// Maximum size of the encoded block body.
+    pub max_total_size: usize,
+}
+
+impl BodyDecodeLimits {
+    /// Checks the encoded block body at the start of `buf` against the limits, without decoding
+    /// or allocating anything.
+    ///
+    /// This expects the layout of [`alloy_consensus::BlockBody`]: a list whose first item is the
+    /// list of transactions. The remaining items are not checked.
+    pub fn check(&self, mut buf: &[u8]) -> Result<(), BodyDecodeError> {
+        let header = decode_list_header(&mut buf)?;
+        let size = header.length_with_payload();
+        if size > self.max_total_size {
+            return Err(BodyDecodeError::BodyTooLarge { size, limit: self.max_total_size })
+        }
+
+        let header = decode_list_header(&mut buf)?;
+        let mut transactions = &buf[..header.payload_length];
+        let mut index = 0;
+        while !transactions.is_empty() {
+            if index == self.max_transactions {
+                return Err(BodyDecodeError::TooManyTransactions { limit: self.max_transactions })
+            }
+
+            let remaining = transactions.len();
+            let header = alloy_rlp::Header::decode(&mut transactions)?;
+            transactions = &transactions[header.payload_length..];
+            let size = remaining - transactions.len();
+            if size > self.max_tx_size {
+                return Err(BodyDecodeError::TransactionTooLarge {
+                    index,
+                    size,
+                    limit: self.max_tx_size,
+                })
+            }
+            index += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for BodyDecodeLimits {
+    fn default() -> Self {
+        Self { max_transactions: 1 << 20, max_tx_size: 16 << 20, max_total_size: 16 << 20 }
+    }
+}
+
+/// Decodes the header of an RLP list, which is checked to fit into `buf`.
+fn decode_list_header(buf: &mut &[u8]) -> alloy_rlp::Result<alloy_rlp::Header> {
+    let header = alloy_rlp::Header::decode(buf)?;
+    if !header.list {
+        return Err(alloy_rlp::Error::UnexpectedString)
+    }
+    Ok(header)
+}
+
+/// A [`BlockBody`] that can be rebuilt with a subset of its transactions.
+///
+/// This is a separate trait, because constructing a body requires knowledge of the concrete type.
+pub trait FilterableBlockBody: BlockBody {
+    /// Returns a copy of the body that only contains the transactions matching `predicate`, in
+    /// their original order. All other fields, such as the ommers and withdrawals, are kept.
+    fn filter_transactions<F>(&self, predicate: F) -> Self
+    where
+        F: Fn(&Self::Transaction) -> bool;
+}
+
+impl<T, H> FilterableBlockBody for alloy_consensus::BlockBody<T, H>
+where
+    T: SignedTransaction,
+    H: BlockHeader,
+{
+    fn filter_transactions<F>(&self, predicate: F) -> Self
+    where
+        F: Fn(&Self::Transaction) -> bool,
+    {
+        Self {
+            transactions: self.transactions.iter().filter(|tx| predicate(tx)).cloned().collect(),
+            ommers: self.ommers.clone(),
+            withdrawals: self.withdrawals.clone(),
+        }
+    }
+}
+
 /// This is a helper alias to make it easy to refer to the inner `Transaction` associated type of a
 /// given type that implements [`BlockBody`].
 pub type BodyTx<N> = <N as BlockBody>::Transaction;
@@ -209,3 +395,310 @@ pub type BodyTx<N> = <N as BlockBody>::Transaction;
 /// This is a helper alias to make it easy to refer to the inner `OmmerHeader` associated type of a
 /// given type that implements [`BlockBody`].
 pub type BodyOmmer<N> = <N as BlockBody>::OmmerHeader;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{
+        transaction::PooledTransaction, Signed, TxEip1559, TxEip4844, TxEip4844WithSidecar,
+        TxLegacy,
+    };
+    use alloy_eips::{
+        eip4844::{BlobTransactionSidecar, DATA_GAS_PER_BLOB},
+        eip4895::Withdrawal,
+    };
+    use alloy_primitives::PrimitiveSignature as Signature;
+    use proptest::{prelude::ProptestConfig, prop_assert_eq, proptest};
+    use proptest_arbitrary_interop::arb;
+
+    fn legacy_tx() -> PooledTransaction {
+        Signed::new_unchecked(TxLegacy::default(), Signature::test_signature(), B256::random())
+            .into()
+    }
+
+    fn blob_tx(hashes: Vec<B256>) -> PooledTransaction {
+        let tx = TxEip4844 { blob_versioned_hashes: hashes, ..Default::default() };
+        let tx = TxEip4844WithSidecar { tx, sidecar: BlobTransactionSidecar::default() };
+        Signed::new_unchecked(tx, Signature::test_signature(), B256::random()).into()
+    }
+
+    #[test]
+    fn blob_helpers_mixed_body() {
+        let eip1559 = Signed::new_unchecked(
+            TxEip1559::default(),
+            Signature::test_signature(),
+            B256::random(),
+        );
+        let (a, b, c) = (B256::with_last_byte(1), B256::with_last_byte(2), B256::with_last_byte(3));
+        let first_blob = blob_tx(vec![a, b]);
+        let second_blob = blob_tx(vec![c]);
+
+        let body = alloy_consensus::BlockBody::<_, alloy_consensus::Header> {
+            transactions: vec![
+                legacy_tx(),
+                first_blob.clone(),
+                eip1559.into(),
+                second_blob.clone(),
+            ],
+            ommers: Vec::new(),
+            withdrawals: None,
+        };
+
+        assert_eq!(body.transaction_count(), 4);
+        assert!(!body.is_empty());
+        assert_eq!(body.blob_transactions(), vec![&first_blob, &second_blob]);
+        assert_eq!(body.blob_versioned_hashes(), vec![&a, &b, &c]);
+        assert_eq!(body.num_blobs(), 3);
+        assert_eq!(body.blob_gas_used(), 3 * DATA_GAS_PER_BLOB);
+        assert!(!body.exceeds_max_blob_gas());
+    }
+
+    #[test]
+    fn filter_transactions() {
+        let (a, b) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let (first_blob, second_blob) = (blob_tx(vec![a]), blob_tx(vec![b]));
+        let legacy = legacy_tx();
+        let withdrawals =
+            Withdrawals::new(vec![Withdrawal { index: 1, amount: 2, ..Default::default() }]);
+        let body = alloy_consensus::BlockBody {
+            transactions: vec![first_blob.clone(), legacy.clone(), second_blob.clone()],
+            ommers: vec![alloy_consensus::Header { number: 1, ..Default::default() }],
+            withdrawals: Some(withdrawals),
+        };
+
+        let blobs = body.filter_transactions(|tx| tx.is_eip4844());
+        assert_eq!(blobs.transactions, vec![first_blob, second_blob]);
+        assert_eq!(blobs.ommers, body.ommers);
+        assert_eq!(blobs.withdrawals, body.withdrawals);
+
+        assert_eq!(body.filter_transactions(|tx| !tx.is_eip4844()).transactions, vec![legacy]);
+        assert_eq!(body.filter_transactions(|_| true), body);
+        assert!(body.filter_transactions(|_| false).is_empty());
+    }
+
+    #[test]
+    fn decode_with_limits() {
+        type Body = alloy_consensus::BlockBody<PooledTransaction, alloy_consensus::Header>;
+        let legacy = legacy_tx();
+        let blob = blob_tx(vec![B256::with_last_byte(1)]);
+        let body = Body {
+            transactions: vec![legacy.clone(), blob.clone(), legacy],
+            ommers: vec![alloy_consensus::Header::default()],
+            withdrawals: Some(Withdrawals::new(vec![Withdrawal::default()])),
+        };
+        let encoded = alloy_rlp::encode(&body);
+        // the transaction hashes are only valid after a roundtrip
+        let body = <Body as alloy_rlp::Decodable>::decode(&mut &encoded[..]).unwrap();
+        let decode =
+            |limits: BodyDecodeLimits| Body::decode_with_limits(&mut &encoded[..], &limits);
+
+        let mut buf = &encoded[..];
+        assert_eq!(Body::decode_with_limits(&mut buf, &Default::default()).unwrap(), body);
+        assert!(buf.is_empty());
+
+        let limits = BodyDecodeLimits {
+            max_transactions: 3,
+            max_tx_size: alloy_rlp::Encodable::length(&blob),
+            max_total_size: encoded.len(),
+        };
+        assert_eq!(decode(limits).unwrap(), body);
+
+        assert_eq!(
+            decode(BodyDecodeLimits { max_transactions: 2, ..limits }),
+            Err(BodyDecodeError::TooManyTransactions { limit: 2 })
+        );
+        assert_eq!(
+            decode(BodyDecodeLimits { max_tx_size: limits.max_tx_size - 1, ..limits }),
+            Err(BodyDecodeError::TransactionTooLarge {
+                index: 1,
+                size: limits.max_tx_size,
+                limit: limits.max_tx_size - 1
+            })
+        );
+        assert_eq!(
+            decode(BodyDecodeLimits { max_total_size: encoded.len() - 1, ..limits }),
+            Err(BodyDecodeError::BodyTooLarge { size: encoded.len(), limit: encoded.len() - 1 })
+        );
+    }
+
+    #[test]
+    fn decode_with_limits_fails_before_decoding() {
+        type Body = alloy_consensus::BlockBody<PooledTransaction, alloy_consensus::Header>;
+        let limits = BodyDecodeLimits { max_transactions: 10, ..Default::default() };
+        let decode = |buf: &[u8]| Body::decode_with_limits(&mut &buf[..], &limits);
+
+        // a body declaring a length of 4 GiB
+        assert_eq!(
+            decode(&[0xfb, 0xff, 0xff, 0xff, 0xff, 0xc0]),
+            Err(BodyDecodeError::Rlp(alloy_rlp::Error::InputTooShort))
+        );
+
+        // a transaction list declaring a length of 4 GiB
+        assert_eq!(
+            decode(&[0xc6, 0xfb, 0xff, 0xff, 0xff, 0xff, 0xc0]),
+            Err(BodyDecodeError::Rlp(alloy_rlp::Error::InputTooShort))
+        );
+
+        // the transaction count is checked before the transactions are decoded, so this fails with
+        // the limit instead of the invalid transactions
+        let mut buf = Vec::new();
+        alloy_rlp::Header { list: true, payload_length: 1005 }.encode(&mut buf);
+        alloy_rlp::Header { list: true, payload_length: 1000 }.encode(&mut buf);
+        buf.extend([0x01; 1000]);
+        buf.extend([0xc0, 0xc0]);
+        assert_eq!(decode(&buf), Err(BodyDecodeError::TooManyTransactions { limit: 10 }));
+        assert!(<Body as alloy_rlp::Decodable>::decode(&mut &buf[..]).is_err());
+    }
+
+    #[test]
+    fn max_blob_gas_per_block() {
+        type Body = alloy_consensus::BlockBody<PooledTransaction, alloy_consensus::Header>;
+        assert_eq!(Body::MAX_BLOB_GAS_PER_BLOCK, 786_432);
+
+        let max_blobs = Body::MAX_BLOB_GAS_PER_BLOCK / DATA_GAS_PER_BLOB;
+        let mut body = Body {
+            transactions: vec![blob_tx(vec![B256::ZERO; max_blobs as usize])],
+            ..Default::default()
+        };
+        assert_eq!(body.blob_gas_used(), Body::MAX_BLOB_GAS_PER_BLOCK);
+        assert!(!body.exceeds_max_blob_gas());
+
+        body.transactions.push(blob_tx(vec![B256::ZERO]));
+        assert!(body.exceeds_max_blob_gas());
+    }
+
+    #[test]
+    fn transaction_accessors() {
+        let transactions = vec![legacy_tx(), blob_tx(vec![B256::with_last_byte(1)])];
+        let hashes = transactions.iter().map(|tx| *tx.tx_hash()).collect::<Vec<_>>();
+        let body = alloy_consensus::BlockBody::<_, alloy_consensus::Header> {
+            transactions: transactions.clone(),
+            ommers: Vec::new(),
+            withdrawals: None,
+        };
+
+        assert_eq!(body.transaction_hashes_iter().copied().collect::<Vec<_>>(), hashes);
+        assert_eq!(body.transaction_by_hash(&hashes[1]), Some(&transactions[1]));
+        assert_eq!(body.transaction_by_hash(&B256::ZERO), None);
+        assert_eq!(body.transaction_index_by_hash(&hashes[0]), Some(0));
+        assert_eq!(body.transaction_index_by_hash(&hashes[1]), Some(1));
+        assert_eq!(body.transaction_index_by_hash(&B256::ZERO), None);
+        assert_eq!(body.into_transactions(), transactions);
+    }
+
+    #[test]
+    fn blob_helpers_without_blob_transactions() {
+        let body = alloy_consensus::BlockBody::<PooledTransaction, alloy_consensus::Header> {
+            transactions: vec![legacy_tx()],
+            ommers: Vec::new(),
+            withdrawals: None,
+        };
+
+        assert!(body.blob_transactions().is_empty());
+        assert!(body.blob_versioned_hashes().is_empty());
+        assert_eq!(body.blob_gas_used(), 0);
+    }
+
+    #[test]
+    fn total_gas_limit() {
+        let legacy = Signed::new_unchecked(
+            TxLegacy { gas_limit: 21_000, ..Default::default() },
+            Signature::test_signature(),
+            B256::random(),
+        );
+        let eip1559 = Signed::new_unchecked(
+            TxEip1559 { gas_limit: 100_000, ..Default::default() },
+            Signature::test_signature(),
+            B256::random(),
+        );
+        let body = alloy_consensus::BlockBody::<PooledTransaction, alloy_consensus::Header> {
+            transactions: vec![legacy.into(), eip1559.into()],
+            ommers: Vec::new(),
+            withdrawals: None,
+        };
+        assert_eq!(body.total_gas_limit(), 121_000);
+        let empty =
+            alloy_consensus::BlockBody::<PooledTransaction, alloy_consensus::Header>::default();
+        assert_eq!(empty.total_gas_limit(), 0);
+        assert!(empty.is_empty());
+        assert_eq!(empty.transaction_count(), 0);
+    }
+
+    #[test]
+    fn withdrawals_pre_and_post_shanghai() {
+        let mut body =
+            alloy_consensus::BlockBody::<PooledTransaction, alloy_consensus::Header>::default();
+        assert_eq!(body.withdrawals(), None);
+        assert_eq!(body.calculate_withdrawals_root(), None);
+
+        body.withdrawals = Some(Withdrawals::default());
+        assert_eq!(body.withdrawals().map(|w| w.len()), Some(0));
+        assert_eq!(body.calculate_withdrawals_root(), Some(alloy_trie::EMPTY_ROOT_HASH));
+
+        let withdrawal = Withdrawal {
+            index: 1,
+            validator_index: 2,
+            address: Address::with_last_byte(3),
+            amount: 4,
+        };
+        body.withdrawals = Some(Withdrawals::new(vec![withdrawal]));
+        assert_eq!(body.withdrawals().map(|w| w.as_slice()), Some(&[withdrawal][..]));
+        assert_eq!(
+            body.calculate_withdrawals_root(),
+            Some(alloy_consensus::proofs::calculate_withdrawals_root(&[withdrawal]))
+        );
+    }
+
+    #[test]
+    fn transactions_with_senders() {
+        let body = alloy_consensus::BlockBody::<PooledTransaction, alloy_consensus::Header> {
+            transactions: vec![legacy_tx(), blob_tx(vec![B256::with_last_byte(1)])],
+            ..Default::default()
+        };
+        let senders = [Address::with_last_byte(1), Address::with_last_byte(2)];
+
+        let pairs = body.transactions_with_senders(&senders).unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            vec![(&body.transactions[0], &senders[0]), (&body.transactions[1], &senders[1])]
+        );
+        assert!(body.transactions_with_senders(&senders[..1]).is_none());
+        assert!(body.transactions_with_senders(&[Address::ZERO; 3]).is_none());
+
+        let empty =
+            alloy_consensus::BlockBody::<PooledTransaction, alloy_consensus::Header>::default();
+        assert_eq!(empty.transactions_with_senders(&[]).unwrap().count(), 0);
+        assert!(empty.transactions_with_senders(&senders).is_none());
+    }
+
+    proptest! {
+        // bodies with blob sidecars are expensive to generate
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn encoded_size_matches_encoding(
+            body in arb::<alloy_consensus::BlockBody<PooledTransaction, alloy_consensus::Header>>()
+        ) {
+            let mut buf = Vec::new();
+            alloy_rlp::Encodable::encode(&body, &mut buf);
+            prop_assert_eq!(body.encoded_size(), buf.len());
+        }
+
+        #[test]
+        fn recover_signers_matches_recover_signer(
+            body in arb::<alloy_consensus::BlockBody<PooledTransaction, alloy_consensus::Header>>()
+        ) {
+            let signers = body
+                .transactions_iter()
+                .map(|tx| tx.recover_signer())
+                .collect::<Result<Vec<_>, _>>()
+                .ok();
+            let recovered = body.recover_signers().ok();
+            prop_assert_eq!(&recovered, &signers);
+            if let Some(recovered) = recovered {
+                let pairs = body.transactions_with_senders(&recovered).unwrap();
+                prop_assert_eq!(pairs.count(), body.transaction_count());
+            }
+        }
+    }
+}
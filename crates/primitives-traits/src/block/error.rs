@@ -31,3 +31,43 @@ impl<T> From<BlockRecoveryError<T>> for RecoveryError {
         Self
     }
 }
+
+/// Error returned by [`BlockBody::decode_with_limits`](crate::BlockBody::decode_with_limits) if
+/// the encoded block body exceeds the
+/// [`BodyDecodeLimits`](crate::block::body::BodyDecodeLimits) or is invalid RLP.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BodyDecodeError {
+    /// The encoded block body is larger than the limit.
+    #[error("block body of {size} bytes exceeds the limit of {limit} bytes")]
+    BodyTooLarge {
+        /// The size of the encoded block body.
+        size: usize,
+        /// The maximum size of an encoded block body.
+        limit: usize,
+    },
+    /// The block body contains more transactions than the limit.
+    #[error("block body exceeds the limit of {limit} transactions")]
+    TooManyTransactions {
+        /// The maximum number of transactions in a block body.
+        limit: usize,
+    },
+    /// An encoded transaction of the block body is larger than the limit.
+    #[error("transaction {index} of {size} bytes exceeds the limit of {limit} bytes")]
+    TransactionTooLarge {
+        /// The index of the transaction in the block body.
+        index: usize,
+        /// The size of the encoded transaction.
+        size: usize,
+        /// The maximum size of an encoded transaction.
+        limit: usize,
+    },
+    /// The block body is not valid RLP.
+    #[error("failed to decode block body: {0}")]
+    Rlp(alloy_rlp::Error),
+}
+
+impl From<alloy_rlp::Error> for BodyDecodeError {
+    fn from(err: alloy_rlp::Error) -> Self {
+        Self::Rlp(err)
+    }
+}
@@ -116,7 +116,7 @@ pub trait Block:
     /// Returns the rlp length of the block with the given header and body.
     fn rlp_length(header: &Self::Header, body: &Self::Body) -> usize {
         // TODO(mattsse): replace default impl with <https://github.com/alloy-rs/alloy/pull/1906>
-        header.length() + body.length()
+        header.length() + body.encoded_size()
     }
 
     /// Expensive operation that recovers transaction signer.
@@ -255,3 +255,56 @@ where
         self.header = header
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{transaction::PooledTransaction, Header, Signed, TxLegacy};
+    use alloy_primitives::{PrimitiveSignature as Signature, U256};
+
+    type TestBlock = alloy_consensus::Block<PooledTransaction>;
+
+    fn legacy_tx(nonce: u64, signature: Signature) -> PooledTransaction {
+        let tx = TxLegacy { nonce, ..Default::default() };
+        Signed::new_unchecked(tx, signature, B256::random()).into()
+    }
+
+    #[test]
+    fn split_and_join_block() {
+        let header = Header { number: 1, ..Default::default() };
+        let body = alloy_consensus::BlockBody {
+            transactions: vec![legacy_tx(0, Signature::test_signature())],
+            ommers: Vec::new(),
+            withdrawals: None,
+        };
+        let block = <TestBlock as Block>::new(header, body);
+
+        let (header, body) = block.clone().split();
+        assert_eq!(<TestBlock as Block>::new(header, body), block);
+    }
+
+    #[test]
+    fn try_into_recovered_invalid_signature() {
+        let invalid = Signature::new(U256::ZERO, U256::ZERO, false);
+        let body = alloy_consensus::BlockBody {
+            transactions: vec![
+                legacy_tx(0, Signature::test_signature()),
+                legacy_tx(1, invalid),
+                legacy_tx(2, Signature::test_signature()),
+            ],
+            ommers: Vec::new(),
+            withdrawals: None,
+        };
+        let block = <TestBlock as Block>::new(Header::default(), body);
+
+        // the block is returned intact
+        let err = block.clone().try_into_recovered().unwrap_err();
+        assert_eq!(err.into_inner(), block);
+
+        let mut valid = block;
+        valid.body.transactions.remove(1);
+        let recovered = valid.clone().try_into_recovered().unwrap();
+        assert_eq!(recovered.senders(), valid.recover_signers().unwrap());
+        assert_eq!(recovered.into_block(), valid);
+    }
+}
@@ -94,7 +94,7 @@ pub use transaction::{
 
 pub mod block;
 pub use block::{
-    body::{BlockBody, FullBlockBody},
+    body::{BlockBody, BodyDecodeLimits, FilterableBlockBody, FullBlockBody},
     header::{BlockHeader, FullBlockHeader},
     Block, FullBlock, RecoveredBlock, SealedBlock,
 };
@@ -56,13 +56,14 @@ where
             Ok(())
         }
 
-        let AllPoolTransactions { pending, queued } = self.pool.all_transactions();
+        let AllPoolTransactions { pending, queued, blob } = self.pool.all_transactions();
 
         let mut content = TxpoolContent { pending: BTreeMap::new(), queued: BTreeMap::new() };
         for pending in pending {
             insert::<_, Eth>(&pending.transaction, &mut content.pending, &self.tx_resp_builder)?;
         }
-        for queued in queued {
+        // parked blob transactions are reported as queued
+        for queued in queued.into_iter().chain(blob) {
             insert::<_, Eth>(&queued.transaction, &mut content.queued, &self.tx_resp_builder)?;
         }
 
@@ -84,7 +85,10 @@ where
     async fn txpool_status(&self) -> RpcResult<TxpoolStatus> {
         trace!(target: "rpc::eth", "Serving txpool_status");
         let all = self.pool.all_transactions();
-        Ok(TxpoolStatus { pending: all.pending.len() as u64, queued: all.queued.len() as u64 })
+        Ok(TxpoolStatus {
+            pending: all.pending.len() as u64,
+            queued: (all.queued.len() + all.blob.len()) as u64,
+        })
     }
 
     /// Returns a summary of all the transactions currently pending for inclusion in the next
@@ -106,14 +110,14 @@ where
             entry.insert(tx.nonce().to_string(), tx.into_tx().into());
         }
 
-        let AllPoolTransactions { pending, queued } = self.pool.all_transactions();
+        let AllPoolTransactions { pending, queued, blob } = self.pool.all_transactions();
 
         Ok(TxpoolInspect {
             pending: pending.iter().fold(Default::default(), |mut acc, tx| {
                 insert(&tx.transaction, &mut acc);
                 acc
             }),
-            queued: queued.iter().fold(Default::default(), |mut acc, tx| {
+            queued: queued.iter().chain(&blob).fold(Default::default(), |mut acc, tx| {
                 insert(&tx.transaction, &mut acc);
                 acc
             }),
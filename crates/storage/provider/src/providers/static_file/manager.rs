@@ -319,18 +319,14 @@ impl<N: NodePrimitives> StaticFileProvider<N> {
 
                 entries += jar_provider.rows();
 
-                let data_size = reth_fs_util::metadata(jar_provider.data_path())
-                    .map(|metadata| metadata.len())
-                    .unwrap_or_default();
-                let index_size = reth_fs_util::metadata(jar_provider.index_path())
-                    .map(|metadata| metadata.len())
-                    .unwrap_or_default();
-                let offsets_size = reth_fs_util::metadata(jar_provider.offsets_path())
-                    .map(|metadata| metadata.len())
-                    .unwrap_or_default();
-                let config_size = reth_fs_util::metadata(jar_provider.config_path())
-                    .map(|metadata| metadata.len())
-                    .unwrap_or_default();
+                let data_size =
+                    reth_fs_util::file_size(jar_provider.data_path()).unwrap_or_default();
+                let index_size =
+                    reth_fs_util::file_size(jar_provider.index_path()).unwrap_or_default();
+                let offsets_size =
+                    reth_fs_util::file_size(jar_provider.offsets_path()).unwrap_or_default();
+                let config_size =
+                    reth_fs_util::file_size(jar_provider.config_path()).unwrap_or_default();
 
                 size += data_size + index_size + offsets_size + config_size;
             }
@@ -3,7 +3,10 @@ use crate::{
     PoolSize, TransactionOrigin,
 };
 use alloy_consensus::constants::EIP4844_TX_TYPE_ID;
-use alloy_eips::eip1559::{ETHEREUM_BLOCK_GAS_LIMIT, MIN_PROTOCOL_BASE_FEE};
+use alloy_eips::{
+    eip1559::{ETHEREUM_BLOCK_GAS_LIMIT, MIN_PROTOCOL_BASE_FEE},
+    eip4844::BYTES_PER_BLOB,
+};
 use alloy_primitives::Address;
 use std::{collections::HashSet, ops::Mul};
 
@@ -16,6 +19,11 @@ pub const TXPOOL_SUBPOOL_MAX_TXS_DEFAULT: usize = 10_000;
 /// The default maximum allowed size of the given subpool.
 pub const TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT: usize = 20;
 
+/// The default maximum allowed size of the blob sidecars of the blob subpool, which fits a single
+/// blob for each of the [`TXPOOL_SUBPOOL_MAX_TXS_DEFAULT`] transactions of the blob subpool.
+pub const TXPOOL_BLOB_SIZE_LIMIT_MB_DEFAULT: usize =
+    TXPOOL_SUBPOOL_MAX_TXS_DEFAULT * BYTES_PER_BLOB / (1024 * 1024);
+
 /// The default additional validation tasks size.
 pub const DEFAULT_TXPOOL_ADDITIONAL_VALIDATION_TASKS: usize = 1;
 
@@ -41,6 +49,10 @@ pub struct PoolConfig {
     pub queued_limit: SubPoolLimit,
     /// Max number of transactions in the blob sub-pool
     pub blob_limit: SubPoolLimit,
+    /// Max estimated size in bytes of the blob sidecars of all transactions in the blob sub-pool.
+    ///
+    /// The sidecars are kept in the blob store and are not accounted for by `blob_limit`.
+    pub blob_size_limit: usize,
     /// Max number of executable transaction slots guaranteed per account
     pub max_account_slots: usize,
     /// Price bump (in %) for the transaction pool underpriced check.
@@ -78,6 +90,7 @@ impl Default for PoolConfig {
             basefee_limit: Default::default(),
             queued_limit: Default::default(),
             blob_limit: Default::default(),
+            blob_size_limit: TXPOOL_BLOB_SIZE_LIMIT_MB_DEFAULT * 1024 * 1024,
             max_account_slots: TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER,
             price_bumps: Default::default(),
             minimal_protocol_basefee: MIN_PROTOCOL_BASE_FEE,
@@ -155,14 +155,15 @@ pub use crate::{
     config::{
         LocalTransactionConfig, PoolConfig, PriceBumpConfig, SubPoolLimit, DEFAULT_PRICE_BUMP,
         DEFAULT_TXPOOL_ADDITIONAL_VALIDATION_TASKS, MAX_NEW_PENDING_TXS_NOTIFICATIONS,
-        REPLACE_BLOB_PRICE_BUMP, TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER,
-        TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT, TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
+        REPLACE_BLOB_PRICE_BUMP, TXPOOL_BLOB_SIZE_LIMIT_MB_DEFAULT,
+        TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER, TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT,
+        TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
     },
     error::PoolResult,
     ordering::{CoinbaseTipOrdering, Priority, TransactionOrdering},
     pool::{
         blob_tx_priority, fee_delta, state::SubPool, AllTransactionsEvents, FullTransactionEvent,
-        TransactionEvent, TransactionEvents,
+        PersistedBlobTx, TransactionEvent, TransactionEvents,
     },
     traits::*,
     validate::{
@@ -460,6 +461,10 @@ where
         self.pool.all_transactions()
     }
 
+    fn persisted_blob_transactions(&self) -> Vec<PersistedBlobTx> {
+        self.pool.persisted_blob_transactions()
+    }
+
     fn remove_transactions(
         &self,
         hashes: Vec<TxHash>,
@@ -58,12 +58,23 @@ impl Default for MaintainPoolConfig {
 pub struct LocalTransactionBackupConfig {
     /// Path to transactions backup file
     pub transactions_path: Option<PathBuf>,
+    /// Path to the backup file of the parked blob transactions
+    pub blob_transactions_path: Option<PathBuf>,
 }
 
 impl LocalTransactionBackupConfig {
     /// Receive path to transactions backup and return initialized config
     pub const fn with_local_txs_backup(transactions_path: PathBuf) -> Self {
-        Self { transactions_path: Some(transactions_path) }
+        Self { transactions_path: Some(transactions_path), blob_transactions_path: None }
+    }
+
+    /// Also backs up the parked blob transactions to the given path on shutdown, see
+    /// [`TransactionPool::persisted_blob_transactions`].
+    ///
+    /// Only the transactions are persisted, their sidecars are kept in the blob store.
+    pub fn with_blob_txs_backup(mut self, blob_transactions_path: PathBuf) -> Self {
+        self.blob_transactions_path = Some(blob_transactions_path);
+        self
     }
 }
 
@@ -616,9 +627,9 @@ where
     let mut buf = Vec::new();
     alloy_rlp::encode_list(&local_transactions, &mut buf);
     info!(target: "txpool", txs_file =?file_path, num_txs=%num_txs, "Saving current local transactions");
-    let parent_dir = file_path.parent().map(std::fs::create_dir_all).transpose();
-
-    match parent_dir.map(|_| reth_fs_util::write(file_path, buf)) {
+    match reth_fs_util::ensure_parent_dirs_exist(file_path)
+        .and_then(|_| reth_fs_util::write(file_path, buf))
+    {
         Ok(_) => {
             info!(target: "txpool", txs_file=?file_path, "Wrote local transactions to file");
         }
@@ -628,6 +639,88 @@ where
     }
 }
 
+/// Loads the parked blob transactions from a JSON lines file and reinserts them into the
+/// transaction pool on node boot up, in the order they were originally submitted.
+///
+/// Entries that can't be decoded are skipped, as are transactions whose sidecars are no longer in
+/// the blob store. The sidecars are reattached and the transactions are validated again.
+/// The file is removed after the transactions have been processed.
+#[cfg(feature = "serde")]
+async fn load_and_reinsert_blob_transactions<P>(
+    pool: P,
+    file_path: &Path,
+) -> Result<(), TransactionsBackupError>
+where
+    P: TransactionPool<Transaction: EthPoolTransaction<Consensus: SignedTransaction>>,
+{
+    use alloy_eips::eip2718::Decodable2718;
+
+    if !file_path.exists() {
+        return Ok(())
+    }
+
+    debug!(target: "txpool", txs_file =?file_path, "Check local persistent storage for saved blob transactions");
+    let mut persisted = Vec::new();
+    for entry in reth_fs_util::read_json_lines::<crate::PersistedBlobTx>(file_path)? {
+        match entry {
+            Ok(entry) => persisted.push(entry),
+            Err(err) => {
+                warn!(target: "txpool", %err, "Skipping invalid blob transaction backup entry")
+            }
+        }
+    }
+
+    let pool_transactions = persisted
+        .into_iter()
+        .filter_map(|tx| {
+            <P::Transaction as PoolTransaction>::Consensus::decode_2718(
+                &mut tx.transaction.as_ref(),
+            )
+            .ok()
+        })
+        .filter_map(|tx| tx.try_clone_into_recovered().ok())
+        .filter_map(|tx| {
+            let sidecar = pool.get_blob(*tx.tx_hash()).ok().flatten()?;
+            <P::Transaction as EthPoolTransaction>::try_from_eip4844(
+                tx,
+                Arc::unwrap_or_clone(sidecar),
+            )
+        })
+        .collect();
+
+    let outcome =
+        pool.add_transactions(crate::TransactionOrigin::External, pool_transactions).await;
+
+    info!(target: "txpool", txs_file =?file_path, num_txs=%outcome.len(), "Successfully reinserted blob transactions from file");
+    reth_fs_util::remove_file(file_path)?;
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+fn save_blob_txs_backup<P>(pool: P, file_path: &Path)
+where
+    P: TransactionPool,
+{
+    let blob_transactions = pool.persisted_blob_transactions();
+    if blob_transactions.is_empty() {
+        trace!(target: "txpool", "no blob transactions to save");
+        return
+    }
+
+    let num_txs = blob_transactions.len();
+    info!(target: "txpool", txs_file =?file_path, num_txs=%num_txs, "Saving current blob transactions");
+    match reth_fs_util::ensure_parent_dirs_exist(file_path)
+        .and_then(|_| reth_fs_util::write_json_lines(file_path, blob_transactions))
+    {
+        Ok(_) => {
+            info!(target: "txpool", txs_file=?file_path, "Wrote blob transactions to file");
+        }
+        Err(err) => {
+            warn!(target: "txpool", %err, txs_file=?file_path, "Failed to write blob transactions to file");
+        }
+    }
+}
+
 /// Errors possible during txs backup load and decode
 #[derive(thiserror::Error, Debug)]
 pub enum TransactionsBackupError {
@@ -644,12 +737,15 @@ pub enum TransactionsBackupError {
 
 /// Task which manages saving local transactions to the persistent file in case of shutdown.
 /// Reloads the transactions from the file on the boot up and inserts them into the pool.
+///
+/// If configured, the parked blob transactions are saved and reloaded the same way, see
+/// [`LocalTransactionBackupConfig::with_blob_txs_backup`].
 pub async fn backup_local_transactions_task<P>(
     shutdown: reth_tasks::shutdown::GracefulShutdown,
     pool: P,
     config: LocalTransactionBackupConfig,
 ) where
-    P: TransactionPool<Transaction: PoolTransaction<Consensus: SignedTransaction>> + Clone,
+    P: TransactionPool<Transaction: EthPoolTransaction<Consensus: SignedTransaction>> + Clone,
 {
     let Some(transactions_path) = config.transactions_path else {
         // nothing to do
@@ -660,9 +756,22 @@ pub async fn backup_local_transactions_task<P>(
         error!(target: "txpool", "{}", err)
     }
 
+    #[cfg(feature = "serde")]
+    if let Some(blob_transactions_path) = &config.blob_transactions_path {
+        if let Err(err) =
+            load_and_reinsert_blob_transactions(pool.clone(), blob_transactions_path).await
+        {
+            error!(target: "txpool", "{}", err)
+        }
+    }
+
     let graceful_guard = shutdown.await;
 
     // write transactions to disk
+    #[cfg(feature = "serde")]
+    if let Some(blob_transactions_path) = &config.blob_transactions_path {
+        save_blob_txs_backup(pool.clone(), blob_transactions_path);
+    }
     save_local_txs_backup(pool, &transactions_path);
 
     drop(graceful_guard)
@@ -742,6 +851,110 @@ mod tests {
         temp_dir.close().unwrap();
     }
 
+    #[cfg(feature = "serde")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_save_and_reload_blob_txs_backup() {
+        use crate::{blobstore::BlobStore, BlockInfo, PersistedBlobTx};
+        use alloy_consensus::{SignableTransaction, TxEip4844};
+        use alloy_eips::{eip2718::Encodable2718, eip4844::BlobTransactionSidecar};
+        use alloy_primitives::B256;
+        use reth_primitives::{kzg::Blob, sign_message, Transaction};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let blob_transactions_path = temp_dir.path().join("blob_transactions.jsonl");
+        let config = LocalTransactionBackupConfig::with_local_txs_backup(
+            temp_dir.path().join(FILENAME).with_extension(EXTENSION),
+        )
+        .with_blob_txs_backup(blob_transactions_path.clone());
+
+        let json_content = fs::read_to_string(
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_data/blob1.json"),
+        )
+        .unwrap();
+        let json_value: serde_json::Value = serde_json::from_str(&json_content).unwrap();
+        let blob = Blob::from_hex(json_value.get("data").unwrap().as_str().unwrap()).unwrap();
+        let sidecar = BlobTransactionSidecar::try_from_blobs(vec![blob]).unwrap();
+
+        let provider = MockEthProvider::default();
+        let blob_store = InMemoryBlobStore::default();
+        let validator = EthTransactionValidatorBuilder::new(MAINNET.clone())
+            .build(provider.clone(), blob_store.clone());
+        let new_pool = || {
+            let pool = Pool::new(
+                validator.clone(),
+                CoinbaseTipOrdering::default(),
+                blob_store.clone(),
+                Default::default(),
+            );
+            // the blob fee caps of all transactions are below the pending blob fee, so that they
+            // are parked in the blob sub-pool
+            pool.set_block_info(BlockInfo { pending_blob_fee: Some(1_000), ..pool.block_info() });
+            pool
+        };
+
+        // submitted in a different order than their priority, which is the order they are
+        // persisted and reinserted in
+        let transactions = [100, 300, 200, 300]
+            .into_iter()
+            .enumerate()
+            .map(|(i, max_fee_per_blob_gas)| {
+                let tx = Transaction::Eip4844(TxEip4844 {
+                    chain_id: 1,
+                    gas_limit: 21_000,
+                    max_fee_per_gas: 10_000_000_000,
+                    max_priority_fee_per_gas: 1_000_000_000,
+                    to: Address::random(),
+                    max_fee_per_blob_gas,
+                    blob_versioned_hashes: sidecar.versioned_hashes().collect(),
+                    ..Default::default()
+                });
+                let secret = B256::with_last_byte(i as u8 + 1);
+                let signature = sign_message(secret, tx.signature_hash()).unwrap();
+                let tx = TransactionSigned::new_unhashed(tx, signature);
+                let encoded_length = tx.encode_2718_len();
+                let tx = tx.try_into_recovered().unwrap();
+                provider.add_account(tx.signer(), ExtendedAccount::new(0, U256::MAX));
+                // the sidecars are kept in the blob store across restarts
+                blob_store.insert(*tx.tx_hash(), sidecar.clone()).unwrap();
+                EthPooledTransaction::new(tx, encoded_length)
+            })
+            .collect::<Vec<_>>();
+
+        let txpool = new_pool();
+        for result in txpool.add_transactions(TransactionOrigin::External, transactions).await {
+            result.unwrap();
+        }
+        assert_eq!(txpool.pool_size().blob, 4);
+        let persisted = txpool.persisted_blob_transactions();
+
+        let handle = tokio::runtime::Handle::current();
+        let manager = TaskManager::new(handle.clone());
+        manager.executor().spawn_critical_with_graceful_shutdown_signal("test task", |shutdown| {
+            backup_local_transactions_task(shutdown, txpool.clone(), config.clone())
+        });
+        manager.graceful_shutdown();
+
+        let saved = fs::read_json_lines::<PersistedBlobTx>(&blob_transactions_path)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(saved, persisted);
+
+        // restart with a new pool, which reloads the transactions on startup
+        let restarted = new_pool();
+        let manager = TaskManager::new(handle);
+        manager.executor().spawn_critical_with_graceful_shutdown_signal("test task", |shutdown| {
+            backup_local_transactions_task(shutdown, restarted.clone(), config)
+        });
+        manager.graceful_shutdown();
+
+        assert_eq!(restarted.pool_size().blob, txpool.pool_size().blob);
+        assert_eq!(restarted.pool_size().blob_size, txpool.pool_size().blob_size);
+        assert_eq!(restarted.persisted_blob_transactions(), persisted);
+
+        temp_dir.close().unwrap();
+    }
+
     #[test]
     fn test_update_with_higher_finalized_block() {
         let mut tracker = FinalizedBlockTracker::new(Some(10));
@@ -1,7 +1,7 @@
 //! Transaction pool metrics.
 
 use reth_metrics::{
-    metrics::{Counter, Gauge},
+    metrics::{Counter, Gauge, Histogram},
     Metrics,
 };
 
@@ -53,6 +53,44 @@ pub struct TxPoolMetrics {
     pub(crate) performed_state_updates: Counter,
 }
 
+/// Blob sub-pool metrics, registered under a custom scope.
+///
+/// The [`Default`] metrics are registered under the `transaction_pool.blob_pool` scope.
+#[derive(Metrics)]
+#[metrics(dynamic = true)]
+pub struct BlobPoolMetrics {
+    /// Number of transactions in the blob sub-pool
+    pub(crate) transactions: Gauge,
+    /// Total amount of memory used by the transactions in the blob sub-pool in bytes
+    pub(crate) size_bytes: Gauge,
+    /// Number of blobs of the transactions in the blob sub-pool
+    pub(crate) blobs: Gauge,
+    /// Total blob gas of the transactions in the blob sub-pool
+    pub(crate) blob_gas: Gauge,
+    /// Blob gas used by a block filled with the best transactions of the blob sub-pool
+    pub(crate) next_block_blob_gas: Gauge,
+    /// Priority of the best transaction in the blob sub-pool
+    pub(crate) best_priority: Gauge,
+    /// Priority of the worst transaction in the blob sub-pool, which is evicted first
+    pub(crate) worst_priority: Gauge,
+    /// Number of transactions inserted into the blob sub-pool
+    pub(crate) insertions_total: Counter,
+    /// Number of transactions promoted from the blob sub-pool to the pending sub-pool
+    pub(crate) promotions_total: Counter,
+    /// Number of transactions evicted from the blob sub-pool
+    pub(crate) evictions_total: Counter,
+    /// Priority of the transactions inserted into the blob sub-pool
+    pub(crate) insertion_priority: Histogram,
+    /// Time the transactions removed from the blob sub-pool spent in it
+    pub(crate) residence_duration_seconds: Histogram,
+}
+
+impl Default for BlobPoolMetrics {
+    fn default() -> Self {
+        Self::new("transaction_pool.blob_pool")
+    }
+}
+
 /// Transaction pool blobstore metrics
 #[derive(Metrics)]
 #[metrics(scope = "transaction_pool")]
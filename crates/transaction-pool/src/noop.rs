@@ -12,8 +12,8 @@ use crate::{
     },
     validate::ValidTransaction,
     AllPoolTransactions, AllTransactionsEvents, BestTransactions, BlockInfo, EthPoolTransaction,
-    EthPooledTransaction, NewTransactionEvent, PoolResult, PoolSize, PoolTransaction,
-    PropagatedTransactions, TransactionEvents, TransactionOrigin, TransactionPool,
+    EthPooledTransaction, NewTransactionEvent, PersistedBlobTx, PoolResult, PoolSize,
+    PoolTransaction, PropagatedTransactions, TransactionEvents, TransactionOrigin, TransactionPool,
     TransactionValidationOutcome, TransactionValidator, ValidPoolTransaction,
 };
 use alloy_eips::{
@@ -179,6 +179,10 @@ impl TransactionPool for NoopTransactionPool {
         AllPoolTransactions::default()
     }
 
+    fn persisted_blob_transactions(&self) -> Vec<PersistedBlobTx> {
+        vec![]
+    }
+
     fn remove_transactions(
         &self,
         _hashes: Vec<TxHash>,
@@ -1,21 +1,37 @@
 use super::txpool::PendingFees;
 use crate::{
-    identifier::TransactionId, pool::size::SizeTracker, traits::BestTransactionsAttributes,
-    PoolTransaction, SubPoolLimit, ValidPoolTransaction,
+    error::{PoolError, PoolErrorKind, PoolResult},
+    identifier::{SenderId, TransactionId},
+    metrics::BlobPoolMetrics,
+    pool::size::SizeTracker,
+    traits::BestTransactionsAttributes,
+    PoolTransaction, PriceBumpConfig, SubPoolLimit, ValidPoolTransaction,
 };
+use alloy_eips::{
+    eip2718::Encodable2718,
+    eip4844::{BYTES_PER_BLOB, DATA_GAS_PER_BLOB, MAX_BLOBS_PER_BLOCK},
+};
+use alloy_primitives::Bytes;
+use rustc_hash::FxHashMap;
 use std::{
     cmp::Ordering,
     collections::{BTreeMap, BTreeSet},
+    ops::Bound::{Excluded, Unbounded},
     sync::Arc,
+    time::Instant,
 };
 
+/// The default maximum number of blob transactions a single sender can have in the blob pool.
+pub(crate) const DEFAULT_MAX_BLOB_TXS_PER_SENDER: usize = 16;
+
 /// A set of validated blob transactions in the pool that are __not pending__.
 ///
 /// The purpose of this pool is to keep track of blob transactions that are queued and to evict the
 /// worst blob transactions once the sub-pool is full.
 ///
 /// This expects that certain constraints are met:
-///   - blob transactions are always gap less
+///   - blob transactions are always gap less: the transactions of a sender in this pool have
+///     consecutive nonces, because a transaction can only become pending after its ancestor.
 pub(crate) struct BlobTransactions<T: PoolTransaction> {
     /// Keeps track of transactions inserted in the pool.
     ///
@@ -31,17 +47,37 @@ pub(crate) struct BlobTransactions<T: PoolTransaction> {
     ///
     /// See also [`PoolTransaction::size`].
     size_of: SizeTracker,
+    /// Keeps track of the estimated size of the blob sidecars of all transactions in this pool.
+    ///
+    /// The sidecars are kept in the blob store, see [`blob_sidecar_size`].
+    blob_size_of: SizeTracker,
+    /// Keeps track of the total number of blobs of all transactions in this pool.
+    blob_count: usize,
+    /// Maximum number of transactions a single sender can have in this pool.
+    max_txs_per_sender: usize,
+    /// Aggregated blob info of the transactions of each sender in this pool.
+    sender_blob_info: FxHashMap<SenderId, SenderBlobInfo>,
+    /// Metrics of this pool, if enabled.
+    metrics: Option<BlobPoolMetrics>,
 }
 
 // === impl BlobTransactions ===
 
 impl<T: PoolTransaction> BlobTransactions<T> {
+    /// Enables the given metrics for this pool.
+    pub(crate) fn with_metrics(mut self, metrics: BlobPoolMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self.update_metrics();
+        self
+    }
+
     /// Adds a new transactions to the pending queue.
     ///
     /// # Panics
     ///
     ///   - If the transaction is not a blob tx.
-    ///   - If the transaction is already included.
+    ///   - If the transaction is already included, see [`Self::insert_or_replace`] for
+    ///     replacements.
     pub(crate) fn add_transaction(&mut self, tx: Arc<ValidPoolTransaction<T>>) {
         assert!(tx.is_eip4844(), "transaction is not a blob tx");
         let id = *tx.id();
@@ -50,12 +86,70 @@ impl<T: PoolTransaction> BlobTransactions<T> {
 
         // keep track of size
         self.size_of += tx.size();
+        self.blob_size_of += blob_sidecar_size(&tx.transaction);
+        self.blob_count += tx.transaction.blob_count();
+        self.sender_blob_info.entry(id.sender).or_default().add(&tx);
 
         // set transaction, which will also calculate priority based on current pending fees
         let transaction = BlobTransaction::new(tx, submission_id, &self.pending_fees);
 
+        if let Some(metrics) = &self.metrics {
+            metrics.insertions_total.increment(1);
+            metrics.insertion_priority.record(transaction.ord.priority as f64);
+        }
+
         self.by_id.insert(id, transaction.clone());
         self.all.insert(transaction);
+        self.update_metrics();
+    }
+
+    /// Adds the transaction to the pool, replacing the transaction with the same id if it exists.
+    ///
+    /// A replacement must bump the fees of the existing transaction by the blob transaction price
+    /// bump of the given [`PriceBumpConfig`], see [`ValidPoolTransaction::is_underpriced`].
+    /// Otherwise [`BlobReplacementError::Underpriced`] is returned and the pool is left untouched.
+    ///
+    /// Returns the replaced transaction, if any.
+    ///
+    /// # Panics
+    ///
+    ///   - If the transaction is not a blob tx.
+    pub(crate) fn insert_or_replace(
+        &mut self,
+        tx: Arc<ValidPoolTransaction<T>>,
+        price_bumps: &PriceBumpConfig,
+    ) -> Result<Option<Arc<ValidPoolTransaction<T>>>, BlobReplacementError> {
+        let replaced = if let Some(existing) = self.get(tx.id()) {
+            if existing.transaction.is_underpriced(&tx, price_bumps) {
+                return Err(BlobReplacementError::Underpriced {
+                    existing: BlobTxFees::new(&existing.transaction),
+                    replacement: BlobTxFees::new(&tx),
+                    price_bump: price_bumps.price_bump(tx.tx_type()),
+                })
+            }
+            self.remove_transaction(tx.id())
+        } else {
+            None
+        };
+
+        self.add_transaction(tx);
+        Ok(replaced)
+    }
+
+    /// Updates the gauges of the pool's metrics, if enabled.
+    fn update_metrics(&self) {
+        if let Some(metrics) = &self.metrics {
+            let snapshot = self.snapshot();
+            metrics.transactions.set(snapshot.count as f64);
+            metrics.size_bytes.set(snapshot.bytes as f64);
+            metrics.best_priority.set(snapshot.best_priority.unwrap_or_default() as f64);
+            metrics.worst_priority.set(snapshot.worst_priority.unwrap_or_default() as f64);
+            metrics.blobs.set(self.blob_count() as f64);
+            metrics.blob_gas.set(self.total_blob_gas() as f64);
+            metrics
+                .next_block_blob_gas
+                .set(self.blob_gas_used_if_included(MAX_BLOBS_PER_BLOCK) as f64);
+        }
     }
 
     fn next_id(&mut self) -> u64 {
@@ -68,6 +162,15 @@ impl<T: PoolTransaction> BlobTransactions<T> {
     pub(crate) fn remove_transaction(
         &mut self,
         id: &TransactionId,
+    ) -> Option<Arc<ValidPoolTransaction<T>>> {
+        self.remove_transaction_with_reason(id, BlobRemovalReason::Other)
+    }
+
+    /// Removes the transaction from the pool, recording the reason in the pool's metrics.
+    pub(crate) fn remove_transaction_with_reason(
+        &mut self,
+        id: &TransactionId,
+        reason: BlobRemovalReason,
     ) -> Option<Arc<ValidPoolTransaction<T>>> {
         // remove from queues
         let tx = self.by_id.remove(id)?;
@@ -76,6 +179,25 @@ impl<T: PoolTransaction> BlobTransactions<T> {
 
         // keep track of size
         self.size_of -= tx.transaction.size();
+        self.blob_size_of -= blob_sidecar_size(&tx.transaction.transaction);
+        self.blob_count -= tx.transaction.transaction.blob_count();
+        if self.contains_sender(id.sender) {
+            if let Some(info) = self.sender_blob_info.get_mut(&id.sender) {
+                info.sub(&tx.transaction);
+            }
+        } else {
+            self.sender_blob_info.remove(&id.sender);
+        }
+
+        if let Some(metrics) = &self.metrics {
+            metrics.residence_duration_seconds.record(tx.inserted_at.elapsed());
+            match reason {
+                BlobRemovalReason::Promoted => metrics.promotions_total.increment(1),
+                BlobRemovalReason::Evicted => metrics.evictions_total.increment(1),
+                BlobRemovalReason::Other => {}
+            }
+        }
+        self.update_metrics();
 
         Some(tx.transaction)
     }
@@ -118,6 +240,58 @@ impl<T: PoolTransaction> BlobTransactions<T> {
         transactions
     }
 
+    /// Returns an iterator over all transactions that satisfy the given basefee and blobfee,
+    /// ordered by their priority under these fees.
+    ///
+    /// Transactions of a sender are yielded gapless and in nonce order: a transaction is only
+    /// yielded after its ancestor, and if a transaction does not satisfy the fees none of its
+    /// descendants are yielded. Like [`Self::satisfy_attributes`], this yields nothing if no blob
+    /// fee is provided.
+    ///
+    /// Note: This does not remove any of the transactions from the pool.
+    #[cfg(test)]
+    pub(crate) fn best_transactions_with_attributes(
+        &self,
+        best_transactions_attributes: BestTransactionsAttributes,
+    ) -> Box<dyn Iterator<Item = Arc<ValidPoolTransaction<T>>> + '_> {
+        let Some(blob_fee) = best_transactions_attributes.blob_fee.map(|fee| fee as u128) else {
+            return Box::new(std::iter::empty())
+        };
+        let base_fee = best_transactions_attributes.basefee as u128;
+
+        let satisfies = move |tx: &BlobTransaction<T>| {
+            tx.transaction.max_fee_per_blob_gas().unwrap_or_default() >= blob_fee &&
+                tx.transaction.max_fee_per_gas() >= base_fee
+        };
+        let ord = move |tx: &BlobTransaction<T>| BlobOrd {
+            submission_id: tx.ord.submission_id,
+            priority: blob_tx_priority(
+                tx.transaction.max_fee_per_blob_gas().unwrap_or_default(),
+                blob_fee,
+                tx.transaction.max_fee_per_gas(),
+                base_fee,
+            ),
+        };
+
+        // start with the first transaction of each sender, if it satisfies the fees
+        let mut independent = BTreeSet::new();
+        let mut prev_sender = None;
+        for (id, tx) in &self.by_id {
+            if prev_sender.replace(id.sender) != Some(id.sender) && satisfies(tx) {
+                independent.insert((ord(tx), *id));
+            }
+        }
+
+        Box::new(std::iter::from_fn(move || {
+            let (_, id) = independent.pop_first()?;
+            let descendant = id.descendant();
+            if let Some(tx) = self.by_id.get(&descendant).filter(|tx| satisfies(tx)) {
+                independent.insert((ord(tx), descendant));
+            }
+            Some(self.by_id[&id].transaction.clone())
+        }))
+    }
+
     /// Returns true if the pool exceeds the given limit
     #[inline]
     pub(crate) fn exceeds(&self, limit: &SubPoolLimit) -> bool {
@@ -129,6 +303,44 @@ impl<T: PoolTransaction> BlobTransactions<T> {
         self.size_of.into()
     }
 
+    /// The estimated size of the blob sidecars of all transactions in this pool.
+    ///
+    /// See also [`blob_sidecar_size`].
+    pub(crate) fn blob_size(&self) -> usize {
+        self.blob_size_of.into()
+    }
+
+    /// The total number of blobs of all transactions in this pool.
+    pub(crate) const fn blob_count(&self) -> usize {
+        self.blob_count
+    }
+
+    /// The total blob gas of all transactions in this pool.
+    pub(crate) fn total_blob_gas(&self) -> u128 {
+        self.blob_count as u128 * DATA_GAS_PER_BLOB as u128
+    }
+
+    /// Returns the blob gas that would be used by a block with at most `max_blobs_per_block`
+    /// blobs, filled with the transactions of this pool in priority order.
+    ///
+    /// Transactions that don't fit into the remaining blob capacity are skipped.
+    ///
+    /// Note: this is an estimate of the blob gas demand, which does not take the nonce order of a
+    /// sender's transactions into account.
+    pub(crate) fn blob_gas_used_if_included(&self, max_blobs_per_block: usize) -> u128 {
+        let mut remaining = max_blobs_per_block;
+        for tx in self.all() {
+            if remaining == 0 {
+                break
+            }
+            let blob_count = tx.transaction.blob_count();
+            if blob_count <= remaining {
+                remaining -= blob_count;
+            }
+        }
+        (max_blobs_per_block - remaining) as u128 * DATA_GAS_PER_BLOB as u128
+    }
+
     /// Number of transactions in the entire pool
     pub(crate) fn len(&self) -> usize {
         self.by_id.len()
@@ -141,16 +353,53 @@ impl<T: PoolTransaction> BlobTransactions<T> {
         self.by_id.is_empty()
     }
 
+    /// Returns an iterator over all transactions in the pool, starting with the highest priority.
+    pub(crate) fn all(&self) -> impl Iterator<Item = Arc<ValidPoolTransaction<T>>> + '_ {
+        self.all.iter().map(|tx| tx.transaction.clone())
+    }
+
+    /// Returns all transactions in the pool grouped by sender, each group ordered by nonce.
+    pub(crate) fn grouped_by_sender(
+        &self,
+    ) -> BTreeMap<SenderId, Vec<Arc<ValidPoolTransaction<T>>>> {
+        let mut grouped = BTreeMap::<_, Vec<_>>::new();
+        for (id, tx) in &self.by_id {
+            grouped.entry(id.sender).or_default().push(tx.transaction.clone());
+        }
+        grouped
+    }
+
+    /// Returns a summary of the pool's contents.
+    pub(crate) fn snapshot(&self) -> BlobPoolSnapshot {
+        BlobPoolSnapshot {
+            count: self.len(),
+            bytes: self.size(),
+            best_priority: self.all.first().map(|tx| tx.ord.priority),
+            worst_priority: self.all.last().map(|tx| tx.ord.priority),
+        }
+    }
+
     /// Returns all transactions which:
     ///  * have a `max_fee_per_blob_gas` greater than or equal to the given `blob_fee`, _and_
-    ///  * have a `max_fee_per_gas` greater than or equal to the given `base_fee`
-    fn satisfy_pending_fee_ids(&self, pending_fees: &PendingFees) -> Vec<TransactionId> {
+    ///  * have a `max_fee_per_gas` greater than or equal to the given `base_fee`, _and_
+    ///  * have no nonce gap: the nonce is the sender's `next_nonce` or directly follows another
+    ///    returned transaction of the sender
+    fn satisfy_pending_fee_ids(
+        &self,
+        pending_fees: &PendingFees,
+        next_nonce: impl Fn(SenderId) -> u64,
+    ) -> Vec<TransactionId> {
         let mut transactions = Vec::new();
         {
             let mut iter = self.by_id.iter().peekable();
 
             while let Some((id, tx)) = iter.next() {
-                if tx.transaction.max_fee_per_blob_gas() < Some(pending_fees.blob_fee) ||
+                let expected_nonce = match transactions.last() {
+                    Some(TransactionId { sender, nonce }) if *sender == id.sender => nonce + 1,
+                    _ => next_nonce(id.sender),
+                };
+                if id.nonce != expected_nonce ||
+                    tx.transaction.max_fee_per_blob_gas() < Some(pending_fees.blob_fee) ||
                     tx.transaction.max_fee_per_gas() < pending_fees.base_fee as u128
                 {
                     // still parked in blob pool -> skip descendant transactions
@@ -186,9 +435,34 @@ impl<T: PoolTransaction> BlobTransactions<T> {
         }
     }
 
+    /// Removes and returns all transactions that can be promoted to the pending pool under the
+    /// given [`PendingFees`], see [`Self::satisfy_pending_fee_ids`].
+    ///
+    /// Unlike [`Self::enforce_pending_fees`], this does not update the pool's [`PendingFees`].
+    ///
+    /// The transactions are returned ordered by sender and nonce.
+    pub(crate) fn drain_promotable(
+        &mut self,
+        pending_fees: &PendingFees,
+        next_nonce: impl Fn(SenderId) -> u64,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        self.satisfy_pending_fee_ids(pending_fees, next_nonce)
+            .into_iter()
+            .map(|id| {
+                self.remove_transaction_with_reason(&id, BlobRemovalReason::Promoted)
+                    .expect("transaction exists")
+            })
+            .collect()
+    }
+
     /// Removes all transactions (and their descendants) which:
     ///  * have a `max_fee_per_blob_gas` greater than or equal to the given `blob_fee`, _and_
-    ///  * have a `max_fee_per_gas` greater than or equal to the given `base_fee`
+    ///  * have a `max_fee_per_gas` greater than or equal to the given `base_fee`, _and_
+    ///  * have no nonce gap
+    ///
+    /// `next_nonce` returns the nonce of the next executable transaction of a sender, i.e. the
+    /// on chain nonce followed by the sender's pending transactions. A transaction is only removed
+    /// if it has exactly this nonce or directly follows another removed transaction of the sender.
     ///
     /// This also sets the [`PendingFees`] for the pool, resorting transactions based on their
     /// updated priority.
@@ -197,12 +471,9 @@ impl<T: PoolTransaction> BlobTransactions<T> {
     pub(crate) fn enforce_pending_fees(
         &mut self,
         pending_fees: &PendingFees,
+        next_nonce: impl Fn(SenderId) -> u64,
     ) -> Vec<Arc<ValidPoolTransaction<T>>> {
-        let removed = self
-            .satisfy_pending_fee_ids(pending_fees)
-            .into_iter()
-            .map(|id| self.remove_transaction(&id).expect("transaction exists"))
-            .collect();
+        let removed = self.drain_promotable(pending_fees, next_nonce);
 
         // Update pending fees and reprioritize
         self.pending_fees = pending_fees.clone();
@@ -211,27 +482,324 @@ impl<T: PoolTransaction> BlobTransactions<T> {
         removed
     }
 
-    /// Removes transactions until the pool satisfies its [`SubPoolLimit`].
+    /// Removes transactions until the pool satisfies its [`SubPoolLimit`] and the estimated size of
+    /// its blob sidecars, see [`Self::blob_size`], is at most `max_blob_size`.
     ///
     /// This is done by removing transactions according to their ordering in the pool, defined by
-    /// the [`BlobOrd`] struct.
+    /// the [`BlobOrd`] struct. The descendants of each evicted transaction are removed as well,
+    /// since blob transactions must remain gapless.
     ///
-    /// Removed transactions are returned in the order they were removed.
+    /// Removed transactions are returned in the order they were removed, which is the order of
+    /// [`Self::evictable`].
     pub(crate) fn truncate_pool(
         &mut self,
         limit: SubPoolLimit,
+        max_blob_size: usize,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        let evictable =
+            self.evictable(&limit, max_blob_size).map(|tx| *tx.id()).collect::<Vec<_>>();
+
+        evictable
+            .iter()
+            .map(|id| {
+                self.remove_transaction_with_reason(id, BlobRemovalReason::Evicted)
+                    .expect("transaction exists")
+            })
+            .collect()
+    }
+
+    /// Removes transactions until the total size of the pool is at most `max_bytes`.
+    ///
+    /// See [`Self::truncate_pool`] for details.
+    #[cfg(test)]
+    pub(crate) fn enforce_size_limit(
+        &mut self,
+        max_bytes: usize,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        self.truncate_pool(SubPoolLimit { max_txs: usize::MAX, max_size: max_bytes }, usize::MAX)
+    }
+
+    /// Removes transactions until the total number of blobs in the pool is at most `max_blobs`.
+    ///
+    /// Like [`Self::truncate_pool`], transactions are evicted according to their ordering in the
+    /// pool, together with their descendants.
+    ///
+    /// Removed transactions are returned in the order they were removed.
+    #[cfg(test)]
+    pub(crate) fn enforce_blob_count_limit(
+        &mut self,
+        max_blobs: usize,
     ) -> Vec<Arc<ValidPoolTransaction<T>>> {
         let mut removed = Vec::new();
 
-        while self.exceeds(&limit) {
-            let tx = self.all.last().expect("pool is not empty");
-            let id = *tx.transaction.id();
-            removed.push(self.remove_transaction(&id).expect("transaction exists"));
+        while self.blob_count > max_blobs {
+            let worst = self.evict_worst().expect("pool is not empty");
+            let id = *worst.id();
+            removed.push(worst);
+            removed.extend(self.remove_descendants_with_reason(&id, BlobRemovalReason::Evicted));
+        }
+
+        removed
+    }
+
+    /// Removes and returns the transaction with the lowest priority, as defined by the [`BlobOrd`]
+    /// struct.
+    ///
+    /// Note: this does not remove the descendants of the evicted transaction.
+    #[cfg(test)]
+    pub(crate) fn evict_worst(&mut self) -> Option<Arc<ValidPoolTransaction<T>>> {
+        let id = *self.worst()?.id();
+        self.remove_transaction_with_reason(&id, BlobRemovalReason::Evicted)
+    }
+
+    /// Returns the transaction with the lowest priority, which is evicted first.
+    #[cfg(test)]
+    pub(crate) fn worst(&self) -> Option<&Arc<ValidPoolTransaction<T>>> {
+        self.all.last().map(|tx| &tx.transaction)
+    }
+
+    /// Returns the transaction with the highest priority.
+    #[cfg(test)]
+    pub(crate) fn best(&self) -> Option<&Arc<ValidPoolTransaction<T>>> {
+        self.all.first().map(|tx| &tx.transaction)
+    }
+
+    /// Returns the transactions that have to be removed for the pool to satisfy the given limits,
+    /// without removing them, see [`Self::truncate_pool`].
+    ///
+    /// Transactions are yielded worst first, according to their ordering in the pool, each
+    /// followed by its descendants, since blob transactions must remain gapless.
+    pub(crate) fn evictable(
+        &self,
+        limit: &SubPoolLimit,
+        max_blob_size: usize,
+    ) -> impl Iterator<Item = &Arc<ValidPoolTransaction<T>>> + '_ {
+        let mut evictable = Vec::new();
+        let (mut len, mut size, mut blob_size) = (self.len(), self.size(), self.blob_size());
+        // the lowest evicted nonce of each sender, all higher nonces are evicted as well
+        let mut evicted_from = FxHashMap::<SenderId, u64>::default();
+
+        let mut worst_first = self.all.iter().rev();
+        while limit.is_exceeded(len, size) || blob_size > max_blob_size {
+            let worst = worst_first.next().expect("pool is not empty");
+            let id = worst.transaction.id();
+            let evicted_nonce = evicted_from.get(&id.sender).copied();
+            if evicted_nonce.is_some_and(|nonce| nonce <= id.nonce) {
+                // already evicted as a descendant
+                continue
+            }
+            evicted_from.insert(id.sender, id.nonce);
+
+            let descendants = self
+                .by_id
+                .range((Excluded(id), Unbounded))
+                .take_while(|(other, _)| {
+                    other.sender == id.sender &&
+                        evicted_nonce.is_none_or(|nonce| other.nonce < nonce)
+                })
+                .map(|(_, tx)| tx);
+            for tx in std::iter::once(worst).chain(descendants) {
+                len -= 1;
+                size -= tx.transaction.size();
+                blob_size -= blob_sidecar_size(&tx.transaction.transaction);
+                evictable.push(&tx.transaction);
+            }
+        }
+
+        evictable.into_iter()
+    }
+
+    /// Returns all transactions in the pool in a form that can be persisted across restarts.
+    ///
+    /// The transactions are returned in their consensus encoding, i.e. without the blob sidecars,
+    /// which are kept in the blob store. They are ordered by submission, except that the
+    /// transactions of a sender are always in nonce order, so that they can be reinserted in this
+    /// order.
+    pub(crate) fn to_persisted(&self) -> Vec<PersistedBlobTx> {
+        // the next transaction of each sender, keyed by submission id, starting with the first one
+        let mut next = self
+            .by_id
+            .iter()
+            .filter(|(id, _)| {
+                id.unchecked_ancestor().is_none_or(|ancestor| !self.by_id.contains_key(&ancestor))
+            })
+            .map(|(_, tx)| (tx.ord.submission_id, tx))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut persisted = Vec::with_capacity(self.len());
+        while let Some((_, tx)) = next.pop_first() {
+            if let Some(descendant) = self.by_id.get(&tx.transaction.id().descendant()) {
+                next.insert(descendant.ord.submission_id, descendant);
+            }
+            persisted.push(PersistedBlobTx {
+                transaction: tx
+                    .transaction
+                    .transaction
+                    .clone_into_consensus()
+                    .into_tx()
+                    .encoded_2718()
+                    .into(),
+            });
+        }
+        persisted
+    }
+
+    /// Returns the transaction that was inserted into this pool first.
+    #[cfg(test)]
+    pub(crate) fn oldest_transaction(&self) -> Option<&Arc<ValidPoolTransaction<T>>> {
+        self.by_id
+            .values()
+            .min_by_key(|tx| (tx.inserted_at, tx.ord.submission_id))
+            .map(|tx| &tx.transaction)
+    }
+
+    /// Removes all transactions that were inserted into this pool more than `max_age` ago, e.g.
+    /// transactions whose blob fee cap is too low to ever be included.
+    ///
+    /// The descendants of each stale transaction are removed as well, since blob transactions
+    /// must remain gapless.
+    ///
+    /// Removed transactions are returned ordered by sender and nonce.
+    #[cfg(test)]
+    pub(crate) fn evict_stale(
+        &mut self,
+        max_age: std::time::Duration,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        match Instant::now().checked_sub(max_age) {
+            Some(deadline) => self.remove_inserted_before(deadline),
+            // no transaction can be older than the clock's origin
+            None => Vec::new(),
         }
+    }
 
+    /// Removes all transactions inserted before `deadline`, together with their descendants.
+    #[cfg(test)]
+    fn remove_inserted_before(&mut self, deadline: Instant) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        let stale = self
+            .by_id
+            .iter()
+            .filter(|(_, tx)| tx.inserted_at < deadline)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        let mut removed = Vec::new();
+        for id in stale {
+            // skip transactions already removed as the descendant of a stale transaction
+            if let Some(tx) = self.remove_transaction_with_reason(&id, BlobRemovalReason::Evicted) {
+                removed.push(tx);
+                removed
+                    .extend(self.remove_descendants_with_reason(&id, BlobRemovalReason::Evicted));
+            }
+        }
         removed
     }
 
+    /// Returns an iterator over all transactions of the given sender, starting with the lowest
+    /// nonce.
+    pub(crate) fn get_all_by_sender(
+        &self,
+        sender: SenderId,
+    ) -> impl Iterator<Item = &Arc<ValidPoolTransaction<T>>> + '_ {
+        self.by_id
+            .range((sender.start_bound(), Unbounded))
+            .take_while(move |(other, _)| sender == other.sender)
+            .map(|(_, tx)| &tx.transaction)
+    }
+
+    /// Returns the number of transactions of the given sender in this pool.
+    pub(crate) fn sender_transaction_count(&self, sender: SenderId) -> usize {
+        self.get_all_by_sender(sender).count()
+    }
+
+    /// Ensures that the sender of the new transaction does not exceed the maximum number of
+    /// transactions per sender in this pool.
+    ///
+    /// A transaction that replaces one that is already in this pool is always accepted.
+    pub(crate) fn ensure_sender_capacity(&self, tx: &ValidPoolTransaction<T>) -> PoolResult<()> {
+        if !self.contains(tx.id()) &&
+            self.sender_transaction_count(tx.sender_id()) >= self.max_txs_per_sender
+        {
+            return Err(PoolError::new(
+                *tx.hash(),
+                PoolErrorKind::SpammerExceededCapacity(tx.sender()),
+            ))
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the pool contains any transaction of the given sender.
+    pub(crate) fn contains_sender(&self, sender: SenderId) -> bool {
+        self.get_all_by_sender(sender).next().is_some()
+    }
+
+    /// Returns the total number of blobs of the given sender's transactions in this pool.
+    #[cfg(test)]
+    pub(crate) fn sender_blob_count(&self, sender: SenderId) -> usize {
+        self.sender_blob_info.get(&sender).map(|info| info.blob_count).unwrap_or_default()
+    }
+
+    /// Returns an iterator over all senders whose transactions in this pool have more than
+    /// `blob_limit` blobs in total, e.g. senders that could never fit into a single block.
+    #[cfg(test)]
+    pub(crate) fn senders_exceeding(
+        &self,
+        blob_limit: usize,
+    ) -> impl Iterator<Item = SenderId> + '_ {
+        self.sender_blob_info
+            .iter()
+            .filter(move |(_, info)| info.blob_count > blob_limit)
+            .map(|(sender, _)| *sender)
+    }
+
+    /// Removes all transactions of the given sender, e.g. when the sender's account was
+    /// invalidated.
+    ///
+    /// Removed transactions are returned in nonce order.
+    #[cfg(test)]
+    pub(crate) fn remove_all_by_sender(
+        &mut self,
+        sender: SenderId,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        let ids = self.get_all_by_sender(sender).map(|tx| *tx.id()).collect::<Vec<_>>();
+        ids.iter().map(|id| self.remove_transaction(id).expect("transaction exists")).collect()
+    }
+
+    /// Removes all transactions that _follow_ after the given id and have the same sender.
+    ///
+    /// NOTE: The range is _exclusive_, the transaction with the given id is not removed.
+    ///
+    /// Removed transactions are returned in nonce order.
+    #[cfg(test)]
+    pub(crate) fn remove_descendants(
+        &mut self,
+        id: &TransactionId,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        self.remove_descendants_with_reason(id, BlobRemovalReason::Other)
+    }
+
+    /// Removes all transactions that _follow_ after the given id and have the same sender,
+    /// recording the reason in the pool's metrics.
+    ///
+    /// Removed transactions are returned in nonce order.
+    #[cfg(test)]
+    fn remove_descendants_with_reason(
+        &mut self,
+        id: &TransactionId,
+        reason: BlobRemovalReason,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        let descendants = self
+            .by_id
+            .range((Excluded(id), Unbounded))
+            .take_while(|(other, _)| id.sender == other.sender)
+            .map(|(other, _)| *other)
+            .collect::<Vec<_>>();
+
+        descendants
+            .iter()
+            .map(|id| self.remove_transaction_with_reason(id, reason).expect("transaction exists"))
+            .collect()
+    }
+
     /// Returns `true` if the transaction with the given id is already included in this pool.
     pub(crate) fn contains(&self, id: &TransactionId) -> bool {
         self.by_id.contains_key(id)
@@ -242,13 +810,63 @@ impl<T: PoolTransaction> BlobTransactions<T> {
         self.by_id.get(id)
     }
 
-    /// Asserts that the bijection between `by_id` and `all` is valid.
+    /// Returns `(sender, expected_nonce)` for every sender whose transactions in this pool have a
+    /// nonce gap, where `expected_nonce` is the first missing nonce.
+    ///
+    /// Transactions in this pool are expected to be gapless, see [`Self::assert_invariants`].
+    #[cfg(any(test, feature = "test-utils"))]
+    pub(crate) fn nonce_gap_check(&self) -> Vec<(SenderId, u64)> {
+        let mut gaps = Vec::new();
+        let mut prev: Option<&TransactionId> = None;
+        for id in self.by_id.keys() {
+            if let Some(prev) = prev.filter(|prev| prev.sender == id.sender) {
+                let expected_nonce = prev.next_nonce();
+                if id.nonce != expected_nonce &&
+                    gaps.last().is_none_or(|(sender, _)| *sender != id.sender)
+                {
+                    gaps.push((id.sender, expected_nonce));
+                }
+            }
+            prev = Some(id);
+        }
+        gaps
+    }
+
+    /// Asserts that the bijection between `by_id` and `all` is valid and that transactions are
+    /// gapless.
     #[cfg(any(test, feature = "test-utils"))]
     pub(crate) fn assert_invariants(&self) {
         assert_eq!(self.by_id.len(), self.all.len(), "by_id.len() != all.len()");
+        let size = self.by_id.values().map(|tx| tx.transaction.size()).sum::<usize>();
+        assert_eq!(self.size(), size, "size_of != sum of transaction sizes");
+        let blob_count =
+            self.by_id.values().map(|tx| tx.transaction.transaction.blob_count()).sum::<usize>();
+        assert_eq!(self.blob_count, blob_count, "blob_count != sum of transaction blob counts");
+        let blob_size = self
+            .by_id
+            .values()
+            .map(|tx| blob_sidecar_size(&tx.transaction.transaction))
+            .sum::<usize>();
+        assert_eq!(self.blob_size(), blob_size, "blob_size_of != sum of blob sidecar sizes");
+        let mut sender_blob_info = FxHashMap::<SenderId, SenderBlobInfo>::default();
+        for (id, tx) in &self.by_id {
+            sender_blob_info.entry(id.sender).or_default().add(&tx.transaction);
+        }
+        assert_eq!(
+            self.sender_blob_info, sender_blob_info,
+            "sender_blob_info != aggregate of transactions"
+        );
+        let gaps = self.nonce_gap_check();
+        assert!(gaps.is_empty(), "nonce gaps: {gaps:?}");
     }
 }
 
+/// Returns the estimated size of the blob sidecar of the given transaction, which is kept in the
+/// blob store and not accounted for by [`PoolTransaction::size`].
+fn blob_sidecar_size<T: PoolTransaction>(tx: &T) -> usize {
+    tx.blob_count() * BYTES_PER_BLOB
+}
+
 impl<T: PoolTransaction> Default for BlobTransactions<T> {
     fn default() -> Self {
         Self {
@@ -256,11 +874,121 @@ impl<T: PoolTransaction> Default for BlobTransactions<T> {
             by_id: Default::default(),
             all: Default::default(),
             size_of: Default::default(),
+            blob_size_of: Default::default(),
+            blob_count: 0,
             pending_fees: Default::default(),
+            max_txs_per_sender: DEFAULT_MAX_BLOB_TXS_PER_SENDER,
+            sender_blob_info: Default::default(),
+            metrics: None,
+        }
+    }
+}
+
+/// The reason a transaction is removed from the [`BlobTransactions`] pool, recorded in its
+/// [`BlobPoolMetrics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BlobRemovalReason {
+    /// The transaction satisfies the pending fees and is moved to the pending pool.
+    Promoted,
+    /// The transaction is evicted to satisfy the pool's limits, or because it is stale.
+    Evicted,
+    /// Any other reason, e.g. the transaction was mined, replaced or moved to another pool.
+    Other,
+}
+
+/// The fees of a blob transaction that are checked when it is replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BlobTxFees {
+    /// The max fee per gas.
+    pub(crate) max_fee_per_gas: u128,
+    /// The max priority fee per gas.
+    pub(crate) max_priority_fee_per_gas: u128,
+    /// The max fee per blob gas.
+    pub(crate) max_fee_per_blob_gas: u128,
+}
+
+impl BlobTxFees {
+    /// Returns the fees of the given transaction.
+    fn new<T: PoolTransaction>(tx: &ValidPoolTransaction<T>) -> Self {
+        Self {
+            max_fee_per_gas: tx.max_fee_per_gas(),
+            max_priority_fee_per_gas: tx.transaction.max_priority_fee_per_gas().unwrap_or_default(),
+            max_fee_per_blob_gas: tx.max_fee_per_blob_gas().unwrap_or_default(),
+        }
+    }
+}
+
+/// Error returned by [`BlobTransactions::insert_or_replace`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum BlobReplacementError {
+    /// The replacement doesn't bump all fees of the existing transaction by the price bump.
+    #[error(
+        "replacement blob transaction is underpriced: {replacement:?} does not bump {existing:?} by {price_bump}%"
+    )]
+    Underpriced {
+        /// The fees of the existing transaction.
+        existing: BlobTxFees,
+        /// The fees of the replacement transaction.
+        replacement: BlobTxFees,
+        /// The required price bump in percent.
+        price_bump: u128,
+    },
+}
+
+impl From<BlobReplacementError> for PoolErrorKind {
+    fn from(err: BlobReplacementError) -> Self {
+        match err {
+            BlobReplacementError::Underpriced { .. } => Self::ReplacementUnderpriced,
         }
     }
 }
 
+/// A summary of the [`BlobTransactions`] pool, see [`BlobTransactions::snapshot`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct BlobPoolSnapshot {
+    /// Number of transactions in the pool.
+    pub(crate) count: usize,
+    /// Size of all transactions in the pool, see [`PoolTransaction::size`].
+    pub(crate) bytes: usize,
+    /// Priority of the best transaction, or `None` if the pool is empty.
+    pub(crate) best_priority: Option<i64>,
+    /// Priority of the worst transaction, the next one to be evicted, or `None` if the pool is
+    /// empty.
+    pub(crate) worst_priority: Option<i64>,
+}
+
+/// A parked blob transaction that is persisted across restarts, see
+/// [`TransactionPool::persisted_blob_transactions`](crate::TransactionPool::persisted_blob_transactions).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PersistedBlobTx {
+    /// The EIP-2718 encoded consensus transaction, without the blob sidecar.
+    pub transaction: Bytes,
+}
+
+/// Aggregated blob info of all transactions of a sender in the [`BlobTransactions`] pool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct SenderBlobInfo {
+    /// Total number of blobs of the sender's transactions.
+    blob_count: usize,
+    /// Cumulative size of the sender's transactions, see [`PoolTransaction::size`].
+    size: usize,
+}
+
+impl SenderBlobInfo {
+    /// Adds the given transaction to the aggregate.
+    fn add<T: PoolTransaction>(&mut self, tx: &ValidPoolTransaction<T>) {
+        self.blob_count += tx.transaction.blob_count();
+        self.size += tx.size();
+    }
+
+    /// Removes the given transaction from the aggregate.
+    fn sub<T: PoolTransaction>(&mut self, tx: &ValidPoolTransaction<T>) {
+        self.blob_count -= tx.transaction.blob_count();
+        self.size -= tx.size();
+    }
+}
+
 /// A transaction that is ready to be included in a block.
 #[derive(Debug)]
 struct BlobTransaction<T: PoolTransaction> {
@@ -268,40 +996,46 @@ struct BlobTransaction<T: PoolTransaction> {
     transaction: Arc<ValidPoolTransaction<T>>,
     /// The value that determines the order of this transaction.
     ord: BlobOrd,
+    /// When the transaction was inserted into the pool.
+    inserted_at: Instant,
 }
 
 impl<T: PoolTransaction> BlobTransaction<T> {
     /// Creates a new blob transaction, based on the pool transaction, submission id, and current
-    /// pending fees.
+    /// pending fees, inserted at the current time.
     pub(crate) fn new(
         transaction: Arc<ValidPoolTransaction<T>>,
         submission_id: u64,
         pending_fees: &PendingFees,
     ) -> Self {
         let priority = blob_tx_priority(
-            pending_fees.blob_fee,
             transaction.max_fee_per_blob_gas().unwrap_or_default(),
-            pending_fees.base_fee as u128,
+            pending_fees.blob_fee,
             transaction.max_fee_per_gas(),
+            pending_fees.base_fee as u128,
         );
         let ord = BlobOrd { priority, submission_id };
-        Self { transaction, ord }
+        Self { transaction, ord, inserted_at: Instant::now() }
     }
 
     /// Updates the priority for the transaction based on the current pending fees.
     pub(crate) fn update_priority(&mut self, pending_fees: &PendingFees) {
         self.ord.priority = blob_tx_priority(
-            pending_fees.blob_fee,
             self.transaction.max_fee_per_blob_gas().unwrap_or_default(),
-            pending_fees.base_fee as u128,
+            pending_fees.blob_fee,
             self.transaction.max_fee_per_gas(),
+            pending_fees.base_fee as u128,
         );
     }
 }
 
 impl<T: PoolTransaction> Clone for BlobTransaction<T> {
     fn clone(&self) -> Self {
-        Self { transaction: self.transaction.clone(), ord: self.ord.clone() }
+        Self {
+            transaction: self.transaction.clone(),
+            ord: self.ord.clone(),
+            inserted_at: self.inserted_at,
+        }
     }
 }
 
@@ -325,8 +1059,41 @@ impl<T: PoolTransaction> Ord for BlobTransaction<T> {
     }
 }
 
-/// This is the log base 2 of 1.125, which we'll use to calculate the priority
-const LOG_2_1_125: f64 = 0.16992500144231237;
+/// Number of fractional bits of the fixed-point logarithms computed by [`log2_fixed`].
+const LOG2_FRACTION_BITS: u32 = 40;
+
+/// This is the log base 2 of 1.125 in fixed-point, which we'll use to calculate the priority
+const LOG2_1_125: i128 = log2_fixed(9) - log2_fixed(8);
+
+/// Scale of the fixed-point number of fee jumps computed in [`fee_delta`].
+const JUMP_SCALE: i64 = 1_000_000;
+
+/// Returns `log2(value)` as a fixed-point number with [`LOG2_FRACTION_BITS`] fractional bits.
+///
+/// Returns zero for a zero `value`.
+const fn log2_fixed(value: u128) -> i128 {
+    if value == 0 {
+        return 0
+    }
+
+    let int = 127 - value.leading_zeros();
+    // normalize the value into [1, 2) with 63 fractional bits
+    let mut mantissa = if int > 63 { value >> (int - 63) } else { value << (63 - int) };
+    let mut log = (int as i128) << LOG2_FRACTION_BITS;
+
+    // each squaring of the mantissa yields the next fractional bit of the logarithm
+    let mut bit = 1 << (LOG2_FRACTION_BITS - 1);
+    while bit > 0 {
+        mantissa = (mantissa * mantissa) >> 63;
+        if mantissa >= 1 << 64 {
+            mantissa >>= 1;
+            log += bit;
+        }
+        bit >>= 1;
+    }
+
+    log
+}
 
 /// The blob step function, attempting to compute the delta given the `max_tx_fee`, and
 /// `current_fee`.
@@ -347,37 +1114,31 @@ const LOG_2_1_125: f64 = 0.16992500144231237;
 /// A negative value means that the transaction is currently not executable, and requires the
 /// current fee to decrease by some number of jumps before the max fee is greater than the current
 /// fee.
+///
+/// The computation uses fixed-point integer arithmetic, so that it is exact for the whole `u128`
+/// range.
 pub fn fee_delta(max_tx_fee: u128, current_fee: u128) -> i64 {
     if max_tx_fee == current_fee {
         // if these are equal, then there's no fee jump
         return 0
     }
 
-    let max_tx_fee_jumps = if max_tx_fee == 0 {
-        // we can't take log2 of 0, so we set this to zero here
-        0f64
-    } else {
-        (max_tx_fee.ilog2() as f64) / LOG_2_1_125
-    };
-
-    let current_fee_jumps = if current_fee == 0 {
-        // we can't take log2 of 0, so we set this to zero here
-        0f64
-    } else {
-        (current_fee.ilog2() as f64) / LOG_2_1_125
-    };
+    // jumps = log1.125(txfee) - log1.125(basefee), scaled by `JUMP_SCALE`
+    let jumps =
+        (log2_fixed(max_tx_fee) - log2_fixed(current_fee)) * JUMP_SCALE as i128 / LOG2_1_125;
+    let jumps = jumps as i64;
 
-    // jumps = log1.125(txfee) - log1.125(basefee)
-    let jumps = max_tx_fee_jumps - current_fee_jumps;
+    if jumps.abs() < JUMP_SCALE {
+        // less than a single jump, can't take ilog2 of 0
+        return 0
+    }
 
-    // delta = sign(jumps) * log(abs(jumps))
-    match (jumps as i64).cmp(&0) {
-        Ordering::Equal => {
-            // can't take ilog2 of 0
-            0
-        }
-        Ordering::Greater => (jumps.ceil() as i64).ilog2() as i64,
-        Ordering::Less => -((-jumps.floor() as i64).ilog2() as i64),
+    // delta = sign(jumps) * log(ceil(abs(jumps)))
+    let delta = jumps.unsigned_abs().div_ceil(JUMP_SCALE as u64).ilog2() as i64;
+    if jumps > 0 {
+        delta
+    } else {
+        -delta
     }
 }
 
@@ -452,6 +1213,16 @@ impl Ord for BlobOrd {
 mod tests {
     use super::*;
     use crate::test_utils::{MockTransaction, MockTransactionFactory};
+    use alloy_eips::eip4844::BlobTransactionSidecar;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use std::{collections::HashMap, time::Duration};
+
+    impl<T: PoolTransaction> BlobTransactions<T> {
+        /// Creates an empty pool with the given pending fees.
+        fn with_pending_fees(pending_fees: PendingFees) -> Self {
+            Self { pending_fees, ..Default::default() }
+        }
+    }
 
     /// Represents the fees for a single transaction, which will be built inside of a test.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -633,9 +1404,14 @@ mod tests {
             let mut pool = BlobTransactions::default();
 
             // create tx from fees
+            //
+            // The fees are listed in eviction order, with ties split by tip in the reference
+            // implementation. Ties are split by evicting the most recent submission first here, so
+            // the transactions are submitted in reverse.
             let txs = ordering
                 .fees
                 .iter()
+                .rev()
                 .map(|fees| {
                     MockTransaction::eip4844()
                         .with_blob_fee(fees.max_blob_fee)
@@ -652,12 +1428,13 @@ mod tests {
             pool.pending_fees = ordering.network_fees.clone();
             pool.reprioritize();
 
-            // now iterate through the pool and make sure they're in the same order as the original
-            // fees - map to TransactionFees so it's easier to compare the ordering without having
-            // to see irrelevant fields
+            // now iterate through the pool from the worst transaction and make sure they're in the
+            // same order as the original fees - map to TransactionFees so it's easier to compare
+            // the ordering without having to see irrelevant fields
             let actual_txs = pool
                 .all
                 .iter()
+                .rev()
                 .map(|tx| TransactionFees {
                     max_blob_fee: tx.transaction.max_fee_per_blob_gas().unwrap_or_default(),
                     max_priority_fee_per_gas: tx.transaction.priority_fee_or_price(),
@@ -673,11 +1450,60 @@ mod tests {
     }
 
     #[test]
-    fn priority_tests() {
-        // Test vectors from:
-        // <https://github.com/ethereum/go-ethereum/blob/e91cdb49beb4b2a3872b5f2548bf2d6559e4f561/core/txpool/blobpool/priority_test.go#L27-L49>
-        let vectors = vec![
-            (7u128, 10u128, 2i64),
+    fn test_low_blob_fee_cap_evicted_first() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool =
+            BlobTransactions::with_pending_fees(PendingFees { base_fee: 10, blob_fee: 1 << 20 });
+
+        let close = factory.validated_arc(MockTransaction::eip4844().with_blob_fee(1 << 19));
+        let far_below = factory.validated_arc(MockTransaction::eip4844().with_blob_fee(1 << 5));
+        let above = factory.validated_arc(MockTransaction::eip4844().with_blob_fee(1 << 21));
+        let far_below_id = *far_below.id();
+        pool.add_transaction(close);
+        pool.add_transaction(far_below);
+        pool.add_transaction(above);
+
+        assert_eq!(pool.all.last().unwrap().transaction.id(), &far_below_id);
+
+        let removed =
+            pool.truncate_pool(SubPoolLimit { max_txs: 2, max_size: usize::MAX }, usize::MAX);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].id(), &far_below_id);
+    }
+
+    #[test]
+    fn test_reprioritize_after_fee_change() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::with_pending_fees(PendingFees {
+            base_fee: 1 << 10,
+            blob_fee: 1 << 20,
+        });
+
+        // far below the blob fee, but with a high fee cap
+        let low_blob_fee = factory
+            .validated_arc(MockTransaction::eip4844().with_blob_fee(1 << 10).with_max_fee(1 << 20));
+        // high blob fee cap, but with a fee cap at the base fee
+        let low_max_fee = factory
+            .validated_arc(MockTransaction::eip4844().with_blob_fee(1 << 20).with_max_fee(1 << 10));
+        let low_blob_fee_id = *low_blob_fee.id();
+        let low_max_fee_id = *low_max_fee.id();
+        pool.add_transaction(low_blob_fee);
+        pool.add_transaction(low_max_fee);
+        assert_eq!(pool.all.last().unwrap().transaction.id(), &low_blob_fee_id);
+
+        // blob fee drops and base fee rises
+        pool.pending_fees = PendingFees { base_fee: 1 << 20, blob_fee: 1 << 10 };
+        pool.reprioritize();
+        pool.assert_invariants();
+        assert_eq!(pool.all.last().unwrap().transaction.id(), &low_max_fee_id);
+    }
+
+    #[test]
+    fn priority_tests() {
+        // Test vectors from:
+        // <https://github.com/ethereum/go-ethereum/blob/e91cdb49beb4b2a3872b5f2548bf2d6559e4f561/core/txpool/blobpool/priority_test.go#L27-L49>
+        let vectors = vec![
+            (7u128, 10u128, 2i64),
             (17_200_000_000, 17_200_000_000, 0),
             (9_853_941_692, 11_085_092_510, 0),
             (11_544_106_391, 10_356_781_100, 0),
@@ -694,6 +1520,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn blob_ord_priority_then_submission_id() {
+        let high = BlobOrd { submission_id: 1, priority: 0 };
+        let low = BlobOrd { submission_id: 0, priority: -3 };
+        let high_later = BlobOrd { submission_id: 2, priority: 0 };
+
+        // a higher priority sorts first, regardless of submission order
+        assert!(high < low);
+        // equal priorities are ordered by submission
+        assert!(high < high_later);
+
+        let set = BTreeSet::from([low, high_later, high]);
+        let ids = set.iter().map(|ord| ord.submission_id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn fee_delta_large_fees() {
+        // fees that are indistinguishable as `f64`
+        assert_eq!(fee_delta(u128::MAX, u128::MAX - 1), 0);
+        assert_eq!(fee_delta(u128::MAX - 1, u128::MAX), 0);
+
+        // log1.125(2^128) is ~753.3 jumps
+        assert_eq!(fee_delta(u128::MAX, 1), 9);
+        assert_eq!(fee_delta(1, u128::MAX), -9);
+
+        // exactly one and two jumps
+        assert_eq!(fee_delta(9, 8), 0);
+        assert_eq!(fee_delta(81, 64), 1);
+        assert_eq!(fee_delta(1 << 100, 1 << 99), 2);
+        assert_eq!(log2_fixed(1 << 100), 100 << LOG2_FRACTION_BITS);
+    }
+
     #[test]
     fn test_empty_pool_operations() {
         let mut pool: BlobTransactions<MockTransaction> = BlobTransactions::default();
@@ -737,6 +1596,155 @@ mod tests {
         assert!(satisfied.is_empty());
     }
 
+    /// Adds blob transactions with consecutive nonces from a single sender, with the given
+    /// `(max_fee_per_blob_gas, max_fee_per_gas)` fees, and returns their ids.
+    fn add_sender_txs(
+        factory: &mut MockTransactionFactory,
+        pool: &mut BlobTransactions<MockTransaction>,
+        fees: &[(u128, u128)],
+    ) -> Vec<TransactionId> {
+        let mut tx = MockTransaction::eip4844();
+        fees.iter()
+            .map(|&(blob_fee, max_fee)| {
+                let valid_tx =
+                    factory.validated_arc(tx.clone().with_blob_fee(blob_fee).with_max_fee(max_fee));
+                let id = *valid_tx.id();
+                pool.add_transaction(valid_tx);
+                tx = tx.next();
+                id
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_satisfy_attributes_all_satisfy() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        let mut expected = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 3]);
+        expected.extend(add_sender_txs(&mut factory, &mut pool, &[(200, 200); 2]));
+        expected.sort();
+
+        let attributes = BestTransactionsAttributes { blob_fee: Some(100), basefee: 100 };
+        let satisfied = pool.satisfy_attributes(attributes);
+        assert_eq!(satisfied.iter().map(|tx| *tx.id()).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_satisfy_attributes_none_satisfy() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        // blob fee too low
+        add_sender_txs(&mut factory, &mut pool, &[(99, 100); 2]);
+        // base fee too low
+        add_sender_txs(&mut factory, &mut pool, &[(100, 99); 2]);
+
+        let attributes = BestTransactionsAttributes { blob_fee: Some(100), basefee: 100 };
+        assert!(pool.satisfy_attributes(attributes).is_empty());
+    }
+
+    #[test]
+    fn test_satisfy_attributes_fee_subset() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        let satisfying = add_sender_txs(&mut factory, &mut pool, &[(100, 100)]);
+        // blob fee too low
+        add_sender_txs(&mut factory, &mut pool, &[(99, 100)]);
+        // base fee too low
+        add_sender_txs(&mut factory, &mut pool, &[(100, 99)]);
+        assert_eq!(pool.len(), 3);
+
+        let attributes = BestTransactionsAttributes { blob_fee: Some(100), basefee: 100 };
+        let satisfied = pool.satisfy_attributes(attributes);
+        assert_eq!(satisfied.iter().map(|tx| *tx.id()).collect::<Vec<_>>(), satisfying);
+    }
+
+    #[test]
+    fn test_satisfy_attributes_nonce_gap_cuts_off_descendants() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        let ids = add_sender_txs(
+            &mut factory,
+            &mut pool,
+            &[(100, 100), (100, 99), (100, 100), (100, 100)],
+        );
+        let other_ids = add_sender_txs(&mut factory, &mut pool, &[(100, 100), (100, 100)]);
+
+        let attributes = BestTransactionsAttributes { blob_fee: Some(100), basefee: 100 };
+        let satisfied = pool.satisfy_attributes(attributes);
+
+        // only the first transaction of the sender is included, the failing second one cuts off
+        // the later nonces
+        let mut expected = vec![ids[0]];
+        expected.extend(other_ids);
+        expected.sort();
+        assert_eq!(satisfied.iter().map(|tx| *tx.id()).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_drain_promotable() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        let ids = add_sender_txs(&mut factory, &mut pool, &[(100, 100), (100, 99), (100, 100)]);
+        let other_ids = add_sender_txs(&mut factory, &mut pool, &[(100, 100), (100, 100)]);
+        let size = pool.size();
+
+        let pending_fees = PendingFees { base_fee: 100, blob_fee: 100 };
+        let drained = pool.drain_promotable(&pending_fees, |_| 0);
+        pool.assert_invariants();
+
+        // the second transaction of the first sender is not promotable, so it and its descendant
+        // stay in the pool
+        let mut expected = vec![ids[0]];
+        expected.extend(other_ids);
+        expected.sort();
+        assert_eq!(drained.iter().map(|tx| *tx.id()).collect::<Vec<_>>(), expected);
+        assert_eq!(pool.len(), 2);
+        assert!(pool.contains(&ids[1]) && pool.contains(&ids[2]));
+        assert_eq!(
+            pool.size(),
+            size - drained.iter().map(|tx| tx.transaction.size()).sum::<usize>()
+        );
+
+        // the pool's pending fees are left untouched
+        let default_fees = PendingFees::default();
+        assert_eq!(pool.pending_fees.base_fee, default_fees.base_fee);
+        assert_eq!(pool.pending_fees.blob_fee, default_fees.blob_fee);
+        assert!(pool.drain_promotable(&pending_fees, |_| 0).is_empty());
+    }
+
+    #[test]
+    fn test_enforce_pending_fees_nonce_gap() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+
+        // the sender's transactions start at nonce 5
+        let mut tx = MockTransaction::eip4844().with_nonce(5).with_blob_fee(100).with_max_fee(100);
+        let mut gapped = Vec::new();
+        for _ in 0..2 {
+            let valid_tx = factory.validated_arc(tx.clone());
+            gapped.push(*valid_tx.id());
+            pool.add_transaction(valid_tx);
+            tx = tx.next();
+        }
+        let gapless = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 2]);
+        let gapped_sender = gapped[0].sender;
+
+        // nonces 3 and 4 of the first sender are missing, so its transactions stay in the pool
+        let pending_fees = PendingFees { base_fee: 100, blob_fee: 100 };
+        let next_nonce = |sender| if sender == gapped_sender { 3 } else { 0 };
+        let promoted = pool.enforce_pending_fees(&pending_fees, next_nonce);
+        pool.assert_invariants();
+        assert_eq!(promoted.iter().map(|tx| *tx.id()).collect::<Vec<_>>(), gapless);
+        assert_eq!(pool.len(), 2);
+        assert!(gapped.iter().all(|id| pool.contains(id)));
+
+        // once the gap is filled, the transactions are promoted in nonce order
+        let promoted = pool.enforce_pending_fees(&pending_fees, |_| 5);
+        pool.assert_invariants();
+        assert_eq!(promoted.iter().map(|tx| *tx.id()).collect::<Vec<_>>(), gapped);
+        assert!(pool.is_empty());
+    }
+
     #[test]
     #[should_panic(expected = "transaction is not a blob tx")]
     fn test_add_non_blob_transaction() {
@@ -774,7 +1782,7 @@ mod tests {
 
         // Set a size limit that requires truncation
         let limit = SubPoolLimit { max_txs: 2, max_size: 300 };
-        let removed = pool.truncate_pool(limit);
+        let removed = pool.truncate_pool(limit, usize::MAX);
 
         // Check that only one transaction was removed to satisfy the limit
         assert_eq!(removed.len(), 1);
@@ -782,6 +1790,805 @@ mod tests {
         assert!(pool.size() <= limit.max_size);
     }
 
+    #[test]
+    fn test_truncate_pool_removes_descendants() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool =
+            BlobTransactions::with_pending_fees(PendingFees { base_fee: 10, blob_fee: 1 << 20 });
+
+        // the second transaction of the sender is the worst in the pool
+        let ids =
+            add_sender_txs(&mut factory, &mut pool, &[(1 << 20, 100), (1, 100), (1 << 20, 100)]);
+        let other_ids = add_sender_txs(&mut factory, &mut pool, &[(1 << 19, 100); 2]);
+        pool.assert_invariants();
+
+        let removed =
+            pool.truncate_pool(SubPoolLimit { max_txs: 4, max_size: usize::MAX }, usize::MAX);
+        pool.assert_invariants();
+
+        // the worst transaction is removed first, followed by its descendant
+        assert_eq!(removed.iter().map(|tx| *tx.id()).collect::<Vec<_>>(), vec![ids[1], ids[2]]);
+        assert_eq!(pool.len(), 3);
+        for id in removed.iter().map(|tx| tx.id()) {
+            assert!(!pool.contains(id));
+        }
+        assert!(pool.contains(&ids[0]));
+        assert!(other_ids.iter().all(|id| pool.contains(id)));
+    }
+
+    #[test]
+    fn test_best_transactions_with_attributes_order() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+
+        // interleave the submissions of two senders
+        let mut a = MockTransaction::eip4844().with_blob_fee(100).with_max_fee(100);
+        let mut b = MockTransaction::eip4844().with_blob_fee(200).with_max_fee(200);
+        let mut expected = Vec::new();
+        for _ in 0..3 {
+            for tx in [&mut a, &mut b] {
+                let valid_tx = factory.validated_arc(tx.clone());
+                expected.push(*valid_tx.id());
+                pool.add_transaction(valid_tx);
+                *tx = tx.next();
+            }
+        }
+
+        let attributes = BestTransactionsAttributes { blob_fee: Some(100), basefee: 100 };
+        let best = pool.best_transactions_with_attributes(attributes);
+        assert_eq!(best.map(|tx| *tx.id()).collect::<Vec<_>>(), expected);
+
+        // without a blob fee nothing is yielded
+        let attributes = BestTransactionsAttributes { blob_fee: None, basefee: 100 };
+        assert_eq!(pool.best_transactions_with_attributes(attributes).count(), 0);
+    }
+
+    #[test]
+    fn test_best_transactions_with_attributes_gapless() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+
+        // submit the descendant before its ancestor, so it has the better submission id
+        let tx = MockTransaction::eip4844().with_blob_fee(100).with_max_fee(100);
+        let ancestor = factory.validated_arc(tx.clone());
+        let descendant = factory.validated_arc(tx.next());
+        let other = add_sender_txs(&mut factory, &mut pool, &[(100, 100)]);
+        pool.add_transaction(descendant.clone());
+        pool.add_transaction(ancestor.clone());
+
+        let attributes = BestTransactionsAttributes { blob_fee: Some(100), basefee: 100 };
+        let best = pool.best_transactions_with_attributes(attributes);
+        assert_eq!(
+            best.map(|tx| *tx.id()).collect::<Vec<_>>(),
+            vec![other[0], *ancestor.id(), *descendant.id()]
+        );
+    }
+
+    #[test]
+    fn test_best_transactions_with_attributes_skips_descendants() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        // first transaction does not satisfy the blob fee
+        add_sender_txs(&mut factory, &mut pool, &[(99, 100), (100, 100)]);
+        // second transaction does not satisfy the base fee
+        let partial = add_sender_txs(&mut factory, &mut pool, &[(100, 100), (100, 99), (100, 100)]);
+        let satisfying = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 2]);
+
+        let attributes = BestTransactionsAttributes { blob_fee: Some(100), basefee: 100 };
+        let best = pool.best_transactions_with_attributes(attributes);
+        assert_eq!(
+            best.map(|tx| *tx.id()).collect::<Vec<_>>(),
+            vec![partial[0], satisfying[0], satisfying[1]]
+        );
+    }
+
+    #[test]
+    fn test_sender_capacity() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions { max_txs_per_sender: 2, ..BlobTransactions::default() };
+
+        let ids = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 2]);
+        let other_ids = add_sender_txs(&mut factory, &mut pool, &[(100, 100)]);
+        assert_eq!(pool.sender_transaction_count(ids[0].sender), 2);
+        assert_eq!(pool.sender_transaction_count(other_ids[0].sender), 1);
+
+        // the sender is at capacity
+        let tx = pool.get(&ids[1]).unwrap().transaction.transaction.clone();
+        let next = factory.validated(tx.next());
+        let err = pool.ensure_sender_capacity(&next).unwrap_err();
+        assert_eq!(err.hash, *next.hash());
+        assert!(
+            matches!(err.kind, PoolErrorKind::SpammerExceededCapacity(sender) if sender == next.sender())
+        );
+
+        // replacements are accepted
+        let replacement = factory.validated(tx.rng_hash());
+        assert!(pool.ensure_sender_capacity(&replacement).is_ok());
+
+        // other senders are not affected
+        let other = pool.get(&other_ids[0]).unwrap().transaction.transaction.clone();
+        assert!(pool.ensure_sender_capacity(&factory.validated(other.next())).is_ok());
+
+        // removing a transaction frees capacity
+        pool.remove_transaction(&ids[1]);
+        assert!(pool.ensure_sender_capacity(&next).is_ok());
+    }
+
+    #[test]
+    fn test_evict_worst() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool =
+            BlobTransactions::with_pending_fees(PendingFees { base_fee: 10, blob_fee: 1 << 20 });
+        assert!(pool.evict_worst().is_none());
+
+        let best = add_sender_txs(&mut factory, &mut pool, &[(1 << 20, 100)]);
+        let worst = add_sender_txs(&mut factory, &mut pool, &[(1, 100)]);
+        let middle = add_sender_txs(&mut factory, &mut pool, &[(1 << 10, 100)]);
+
+        for expected in [worst, middle, best] {
+            assert_eq!(*pool.evict_worst().unwrap().id(), expected[0]);
+            pool.assert_invariants();
+        }
+        assert!(pool.evict_worst().is_none());
+    }
+
+    #[test]
+    fn test_insert_or_replace() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        let price_bumps = PriceBumpConfig { replace_blob_tx_price_bump: 10, ..Default::default() };
+
+        let tx = MockTransaction::eip4844()
+            .with_max_fee(100)
+            .with_priority_fee(10)
+            .with_blob_fee(100)
+            .with_size(100);
+        let original = factory.validated_arc(tx.clone());
+        assert!(pool.insert_or_replace(original.clone(), &price_bumps).unwrap().is_none());
+        let other = add_sender_txs(&mut factory, &mut pool, &[(100, 100)]);
+
+        // the blob fee is not bumped
+        let underpriced = factory.validated_arc(
+            tx.clone().rng_hash().with_max_fee(110).with_priority_fee(11).with_size(1_000),
+        );
+        let err = pool.insert_or_replace(underpriced, &price_bumps).unwrap_err();
+        assert_eq!(
+            err,
+            BlobReplacementError::Underpriced {
+                existing: BlobTxFees {
+                    max_fee_per_gas: 100,
+                    max_priority_fee_per_gas: 10,
+                    max_fee_per_blob_gas: 100
+                },
+                replacement: BlobTxFees {
+                    max_fee_per_gas: 110,
+                    max_priority_fee_per_gas: 11,
+                    max_fee_per_blob_gas: 100
+                },
+                price_bump: 10,
+            }
+        );
+        assert!(matches!(PoolErrorKind::from(err), PoolErrorKind::ReplacementUnderpriced));
+        pool.assert_invariants();
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.size(), 100 + pool.get(&other[0]).unwrap().transaction.size());
+        assert_eq!(pool.get(original.id()).unwrap().transaction.hash(), original.hash());
+
+        let replacement = factory.validated_arc(
+            tx.rng_hash()
+                .with_max_fee(110)
+                .with_priority_fee(11)
+                .with_blob_fee(110)
+                .with_size(1_000),
+        );
+        let replaced = pool.insert_or_replace(replacement.clone(), &price_bumps).unwrap().unwrap();
+        pool.assert_invariants();
+        assert_eq!(replaced.hash(), original.hash());
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.size(), 1_000 + pool.get(&other[0]).unwrap().transaction.size());
+        assert_eq!(pool.get(replacement.id()).unwrap().transaction.hash(), replacement.hash());
+    }
+
+    #[test]
+    fn test_insert_or_replace_price_bump() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        let price_bumps = PriceBumpConfig { replace_blob_tx_price_bump: 10, ..Default::default() };
+
+        let tx =
+            MockTransaction::eip4844().with_max_fee(100).with_priority_fee(0).with_blob_fee(100);
+        let original = factory.validated_arc(tx.clone());
+        assert!(pool.insert_or_replace(original.clone(), &price_bumps).unwrap().is_none());
+
+        // a 9% bump of the max fee is not enough
+        let underpriced =
+            factory.validated_arc(tx.clone().rng_hash().with_max_fee(109).with_blob_fee(110));
+        assert!(matches!(
+            pool.insert_or_replace(underpriced, &price_bumps),
+            Err(BlobReplacementError::Underpriced { price_bump: 10, .. })
+        ));
+        // neither is a 9% bump of the blob fee
+        let underpriced =
+            factory.validated_arc(tx.clone().rng_hash().with_max_fee(110).with_blob_fee(109));
+        assert!(pool.insert_or_replace(underpriced, &price_bumps).is_err());
+        pool.assert_invariants();
+        assert_eq!(pool.get(original.id()).unwrap().transaction.hash(), original.hash());
+
+        let replacement = factory.validated_arc(tx.rng_hash().with_max_fee(110).with_blob_fee(110));
+        let replaced = pool.insert_or_replace(replacement.clone(), &price_bumps).unwrap().unwrap();
+        pool.assert_invariants();
+        assert_eq!(replaced.hash(), original.hash());
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.get(replacement.id()).unwrap().transaction.hash(), replacement.hash());
+    }
+
+    #[test]
+    fn test_metrics() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let mut pool = metrics::with_local_recorder(&recorder, || {
+            BlobTransactions::with_pending_fees(PendingFees { base_fee: 10, blob_fee: 1 << 20 })
+                .with_metrics(BlobPoolMetrics::new("blob_pool"))
+        });
+        let mut factory = MockTransactionFactory::default();
+
+        let promoted = add_sender_txs(&mut factory, &mut pool, &[(1 << 21, 100)]);
+        let evicted = add_sender_txs(&mut factory, &mut pool, &[(1, 100), (1, 100)]);
+        let kept = blob_tx_with_blobs(2).with_blob_fee(1 << 10).with_max_fee(100);
+        pool.add_transaction(factory.validated_arc(kept));
+
+        let drained =
+            pool.drain_promotable(&PendingFees { base_fee: 10, blob_fee: 1 << 20 }, |_| 0);
+        assert_eq!(drained.iter().map(|tx| *tx.id()).collect::<Vec<_>>(), promoted);
+        let removed =
+            pool.truncate_pool(SubPoolLimit { max_txs: 1, max_size: usize::MAX }, usize::MAX);
+        let mut removed = removed.iter().map(|tx| *tx.id()).collect::<Vec<_>>();
+        removed.sort();
+        assert_eq!(removed, evicted);
+
+        let metrics = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| (key.key().name().to_string(), value))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(metrics["blob_pool.transactions"], DebugValue::Gauge(1.0.into()));
+        assert_eq!(metrics["blob_pool.size_bytes"], DebugValue::Gauge((pool.size() as f64).into()));
+        assert_eq!(metrics["blob_pool.blobs"], DebugValue::Gauge(2.0.into()));
+        let blob_gas = (2 * DATA_GAS_PER_BLOB) as f64;
+        assert_eq!(metrics["blob_pool.blob_gas"], DebugValue::Gauge(blob_gas.into()));
+        assert_eq!(metrics["blob_pool.next_block_blob_gas"], DebugValue::Gauge(blob_gas.into()));
+        let priority = pool.snapshot().best_priority.unwrap() as f64;
+        assert_eq!(metrics["blob_pool.best_priority"], DebugValue::Gauge(priority.into()));
+        assert_eq!(metrics["blob_pool.worst_priority"], DebugValue::Gauge(priority.into()));
+        assert_eq!(metrics["blob_pool.insertions_total"], DebugValue::Counter(4));
+        assert_eq!(metrics["blob_pool.promotions_total"], DebugValue::Counter(1));
+        assert_eq!(metrics["blob_pool.evictions_total"], DebugValue::Counter(2));
+        let DebugValue::Histogram(priorities) = &metrics["blob_pool.insertion_priority"] else {
+            panic!("priority is not a histogram")
+        };
+        assert_eq!(priorities.len(), 4);
+        let DebugValue::Histogram(durations) = &metrics["blob_pool.residence_duration_seconds"]
+        else {
+            panic!("residence duration is not a histogram")
+        };
+        assert_eq!(durations.len(), 3);
+    }
+
+    #[test]
+    fn test_evict_stale() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        assert!(pool.oldest_transaction().is_none());
+
+        let tx = MockTransaction::eip4844();
+        let stale = [tx.clone(), tx.next()].map(|tx| factory.validated_arc(tx));
+        for tx in &stale {
+            pool.add_transaction(tx.clone());
+        }
+        std::thread::sleep(Duration::from_millis(2));
+        let deadline = Instant::now();
+        std::thread::sleep(Duration::from_millis(2));
+        let fresh = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 2]);
+        // inserted after the deadline, but a descendant of stale transactions
+        let descendant = factory.validated_arc(tx.next().next());
+        pool.add_transaction(descendant.clone());
+        assert_eq!(pool.oldest_transaction().unwrap().id(), stale[0].id());
+
+        // nothing is older than the clock's origin
+        assert!(pool.evict_stale(Duration::MAX).is_empty());
+        assert_eq!(pool.len(), 5);
+
+        let removed = pool.remove_inserted_before(deadline);
+        pool.assert_invariants();
+        assert_eq!(
+            removed.iter().map(|tx| *tx.id()).collect::<Vec<_>>(),
+            vec![*stale[0].id(), *stale[1].id(), *descendant.id()]
+        );
+        assert_eq!(pool.by_id.keys().copied().collect::<Vec<_>>(), fresh);
+        assert_eq!(*pool.oldest_transaction().unwrap().id(), fresh[0]);
+
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(pool.evict_stale(Duration::ZERO).len(), 2);
+        assert!(pool.is_empty());
+        pool.assert_invariants();
+    }
+
+    #[test]
+    fn test_enforce_size_limit() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool =
+            BlobTransactions::with_pending_fees(PendingFees { base_fee: 10, blob_fee: 1 << 20 });
+
+        let mut add = |blob_fee: u128, size: usize| {
+            let tx = MockTransaction::eip4844().with_blob_fee(blob_fee).with_size(size);
+            let tx = factory.validated_arc(tx);
+            let id = *tx.id();
+            pool.add_transaction(tx);
+            id
+        };
+        let best = add(1 << 20, 100);
+        let worst = add(1, 100);
+        let middle = add(1 << 10, 100);
+
+        assert!(pool.enforce_size_limit(300).is_empty());
+
+        let removed = pool.enforce_size_limit(150);
+        assert_eq!(removed.iter().map(|tx| *tx.id()).collect::<Vec<_>>(), vec![worst, middle]);
+        assert_eq!(pool.size(), 100);
+        assert!(pool.contains(&best));
+        pool.assert_invariants();
+    }
+
+    #[test]
+    fn test_evictable_matches_truncate_pool() {
+        // the worst transactions of some senders are descendants, which are evicted before their
+        // ancestors
+        let build = || {
+            let mut factory = MockTransactionFactory::default();
+            let mut pool =
+                BlobTransactions::with_pending_fees(PendingFees { base_fee: 100, blob_fee: 100 });
+            add_sender_txs(&mut factory, &mut pool, &[(90, 100), (1, 100), (80, 100), (70, 100)]);
+            add_sender_txs(&mut factory, &mut pool, &[(40, 100), (100, 100)]);
+            add_sender_txs(&mut factory, &mut pool, &[(50, 100), (100, 100), (10, 100)]);
+            add_sender_txs(&mut factory, &mut pool, &[(100, 100), (20, 100)]);
+            pool
+        };
+        let pool = build();
+        assert_eq!(pool.best().unwrap().id(), pool.all().next().unwrap().id());
+        assert_eq!(pool.worst().unwrap().id(), pool.all().last().unwrap().id());
+        assert_eq!(pool.worst().unwrap().max_fee_per_blob_gas(), Some(1));
+        assert!(BlobTransactions::<MockTransaction>::default().worst().is_none());
+
+        // evicting a single transaction also evicts the later nonces of its sender
+        let worst = *pool.worst().unwrap().id();
+        let evictable = pool
+            .evictable(&SubPoolLimit::new(pool.len() - 1, usize::MAX), usize::MAX)
+            .map(|tx| *tx.id())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            evictable,
+            (1..4).map(|nonce| TransactionId::new(worst.sender, nonce)).collect::<Vec<_>>()
+        );
+
+        let size = pool.size() / pool.len();
+        let limits = (0..=pool.len())
+            .map(|max_txs| SubPoolLimit::new(max_txs, usize::MAX))
+            .chain((0..=pool.len()).map(|max_txs| SubPoolLimit::new(usize::MAX, max_txs * size)));
+        for limit in limits {
+            let evictable =
+                pool.evictable(&limit, usize::MAX).map(|tx| *tx.id()).collect::<Vec<_>>();
+            assert_eq!(evictable.len(), pool.evictable(&limit, usize::MAX).count());
+
+            let mut truncated = build();
+            let removed = truncated.truncate_pool(limit, usize::MAX);
+            assert_eq!(evictable, removed.iter().map(|tx| *tx.id()).collect::<Vec<_>>());
+            assert!(!truncated.exceeds(&limit));
+            truncated.assert_invariants();
+        }
+        assert_eq!(pool.len(), 11);
+    }
+
+    #[test]
+    fn test_to_persisted() {
+        use alloy_consensus::Transaction;
+        use alloy_eips::eip2718::Decodable2718;
+        use reth_primitives::TransactionSigned;
+
+        let mut factory = MockTransactionFactory::default();
+        let mut pool =
+            BlobTransactions::with_pending_fees(PendingFees { base_fee: 100, blob_fee: 100 });
+        add_sender_txs(&mut factory, &mut pool, &[(90, 100), (1, 100), (80, 100)]);
+        add_sender_txs(&mut factory, &mut pool, &[(40, 100), (100, 100)]);
+        add_sender_txs(&mut factory, &mut pool, &[(20, 50)]);
+
+        // replacing the first transaction of the first sender submits it last, but its
+        // descendants still have to follow it
+        let replacement = factory.validated_arc(
+            MockTransaction::eip4844()
+                .with_sender(pool.by_id.values().next().unwrap().transaction.sender())
+                .with_blob_fee(200)
+                .with_max_fee(200)
+                .with_priority_fee(200),
+        );
+        pool.insert_or_replace(replacement, &PriceBumpConfig::default()).unwrap().unwrap();
+
+        // the transactions are persisted in their consensus encoding, in submission order
+        let persisted = pool
+            .to_persisted()
+            .into_iter()
+            .map(|persisted| {
+                let tx =
+                    TransactionSigned::decode_2718(&mut persisted.transaction.as_ref()).unwrap();
+                (tx.nonce(), tx.max_fee_per_blob_gas())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            persisted,
+            vec![
+                (0, Some(40)),
+                (1, Some(100)),
+                (0, Some(20)),
+                (0, Some(200)),
+                (1, Some(1)),
+                (2, Some(80))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_all_by_sender_interleaved() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+
+        // senders get consecutive ids and their transactions are added interleaved
+        let a = MockTransaction::eip4844();
+        let b = MockTransaction::eip4844();
+        let txs = [a.clone(), b.clone(), a.next(), b.next(), a.next().next()];
+        let ids = txs
+            .into_iter()
+            .map(|tx| {
+                let tx = factory.validated_arc(tx);
+                let id = *tx.id();
+                pool.add_transaction(tx);
+                id
+            })
+            .collect::<Vec<_>>();
+        let (sender_a, sender_b) = (ids[0].sender, ids[1].sender);
+
+        let by_a = pool.get_all_by_sender(sender_a).map(|tx| *tx.id()).collect::<Vec<_>>();
+        assert_eq!(by_a, vec![ids[0], ids[2], ids[4]]);
+        let by_b = pool.get_all_by_sender(sender_b).map(|tx| *tx.id()).collect::<Vec<_>>();
+        assert_eq!(by_b, vec![ids[1], ids[3]]);
+
+        assert!(pool.contains_sender(sender_a));
+        assert!(pool.contains_sender(sender_b));
+        let unknown = factory.ids.sender_id_or_create(alloy_primitives::Address::random());
+        assert!(!pool.contains_sender(unknown));
+        assert_eq!(pool.get_all_by_sender(unknown).count(), 0);
+    }
+
+    #[test]
+    fn test_grouped_by_sender() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        assert!(pool.grouped_by_sender().is_empty());
+
+        // the transactions are added interleaved and out of nonce order
+        let a = MockTransaction::eip4844();
+        let b = MockTransaction::eip4844();
+        let txs = [a.next(), b.clone(), a.clone(), b.next(), a.next().next()];
+        let ids = txs
+            .into_iter()
+            .map(|tx| {
+                let tx = factory.validated_arc(tx);
+                let id = *tx.id();
+                pool.add_transaction(tx);
+                id
+            })
+            .collect::<Vec<_>>();
+
+        let grouped = pool
+            .grouped_by_sender()
+            .into_iter()
+            .map(|(sender, txs)| (sender, txs.iter().map(|tx| *tx.id()).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            grouped,
+            vec![
+                (ids[0].sender, vec![ids[2], ids[0], ids[4]]),
+                (ids[1].sender, vec![ids[1], ids[3]])
+            ]
+        );
+        assert_eq!(pool.all().count(), pool.len());
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        assert_eq!(pool.snapshot(), BlobPoolSnapshot::default());
+
+        pool.pending_fees = PendingFees { base_fee: 100, blob_fee: 100 };
+        add_sender_txs(&mut factory, &mut pool, &[(100, 100)]);
+        add_sender_txs(&mut factory, &mut pool, &[(10, 10), (50, 50)]);
+
+        let snapshot = pool.snapshot();
+        assert_eq!(snapshot.count, pool.len());
+        assert_eq!(snapshot.bytes, pool.size());
+        assert!(snapshot.best_priority > snapshot.worst_priority);
+
+        // `all` yields the transactions from best to worst
+        let priorities = pool.all().map(|tx| pool.by_id[tx.id()].ord.priority).collect::<Vec<_>>();
+        assert_eq!(priorities.first().copied(), snapshot.best_priority);
+        assert_eq!(priorities.last().copied(), snapshot.worst_priority);
+        assert!(priorities.is_sorted_by(|a, b| a >= b));
+
+        let worst = pool.evict_worst().unwrap();
+        assert_eq!(worst.max_fee_per_blob_gas(), Some(10));
+        assert_eq!(pool.snapshot().count, 2);
+        assert_eq!(pool.snapshot().bytes, pool.size());
+    }
+
+    #[test]
+    fn test_remove_descendants() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+
+        let ids = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 4]);
+        let other_ids = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 2]);
+
+        let removed = pool.remove_descendants(&ids[1]);
+        pool.assert_invariants();
+        assert_eq!(removed.iter().map(|tx| *tx.id()).collect::<Vec<_>>(), vec![ids[2], ids[3]]);
+
+        // the given transaction and the neighboring sender are untouched
+        let remaining =
+            pool.get_all_by_sender(ids[0].sender).map(|tx| *tx.id()).collect::<Vec<_>>();
+        assert_eq!(remaining, vec![ids[0], ids[1]]);
+        assert!(other_ids.iter().all(|id| pool.contains(id)));
+
+        // removing the descendants of the last transaction of a sender is a no-op
+        assert!(pool.remove_descendants(&other_ids[1]).is_empty());
+        assert_eq!(pool.len(), 4);
+
+        // the sender is gone once all of its transactions are removed
+        pool.remove_descendants(&ids[0]);
+        pool.remove_transaction(&ids[0]);
+        assert!(!pool.contains_sender(ids[0].sender));
+        pool.assert_invariants();
+    }
+
+    #[test]
+    fn test_remove_all_by_sender() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+
+        let before = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 2]);
+        let ids = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 3]);
+        let after = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 2]);
+
+        let removed = pool.remove_all_by_sender(ids[0].sender);
+        pool.assert_invariants();
+        assert_eq!(removed.iter().map(|tx| *tx.id()).collect::<Vec<_>>(), ids);
+        assert!(!pool.contains_sender(ids[0].sender));
+
+        // the other senders are untouched
+        assert_eq!(pool.len(), 4);
+        assert!(before.iter().chain(&after).all(|id| pool.contains(id)));
+
+        // removing an unknown sender is a no-op
+        assert!(pool.remove_all_by_sender(ids[0].sender).is_empty());
+        assert_eq!(pool.len(), 4);
+    }
+
+    /// Creates a blob transaction of a new sender with `blob_count` blobs.
+    fn blob_tx_with_blobs(blob_count: usize) -> MockTransaction {
+        MockTransaction::eip4844_with_sidecar(BlobTransactionSidecar {
+            blobs: vec![Default::default(); blob_count],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_sender_blob_info() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+
+        let tx = blob_tx_with_blobs(2);
+        let first = factory.validated_arc(tx.clone());
+        let second = factory.validated_arc(blob_tx_with_blobs(3).with_sender(tx.sender()).next());
+        let other = factory.validated_arc(blob_tx_with_blobs(1));
+        let sender = first.sender_id();
+        for tx in [&first, &second, &other] {
+            pool.add_transaction(tx.clone());
+        }
+        pool.assert_invariants();
+        assert_eq!(pool.sender_blob_count(sender), 5);
+        assert_eq!(pool.sender_blob_info[&sender].size, first.size() + second.size());
+        assert_eq!(pool.sender_blob_count(other.sender_id()), 1);
+
+        assert_eq!(pool.senders_exceeding(1).collect::<Vec<_>>(), vec![sender]);
+        assert_eq!(pool.senders_exceeding(0).count(), 2);
+        assert_eq!(pool.senders_exceeding(5).count(), 0);
+
+        // replace the second transaction with one that has fewer blobs
+        pool.remove_transaction(second.id());
+        pool.assert_invariants();
+        assert_eq!(pool.sender_blob_count(sender), 2);
+        let replacement =
+            factory.validated_arc(blob_tx_with_blobs(1).with_sender(tx.sender()).next());
+        assert_eq!(replacement.id(), second.id());
+        pool.add_transaction(replacement);
+        pool.assert_invariants();
+        assert_eq!(pool.sender_blob_count(sender), 3);
+
+        // the sender is dropped once all of its transactions are removed
+        pool.remove_all_by_sender(sender);
+        pool.assert_invariants();
+        assert_eq!(pool.sender_blob_count(sender), 0);
+        assert!(!pool.sender_blob_info.contains_key(&sender));
+        assert_eq!(pool.senders_exceeding(0).collect::<Vec<_>>(), vec![other.sender_id()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "sender_blob_info != aggregate of transactions")]
+    fn test_sender_blob_info_invariant() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        let tx = factory.validated_arc(blob_tx_with_blobs(2));
+        pool.add_transaction(tx.clone());
+
+        pool.sender_blob_info.get_mut(&tx.sender_id()).unwrap().blob_count += 1;
+        pool.assert_invariants();
+    }
+
+    #[test]
+    fn test_truncate_pool_blob_size() {
+        let build = || {
+            let mut factory = MockTransactionFactory::default();
+            let mut pool = BlobTransactions::with_pending_fees(PendingFees {
+                base_fee: 10,
+                blob_fee: 1 << 20,
+            });
+            // all transactions have the same priority, so the most recently submitted one is the
+            // worst, returned in eviction order
+            let mut txs = (1..=6)
+                .rev()
+                .map(|blob_count| factory.validated_arc(blob_tx_with_blobs(blob_count)))
+                .collect::<Vec<_>>();
+            for tx in &txs {
+                pool.add_transaction(tx.clone());
+            }
+            txs.reverse();
+            (pool, txs)
+        };
+        let (pool, txs) = build();
+        pool.assert_invariants();
+        assert_eq!(pool.blob_size(), 21 * BYTES_PER_BLOB);
+        assert_eq!(pool.worst().unwrap().id(), txs[0].id());
+
+        // the transaction limits are satisfied, only the blob size limit is exceeded
+        let limit = SubPoolLimit::new(txs.len(), usize::MAX);
+        assert!(!pool.exceeds(&limit));
+
+        // (max blobs, number of removed transactions)
+        for (max_blobs, num_removed) in [(21, 0), (20, 1), (15, 3), (6, 5), (5, 6), (0, 6)] {
+            let max_blob_size = max_blobs * BYTES_PER_BLOB;
+            let evictable = pool.evictable(&limit, max_blob_size).map(|tx| *tx.id());
+            let evictable = evictable.collect::<Vec<_>>();
+
+            let (mut truncated, _) = build();
+            let removed = truncated.truncate_pool(limit, max_blob_size);
+            truncated.assert_invariants();
+            let removed = removed.iter().map(|tx| *tx.id()).collect::<Vec<_>>();
+            assert_eq!(removed, txs[..num_removed].iter().map(|tx| *tx.id()).collect::<Vec<_>>());
+            assert_eq!(evictable, removed);
+            assert!(truncated.blob_size() <= max_blob_size);
+            assert_eq!(truncated.blob_count() * BYTES_PER_BLOB, truncated.blob_size());
+        }
+    }
+
+    #[test]
+    fn test_blob_gas() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool =
+            BlobTransactions::with_pending_fees(PendingFees { base_fee: 10, blob_fee: 1 << 20 });
+        assert_eq!(pool.total_blob_gas(), 0);
+        assert_eq!(pool.blob_gas_used_if_included(6), 0);
+
+        // (blob count, blob fee) in priority order
+        for (blob_count, blob_fee) in [(2, 100_000), (5, 1_000), (3, 100)] {
+            let tx = blob_tx_with_blobs(blob_count).with_blob_fee(blob_fee).with_max_fee(100);
+            pool.add_transaction(factory.validated_arc(tx));
+        }
+        let gas = |blobs: u128| blobs * DATA_GAS_PER_BLOB as u128;
+        assert_eq!(pool.total_blob_gas(), gas(10));
+
+        // the second transaction doesn't fit and is skipped in favor of the third one
+        assert_eq!(pool.blob_gas_used_if_included(6), gas(5));
+        assert_eq!(pool.blob_gas_used_if_included(7), gas(7));
+        assert_eq!(pool.blob_gas_used_if_included(1), 0);
+        assert_eq!(pool.blob_gas_used_if_included(100), gas(10));
+    }
+
+    #[test]
+    fn test_enforce_blob_count_limit() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool =
+            BlobTransactions::with_pending_fees(PendingFees { base_fee: 10, blob_fee: 1 << 20 });
+
+        // the best sender has two transactions with two blobs each, where the descendant has a
+        // better priority than its ancestor
+        let tx = blob_tx_with_blobs(2).with_blob_fee(1_000);
+        let best = [
+            factory.validated_arc(tx.clone()),
+            factory.validated_arc(tx.next().with_blob_fee(100_000)),
+        ];
+        // the worst sender has a single transaction with three blobs
+        let worst = factory.validated_arc(blob_tx_with_blobs(3).with_blob_fee(100));
+        for tx in best.iter().chain([&worst]) {
+            pool.add_transaction(tx.clone());
+        }
+        assert_eq!(pool.blob_count(), 7);
+
+        // the limit is already satisfied
+        assert!(pool.enforce_blob_count_limit(7).is_empty());
+
+        let removed = pool.enforce_blob_count_limit(6);
+        pool.assert_invariants();
+        assert_eq!(removed.iter().map(|tx| *tx.id()).collect::<Vec<_>>(), vec![*worst.id()]);
+        assert_eq!(pool.blob_count(), 4);
+
+        // evicting the first transaction of a sender removes its descendant as well
+        let removed = pool.enforce_blob_count_limit(3);
+        pool.assert_invariants();
+        assert_eq!(
+            removed.iter().map(|tx| *tx.id()).collect::<Vec<_>>(),
+            vec![*best[0].id(), *best[1].id()]
+        );
+        assert_eq!(pool.blob_count(), 0);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn test_nonce_gap_check() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+
+        let ids = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 4]);
+        let other_ids = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 3]);
+        assert!(pool.nonce_gap_check().is_empty());
+
+        // removing the first or last transaction of a sender does not create a gap
+        pool.remove_transaction(&ids[0]);
+        pool.remove_transaction(&other_ids[2]);
+        assert!(pool.nonce_gap_check().is_empty());
+
+        // only the first gap of a sender is reported
+        let gapped = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 5]);
+        pool.remove_transaction(&gapped[1]);
+        pool.remove_transaction(&gapped[3]);
+        assert_eq!(pool.nonce_gap_check(), vec![(gapped[0].sender, gapped[1].nonce)]);
+
+        // gaps of multiple senders are reported
+        pool.remove_transaction(&ids[2]);
+        let gaps = pool.nonce_gap_check();
+        assert_eq!(gaps.len(), 2);
+        assert!(gaps.contains(&(ids[0].sender, ids[2].nonce)));
+        assert!(gaps.contains(&(gapped[0].sender, gapped[1].nonce)));
+    }
+
+    #[test]
+    #[should_panic(expected = "nonce gaps")]
+    fn test_nonce_gap_invariant() {
+        let mut factory = MockTransactionFactory::default();
+        let mut pool = BlobTransactions::default();
+        let ids = add_sender_txs(&mut factory, &mut pool, &[(100, 100); 3]);
+        pool.remove_transaction(&ids[1]);
+        pool.assert_invariants();
+    }
+
     #[test]
     fn test_empty_pool_invariants() {
         // Ensure that the invariants hold for an empty pool
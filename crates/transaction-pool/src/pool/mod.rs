@@ -104,7 +104,7 @@ use crate::{
 pub use best::{
     BestPayloadTransactions, BestTransactionFilter, BestTransactionsWithPrioritizedSenders,
 };
-pub use blob::{blob_tx_priority, fee_delta};
+pub use blob::{blob_tx_priority, fee_delta, PersistedBlobTx};
 pub use events::{FullTransactionEvent, TransactionEvent};
 pub use listener::{AllTransactionsEvents, TransactionEvents};
 pub use parked::{BasefeeOrd, ParkedOrd, ParkedPool, QueuedOrd};
@@ -724,9 +724,16 @@ where
         AllPoolTransactions {
             pending: pool.pending_transactions(),
             queued: pool.queued_transactions(),
+            blob: pool.blob_transactions(),
         }
     }
 
+    /// Returns all transactions from the blob sub-pool in a form that can be persisted across
+    /// restarts.
+    pub fn persisted_blob_transactions(&self) -> Vec<PersistedBlobTx> {
+        self.get_pool_data().persisted_blob_transactions()
+    }
+
     /// Removes and returns all matching transactions from the pool.
     pub fn remove_transactions(
         &self,
@@ -4,10 +4,10 @@ use crate::{
     config::{LocalTransactionConfig, TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER},
     error::{Eip4844PoolTransactionError, InvalidPoolTransactionError, PoolError, PoolErrorKind},
     identifier::{SenderId, TransactionId},
-    metrics::{AllTransactionsMetrics, TxPoolMetrics},
+    metrics::{AllTransactionsMetrics, BlobPoolMetrics, TxPoolMetrics},
     pool::{
         best::BestTransactions,
-        blob::BlobTransactions,
+        blob::{BlobRemovalReason, BlobTransactions, PersistedBlobTx},
         parked::{BasefeeOrd, ParkedPool, QueuedOrd},
         pending::PendingPool,
         state::{SubPool, TxState},
@@ -126,7 +126,7 @@ impl<T: TransactionOrdering> TxPool<T> {
             pending_pool: PendingPool::new(ordering),
             queued_pool: Default::default(),
             basefee_pool: Default::default(),
-            blob_pool: Default::default(),
+            blob_pool: BlobTransactions::default().with_metrics(BlobPoolMetrics::default()),
             all_transactions: AllTransactions::new(&config),
             config,
             metrics: Default::default(),
@@ -240,8 +240,22 @@ impl<T: TransactionOrdering> TxPool<T> {
             }
             (Ordering::Less, _) | (_, Ordering::Less) => {
                 // decreased blob/base fee: recheck blob pool and promote all that are now valid
-                let removed =
-                    self.blob_pool.enforce_pending_fees(&self.all_transactions.pending_fees);
+                // and directly follow the sender's on chain nonce and pending transactions
+                let (sender_info, pending_pool) = (&self.sender_info, &self.pending_pool);
+                let removed = self.blob_pool.enforce_pending_fees(
+                    &self.all_transactions.pending_fees,
+                    |sender| {
+                        let state_nonce = sender_info
+                            .get(&sender)
+                            .map(|info| info.state_nonce)
+                            .unwrap_or_default();
+                        let mut id = sender.into_transaction_id(state_nonce);
+                        while pending_pool.contains(&id) {
+                            id = id.descendant();
+                        }
+                        id.nonce
+                    },
+                );
                 for tx in removed {
                     let to = {
                         let tx =
@@ -431,6 +445,17 @@ impl<T: TransactionOrdering> TxPool<T> {
         self.basefee_pool.all().chain(self.queued_pool.all())
     }
 
+    /// Returns all transactions from the blob sub-pool, grouped by sender and ordered by nonce.
+    pub(crate) fn blob_transactions(&self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        self.blob_pool.grouped_by_sender().into_values().flatten().collect()
+    }
+
+    /// Returns all transactions from the blob sub-pool in a form that can be persisted across
+    /// restarts.
+    pub(crate) fn persisted_blob_transactions(&self) -> Vec<PersistedBlobTx> {
+        self.blob_pool.to_persisted()
+    }
+
     /// Returns queued and pending transactions for the specified sender
     pub fn queued_and_pending_txs_by_sender(
         &self,
@@ -466,7 +491,8 @@ impl<T: TransactionOrdering> TxPool<T> {
     /// Returns `true` if the pool is over its configured limits.
     #[inline]
     pub(crate) fn is_exceeded(&self) -> bool {
-        self.config.is_exceeded(self.size())
+        self.config.is_exceeded(self.size()) ||
+            self.blob_pool.blob_size() > self.config.blob_size_limit
     }
 
     /// Returns the transaction for the given hash.
@@ -623,6 +649,14 @@ impl<T: TransactionOrdering> TxPool<T> {
             return Err(PoolError::new(*tx.hash(), PoolErrorKind::AlreadyImported))
         }
 
+        // Enforce the per-sender limit of the blob pool for non-local transactions before the
+        // transaction is inserted, because moving transactions between sub-pools must not fail.
+        if tx.is_eip4844() &&
+            !self.all_transactions.local_transactions_config.is_local(tx.origin, tx.sender_ref())
+        {
+            self.blob_pool.ensure_sender_capacity(&tx)?;
+        }
+
         // Update sender info with balance and nonce
         self.sender_info
             .entry(tx.sender_id())
@@ -632,7 +666,7 @@ impl<T: TransactionOrdering> TxPool<T> {
         match self.all_transactions.insert_tx(tx, on_chain_balance, on_chain_nonce) {
             Ok(InsertOk { transaction, move_to, replaced_tx, updates, .. }) => {
                 // replace the new tx and remove the replaced in the subpool(s)
-                self.add_new_transaction(transaction.clone(), replaced_tx.clone(), move_to);
+                self.add_new_transaction(transaction.clone(), replaced_tx.clone(), move_to)?;
                 // Update inserted transactions metric
                 self.metrics.inserted_transactions.increment(1);
                 let UpdateOutcome { promoted, discarded } = self.process_updates(updates);
@@ -753,7 +787,12 @@ impl<T: TransactionOrdering> TxPool<T> {
         to: SubPool,
         id: &TransactionId,
     ) -> Option<Arc<ValidPoolTransaction<T::Transaction>>> {
-        let tx = self.remove_from_subpool(from, id)?;
+        let reason = if matches!((from, to), (SubPool::Blob, SubPool::Pending)) {
+            BlobRemovalReason::Promoted
+        } else {
+            BlobRemovalReason::Other
+        };
+        let tx = self.remove_from_subpool(from, id, reason)?;
         self.add_transaction_to_subpool(to, tx.clone());
         Some(tx)
     }
@@ -812,7 +851,7 @@ impl<T: TransactionOrdering> TxPool<T> {
         id: &TransactionId,
     ) -> Option<Arc<ValidPoolTransaction<T::Transaction>>> {
         let (tx, pool) = self.all_transactions.remove_transaction(id)?;
-        self.remove_from_subpool(pool, tx.id())
+        self.remove_from_subpool(pool, tx.id(), BlobRemovalReason::Other)
     }
 
     /// Remove the transaction from the entire pool via its hash.
@@ -823,7 +862,7 @@ impl<T: TransactionOrdering> TxPool<T> {
         tx_hash: &B256,
     ) -> Option<Arc<ValidPoolTransaction<T::Transaction>>> {
         let (tx, pool) = self.all_transactions.remove_transaction_by_hash(tx_hash)?;
-        self.remove_from_subpool(pool, tx.id())
+        self.remove_from_subpool(pool, tx.id(), BlobRemovalReason::Other)
     }
 
     /// This removes the transaction from the pool and advances any descendant state inside the
@@ -841,17 +880,21 @@ impl<T: TransactionOrdering> TxPool<T> {
 
     /// Removes the transaction from the given pool.
     ///
+    /// The `blob_removal_reason` is recorded in the metrics of the blob pool if the transaction is
+    /// removed from it.
+    ///
     /// Caution: this only removes the tx from the sub-pool and not from the pool itself
     fn remove_from_subpool(
         &mut self,
         pool: SubPool,
         tx: &TransactionId,
+        blob_removal_reason: BlobRemovalReason,
     ) -> Option<Arc<ValidPoolTransaction<T::Transaction>>> {
         let tx = match pool {
             SubPool::Queued => self.queued_pool.remove_transaction(tx),
             SubPool::Pending => self.pending_pool.remove_transaction(tx),
             SubPool::BaseFee => self.basefee_pool.remove_transaction(tx),
-            SubPool::Blob => self.blob_pool.remove_transaction(tx),
+            SubPool::Blob => self.blob_pool.remove_transaction_with_reason(tx, blob_removal_reason),
         };
 
         if let Some(ref tx) = tx {
@@ -935,18 +978,32 @@ impl<T: TransactionOrdering> TxPool<T> {
 
     /// Inserts the transaction into the given sub-pool.
     /// Optionally, removes the replacement transaction.
+    ///
+    /// Returns an error if the blob sub-pool rejects the replacement of a blob transaction.
     fn add_new_transaction(
         &mut self,
         transaction: Arc<ValidPoolTransaction<T::Transaction>>,
         replaced: Option<(Arc<ValidPoolTransaction<T::Transaction>>, SubPool)>,
         pool: SubPool,
-    ) {
+    ) -> PoolResult<()> {
         if let Some((replaced, replaced_pool)) = replaced {
+            if replaced_pool == SubPool::Blob && pool == SubPool::Blob {
+                // Replace the transaction in place, `all_transactions` already checked the
+                // replacement against the same price bumps
+                trace!(target: "txpool", hash=%transaction.transaction.hash(), replaced=%replaced.hash(), "Replacing transaction in the blob subpool");
+                let hash = *transaction.hash();
+                self.blob_pool
+                    .insert_or_replace(transaction, &self.all_transactions.price_bumps)
+                    .map_err(|err| PoolError::new(hash, err))?;
+                return Ok(())
+            }
+
             // Remove the replaced transaction
-            self.remove_from_subpool(replaced_pool, replaced.id());
+            self.remove_from_subpool(replaced_pool, replaced.id(), BlobRemovalReason::Other);
         }
 
-        self.add_transaction_to_subpool(pool, transaction)
+        self.add_transaction_to_subpool(pool, transaction);
+        Ok(())
     }
 
     /// Ensures that the transactions in the sub-pools are within the given bounds.
@@ -960,9 +1017,14 @@ impl<T: TransactionOrdering> TxPool<T> {
 
         // Helper macro that discards the worst transactions for the pools
         macro_rules! discard_worst {
-            ($this:ident, $removed:ident, [$($limit:ident => $pool:ident),* $(,)*]) => {
+            (
+                $this:ident,
+                $removed:ident,
+                [$($limit:ident $(+ $blob_size_limit:ident)? => $pool:ident),* $(,)*]
+            ) => {
                 $ (
                 while $this.$pool.exceeds(&$this.config.$limit)
+                    $(|| $this.$pool.blob_size() > $this.config.$blob_size_limit)?
                     {
                         trace!(
                             target: "txpool",
@@ -974,7 +1036,10 @@ impl<T: TransactionOrdering> TxPool<T> {
                         );
 
                         // 1. first remove the worst transaction from the subpool
-                        let removed_from_subpool = $this.$pool.truncate_pool($this.config.$limit.clone());
+                        let removed_from_subpool = $this.$pool.truncate_pool(
+                            $this.config.$limit.clone()
+                            $(, $this.config.$blob_size_limit)?
+                        );
 
                         trace!(
                             target: "txpool",
@@ -1008,7 +1073,7 @@ impl<T: TransactionOrdering> TxPool<T> {
             self, removed, [
                 pending_limit => pending_pool,
                 basefee_limit => basefee_pool,
-                blob_limit    => blob_pool,
+                blob_limit + blob_size_limit => blob_pool,
                 queued_limit  => queued_pool,
             ]
         );
@@ -2090,6 +2155,42 @@ mod tests {
         assert!(pool.pending_pool.is_empty());
     }
 
+    #[test]
+    fn test_replace_parked_blob_tx() {
+        let on_chain_balance = U256::MAX;
+        let on_chain_nonce = 0;
+        let mut f = MockTransactionFactory::default();
+        let mut pool = TxPool::new(MockOrdering::default(), Default::default());
+        let tx =
+            MockTransaction::eip4844().with_max_fee(100).with_priority_fee(10).with_blob_fee(100);
+
+        // set block info so the tx and its replacement are underpriced w.r.t. blob fee
+        let mut block_info = pool.block_info();
+        block_info.pending_blob_fee = Some(1_000);
+        pool.set_block_info(block_info);
+
+        let original = f.validated(tx.clone());
+        let original_hash = *original.hash();
+        pool.add_transaction(original, on_chain_balance, on_chain_nonce).unwrap();
+        assert_eq!(pool.blob_pool.len(), 1);
+
+        let replacement =
+            f.validated(tx.rng_hash().with_max_fee(200).with_priority_fee(20).with_blob_fee(200));
+        let id = *replacement.id();
+        let replacement_hash = *replacement.hash();
+        let added = pool.add_transaction(replacement, on_chain_balance, on_chain_nonce).unwrap();
+        assert_eq!(*added.replaced().unwrap().hash(), original_hash);
+
+        // the replacement took the place of the original in the blob pool
+        assert_eq!(pool.blob_pool.len(), 1);
+        assert_eq!(
+            *pool.blob_pool.get_all_by_sender(id.sender).next().unwrap().hash(),
+            replacement_hash
+        );
+        assert_eq!(pool.all_transactions.txs.get(&id).unwrap().subpool, SubPool::Blob);
+        pool.assert_invariants();
+    }
+
     #[test]
     fn test_promote_valid_tx_with_decreasing_blob_fee() {
         let on_chain_balance = U256::MAX;
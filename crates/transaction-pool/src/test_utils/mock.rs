@@ -823,6 +823,14 @@ impl PoolTransaction for MockTransaction {
         *self.get_size()
     }
 
+    /// Returns the number of blobs in the sidecar of the transaction.
+    fn blob_count(&self) -> usize {
+        match self {
+            Self::Eip4844 { sidecar, .. } => sidecar.blobs.len(),
+            _ => 0,
+        }
+    }
+
     /// Returns the transaction type as a byte identifier.
     fn tx_type(&self) -> u8 {
         match self {
@@ -859,13 +867,6 @@ impl EthPoolTransaction for MockTransaction {
         }
     }
 
-    fn blob_count(&self) -> usize {
-        match self {
-            Self::Eip4844 { sidecar, .. } => sidecar.blobs.len(),
-            _ => 0,
-        }
-    }
-
     fn try_into_pooled_eip4844(
         self,
         sidecar: Arc<BlobTransactionSidecar>,
@@ -1,7 +1,7 @@
 use crate::{
     blobstore::BlobStoreError,
     error::{InvalidPoolTransactionError, PoolResult},
-    pool::{state::SubPool, BestTransactionFilter, TransactionEvents},
+    pool::{state::SubPool, BestTransactionFilter, PersistedBlobTx, TransactionEvents},
     validate::ValidPoolTransaction,
     AllTransactionsEvents,
 };
@@ -310,6 +310,14 @@ pub trait TransactionPool: Send + Sync + Clone {
     /// Consumer: RPC
     fn all_transactions(&self) -> AllPoolTransactions<Self::Transaction>;
 
+    /// Returns all transactions of the blob sub-pool in a form that can be persisted across
+    /// restarts, in the order they were submitted.
+    ///
+    /// The blob sidecars are not included, they are kept in the blob store.
+    ///
+    /// Consumer: Utility
+    fn persisted_blob_transactions(&self) -> Vec<PersistedBlobTx>;
+
     /// Removes all transactions corresponding to the given hashes.
     ///
     /// Consumer: Utility
@@ -565,6 +573,9 @@ pub struct AllPoolTransactions<T: PoolTransaction> {
     /// because they depend on other transactions that are not yet included in the pool (nonce gap)
     /// or otherwise blocked.
     pub queued: Vec<Arc<ValidPoolTransaction<T>>>,
+    /// Blob transactions that are parked in the blob sub-pool, because their fee caps don't
+    /// satisfy the current base fee or blob fee, grouped by sender and ordered by nonce.
+    pub blob: Vec<Arc<ValidPoolTransaction<T>>>,
 }
 
 // === impl AllPoolTransactions ===
@@ -580,15 +591,24 @@ impl<T: PoolTransaction> AllPoolTransactions<T> {
         self.queued.iter().map(|tx| tx.transaction.clone().into())
     }
 
-    /// Returns an iterator over all transactions, both pending and queued.
+    /// Returns an iterator over all parked blob [`Recovered`] transactions.
+    pub fn blob_recovered(&self) -> impl Iterator<Item = Recovered<T::Consensus>> + '_ {
+        self.blob.iter().map(|tx| tx.transaction.clone().into())
+    }
+
+    /// Returns an iterator over all transactions, pending, queued and parked blob transactions.
     pub fn all(&self) -> impl Iterator<Item = Recovered<T::Consensus>> + '_ {
-        self.pending.iter().chain(self.queued.iter()).map(|tx| tx.transaction.clone().into())
+        self.pending
+            .iter()
+            .chain(self.queued.iter())
+            .chain(self.blob.iter())
+            .map(|tx| tx.transaction.clone().into())
     }
 }
 
 impl<T: PoolTransaction> Default for AllPoolTransactions<T> {
     fn default() -> Self {
-        Self { pending: Default::default(), queued: Default::default() }
+        Self { pending: Default::default(), queued: Default::default(), blob: Default::default() }
     }
 }
 
@@ -1095,6 +1115,9 @@ pub trait PoolTransaction:
     /// Returns a measurement of the heap usage of this type and all its internals.
     fn size(&self) -> usize;
 
+    /// Returns the number of blobs this transaction has.
+    fn blob_count(&self) -> usize;
+
     /// Returns the transaction type
     fn tx_type(&self) -> u8;
 
@@ -1149,9 +1172,6 @@ pub trait EthPoolTransaction: PoolTransaction {
     /// Extracts the blob sidecar from the transaction.
     fn take_blob(&mut self) -> EthBlobTransactionSidecar;
 
-    /// Returns the number of blobs this transaction has.
-    fn blob_count(&self) -> usize;
-
     /// A specialization for the EIP-4844 transaction type.
     /// Tries to reattach the blob sidecar to the transaction.
     ///
@@ -1378,6 +1398,13 @@ impl PoolTransaction for EthPooledTransaction {
         self.transaction.transaction().input().len()
     }
 
+    fn blob_count(&self) -> usize {
+        match self.transaction.transaction() {
+            Transaction::Eip4844(tx) => tx.blob_versioned_hashes.len(),
+            _ => 0,
+        }
+    }
+
     /// Returns the transaction type
     fn tx_type(&self) -> u8 {
         self.transaction.ty()
@@ -1403,13 +1430,6 @@ impl EthPoolTransaction for EthPooledTransaction {
         }
     }
 
-    fn blob_count(&self) -> usize {
-        match self.transaction.transaction() {
-            Transaction::Eip4844(tx) => tx.blob_versioned_hashes.len(),
-            _ => 0,
-        }
-    }
-
     fn try_into_pooled_eip4844(
         self,
         sidecar: Arc<BlobTransactionSidecar>,
@@ -45,5 +45,6 @@ impl<'a, TX: DbTx> DatabaseTrieWitness<'a, TX>
             ))
             .with_prefix_sets_mut(input.prefix_sets)
             .compute(target)
+            .map(|witness| witness.into_flat_map())
     }
 }
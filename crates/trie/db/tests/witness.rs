@@ -3,16 +3,59 @@
 use alloy_consensus::EMPTY_ROOT_HASH;
 use alloy_primitives::{
     keccak256,
-    map::{HashMap, HashSet},
+    map::{B256HashMap, B256HashSet, HashMap, HashSet},
     Address, Bytes, B256, U256,
 };
 use alloy_rlp::EMPTY_STRING_CODE;
 use reth_db::{cursor::DbCursorRW, tables};
 use reth_db_api::transaction::DbTxMut;
+use reth_execution_errors::TrieWitnessError;
 use reth_primitives_traits::{Account, StorageEntry};
-use reth_provider::{test_utils::create_test_provider_factory, HashingWriter};
-use reth_trie::{proof::Proof, witness::TrieWitness, HashedPostState, HashedStorage, StateRoot};
-use reth_trie_db::{DatabaseProof, DatabaseStateRoot, DatabaseTrieWitness};
+use reth_provider::{test_utils::create_test_provider_factory, HashingWriter, TrieWriter};
+use reth_storage_errors::db::DatabaseError;
+use reth_trie::{
+    hashed_cursor::HashedPostStateCursorFactory,
+    proof::Proof,
+    trie_cursor::{InMemoryTrieCursorFactory, TrieCursorFactory},
+    witness::{
+        prune_unreachable_nodes, read_witness_file, to_eip1186_proof, verify_witness,
+        FileWitnessSink, PreimageProvider, TrieWitness,
+    },
+    HashedPostState, HashedStorage, StateRoot,
+};
+use reth_trie_db::{
+    DatabaseHashedCursorFactory, DatabaseProof, DatabaseStateRoot, DatabaseTrieCursorFactory,
+    DatabaseTrieWitness,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Trie cursor factory that counts the created cursors.
+#[derive(Clone, Debug)]
+struct CountingTrieCursorFactory<F> {
+    inner: F,
+    cursors: Arc<AtomicUsize>,
+}
+
+impl<F: TrieCursorFactory> TrieCursorFactory for CountingTrieCursorFactory<F> {
+    type AccountTrieCursor = F::AccountTrieCursor;
+    type StorageTrieCursor = F::StorageTrieCursor;
+
+    fn account_trie_cursor(&self) -> Result<Self::AccountTrieCursor, DatabaseError> {
+        self.cursors.fetch_add(1, Ordering::Relaxed);
+        self.inner.account_trie_cursor()
+    }
+
+    fn storage_trie_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Self::StorageTrieCursor, DatabaseError> {
+        self.cursors.fetch_add(1, Ordering::Relaxed);
+        self.inner.storage_trie_cursor(hashed_address)
+    }
+}
 
 #[test]
 fn includes_empty_node_preimage() {
@@ -30,7 +73,8 @@ fn includes_empty_node_preimage() {
                 accounts: HashMap::from_iter([(hashed_address, Some(Account::default()))]),
                 storages: HashMap::default(),
             })
-            .unwrap(),
+            .unwrap()
+            .into_flat_map(),
         HashMap::from_iter([(EMPTY_ROOT_HASH, Bytes::from([EMPTY_STRING_CODE]))])
     );
 
@@ -51,12 +95,16 @@ fn includes_empty_node_preimage() {
             )]),
         })
         .unwrap();
-    assert!(witness.contains_key(&state_root));
+    assert!(witness.account_nodes.contains_key(&state_root));
+    assert!(witness.verify(state_root).unwrap());
     for node in multiproof.account_subtree.values() {
-        assert_eq!(witness.get(&keccak256(node)), Some(node));
+        assert_eq!(witness.account_nodes.get(&keccak256(node)), Some(node));
     }
     // witness includes empty state trie root node
-    assert_eq!(witness.get(&EMPTY_ROOT_HASH), Some(&Bytes::from([EMPTY_STRING_CODE])));
+    assert_eq!(
+        witness.storage_nodes[&hashed_address].get(&EMPTY_ROOT_HASH),
+        Some(&Bytes::from([EMPTY_STRING_CODE]))
+    );
 }
 
 #[test]
@@ -90,12 +138,15 @@ fn includes_nodes_for_destroyed_storage_nodes() {
                 )]), // destroyed
             })
             .unwrap();
-    assert!(witness.contains_key(&state_root));
+    assert!(witness.account_nodes.contains_key(&state_root));
+    assert!(witness.verify(state_root).unwrap());
     for node in multiproof.account_subtree.values() {
-        assert_eq!(witness.get(&keccak256(node)), Some(node));
+        assert_eq!(witness.account_nodes.get(&keccak256(node)), Some(node));
     }
-    for node in multiproof.storages.iter().flat_map(|(_, storage)| storage.subtree.values()) {
-        assert_eq!(witness.get(&keccak256(node)), Some(node));
+    for (hashed_address, storage) in &multiproof.storages {
+        for node in storage.subtree.values() {
+            assert_eq!(witness.storage_nodes[hashed_address].get(&keccak256(node)), Some(node));
+        }
     }
 }
 
@@ -140,11 +191,763 @@ fn correctly_decodes_branch_node_values() {
             )]),
         })
         .unwrap();
-    assert!(witness.contains_key(&state_root));
+    assert!(witness.account_nodes.contains_key(&state_root));
+    assert!(witness.verify(state_root).unwrap());
+    for node in multiproof.account_subtree.values() {
+        assert_eq!(witness.account_nodes.get(&keccak256(node)), Some(node));
+    }
+    for (hashed_address, storage) in &multiproof.storages {
+        for node in storage.subtree.values() {
+            assert_eq!(witness.storage_nodes[hashed_address].get(&keccak256(node)), Some(node));
+        }
+    }
+}
+
+#[test]
+fn parallel_witness_matches_sequential() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    let addresses = (0..50).map(|_| Address::random()).collect::<Vec<_>>();
+    let slots = (0..20).map(|_| B256::random()).collect::<Vec<_>>();
+
+    // Insert accounts and slots into database
+    provider
+        .insert_account_for_hashing(
+            addresses.iter().map(|address| (*address, Some(Account::default()))),
+        )
+        .unwrap();
+    provider
+        .insert_storage_for_hashing(addresses.iter().map(|address| {
+            (*address, slots.iter().map(|slot| StorageEntry { key: *slot, value: U256::from(1) }))
+        }))
+        .unwrap();
+
+    // Update half of the slots, remove a few and destroy the storage of one account
+    let state = HashedPostState {
+        accounts: addresses
+            .iter()
+            .map(|address| (keccak256(address), Some(Account::default())))
+            .collect(),
+        storages: addresses
+            .iter()
+            .enumerate()
+            .map(|(i, address)| {
+                let storage = slots
+                    .iter()
+                    .step_by(2)
+                    .enumerate()
+                    .map(|(j, slot)| (keccak256(slot), U256::from(j % 3)));
+                (keccak256(address), HashedStorage::from_iter(i == 0, storage))
+            })
+            .collect(),
+    };
+
+    let sequential = TrieWitness::from_tx(provider.tx_ref()).compute(state.clone()).unwrap();
+    for parallelism in [2, 4, 64] {
+        let parallel = TrieWitness::from_tx(provider.tx_ref())
+            .with_parallelism(parallelism)
+            .compute(state.clone())
+            .unwrap();
+        assert_eq!(parallel, sequential);
+    }
+}
+
+#[test]
+fn streamed_witness_matches_witness() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    let addresses = (0..10).map(|_| Address::random()).collect::<Vec<_>>();
+    let slots = (0..5).map(|_| B256::random()).collect::<Vec<_>>();
+
+    // Insert accounts and slots into database
+    provider
+        .insert_account_for_hashing(
+            addresses.iter().map(|address| (*address, Some(Account::default()))),
+        )
+        .unwrap();
+    provider
+        .insert_storage_for_hashing(addresses.iter().map(|address| {
+            (*address, slots.iter().map(|slot| StorageEntry { key: *slot, value: U256::from(1) }))
+        }))
+        .unwrap();
+
+    // Update and remove slots of all accounts
+    let state = HashedPostState {
+        accounts: addresses
+            .iter()
+            .map(|address| (keccak256(address), Some(Account::default())))
+            .collect(),
+        storages: addresses
+            .iter()
+            .map(|address| {
+                let storage =
+                    slots.iter().enumerate().map(|(i, slot)| (keccak256(slot), U256::from(i % 2)));
+                (keccak256(address), HashedStorage::from_iter(false, storage))
+            })
+            .collect(),
+    };
+
+    let expected =
+        TrieWitness::from_tx(provider.tx_ref()).compute(state.clone()).unwrap().into_flat_map();
+
+    let mut nodes = B256HashMap::default();
+    TrieWitness::from_tx(provider.tx_ref()).compute_into(state.clone(), &mut nodes).unwrap();
+    assert_eq!(nodes, expected);
+
+    let dir = tempfile::tempdir().unwrap();
+    for deduplicate in [false, true] {
+        let path = dir.path().join(format!("witness-{deduplicate}"));
+        let mut sink = FileWitnessSink::create(&path).unwrap().with_deduplication(deduplicate);
+        TrieWitness::from_tx(provider.tx_ref()).compute_into(state.clone(), &mut sink).unwrap();
+        sink.finish().unwrap();
+        assert_eq!(read_witness_file(&path).unwrap(), expected);
+    }
+}
+
+#[test]
+fn witness_max_nodes() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    let addresses = (0..10).map(|_| Address::random()).collect::<Vec<_>>();
+    provider
+        .insert_account_for_hashing(
+            addresses.iter().map(|address| (*address, Some(Account::default()))),
+        )
+        .unwrap();
+
+    let state = HashedPostState {
+        accounts: addresses
+            .iter()
+            .map(|address| (keccak256(address), Some(Account { nonce: 1, ..Default::default() })))
+            .collect(),
+        storages: HashMap::default(),
+    };
+    let witness = TrieWitness::from_tx(provider.tx_ref()).compute(state.clone()).unwrap();
+    let max_nodes = witness.len();
+
+    let bounded = TrieWitness::from_tx(provider.tx_ref())
+        .with_max_nodes(max_nodes)
+        .compute(state.clone())
+        .unwrap();
+    assert_eq!(bounded, witness);
+
+    assert!(matches!(
+        TrieWitness::from_tx(provider.tx_ref()).with_max_nodes(max_nodes - 1).compute(state),
+        Err(TrieWitnessError::WitnessTooLarge(max)) if max == max_nodes - 1
+    ));
+}
+
+#[test]
+fn witness_output_is_deterministic() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    let addresses = (0..50).map(|_| Address::random()).collect::<Vec<_>>();
+    let slots = (0..20).map(|_| B256::random()).collect::<Vec<_>>();
+
+    provider
+        .insert_account_for_hashing(
+            addresses.iter().map(|address| (*address, Some(Account::default()))),
+        )
+        .unwrap();
+    provider
+        .insert_storage_for_hashing(addresses.iter().map(|address| {
+            (*address, slots.iter().map(|slot| StorageEntry { key: *slot, value: U256::from(1) }))
+        }))
+        .unwrap();
+
+    // Remove every other slot, so that nodes are fetched from the blinded providers as well
+    let state = HashedPostState {
+        accounts: addresses
+            .iter()
+            .map(|address| (keccak256(address), Some(Account::default())))
+            .collect(),
+        storages: addresses
+            .iter()
+            .map(|address| {
+                let storage = slots.iter().step_by(2).map(|slot| (keccak256(slot), U256::ZERO));
+                (keccak256(address), HashedStorage::from_iter(false, storage))
+            })
+            .collect(),
+    };
+
+    let compute = |parallelism| {
+        TrieWitness::from_tx(provider.tx_ref())
+            .with_parallelism(parallelism)
+            .compute(state.clone())
+            .unwrap()
+    };
+    let serialized = compute(1).into_witness().into_sorted_vec().concat();
+    for parallelism in [1, 4] {
+        assert_eq!(compute(parallelism).into_witness().into_sorted_vec().concat(), serialized);
+    }
+}
+
+#[test]
+fn collects_preimages() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    let addresses = (0..10).map(|_| Address::random()).collect::<Vec<_>>();
+    let slot = B256::random();
+    let preimages = addresses
+        .iter()
+        .map(|address| (keccak256(address), Bytes::copy_from_slice(address.as_slice())))
+        .chain([(keccak256(slot), Bytes::copy_from_slice(slot.as_slice()))])
+        .collect::<B256HashMap<_>>();
+
+    let state = HashedPostState {
+        accounts: addresses
+            .iter()
+            .map(|address| (keccak256(address), Some(Account::default())))
+            .collect(),
+        storages: HashMap::from_iter([(
+            keccak256(addresses[0]),
+            HashedStorage::from_iter(false, [(keccak256(slot), U256::from(1))]),
+        )]),
+    };
+
+    let provider_preimages = preimages.clone();
+    let preimage_provider =
+        PreimageProvider::new(move |hash| provider_preimages.get(hash).cloned());
+
+    let witness = TrieWitness::from_tx(provider.tx_ref())
+        .with_preimages(true)
+        .with_preimage_provider(preimage_provider.clone())
+        .compute(state.clone())
+        .unwrap();
+    for hashed_address in state.accounts.keys() {
+        assert!(witness.preimages.contains_key(hashed_address));
+    }
+    assert_eq!(witness.preimages, preimages);
+
+    // preimages are not collected if disabled
+    let without_preimages = TrieWitness::from_tx(provider.tx_ref())
+        .with_preimage_provider(preimage_provider)
+        .compute(state)
+        .unwrap();
+    assert!(without_preimages.preimages.is_empty());
+    assert_eq!(without_preimages.into_flat_map(), witness.into_flat_map());
+}
+
+#[test]
+fn partial_witness_matches_full_witness() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    let addresses = (0..20).map(|_| Address::random()).collect::<Vec<_>>();
+    let slots = (0..5).map(|_| B256::random()).collect::<Vec<_>>();
+    let hashed_slots = slots.iter().map(keccak256).collect::<B256HashSet>();
+
+    // Insert accounts and slots into database
+    provider
+        .insert_account_for_hashing(
+            addresses.iter().map(|address| (*address, Some(Account::default()))),
+        )
+        .unwrap();
+    provider
+        .insert_storage_for_hashing(addresses.iter().map(|address| {
+            (*address, slots.iter().map(|slot| StorageEntry { key: *slot, value: U256::from(1) }))
+        }))
+        .unwrap();
+
+    // Update the first half of the accounts and the storage of the first few
+    let state = HashedPostState {
+        accounts: addresses[..10]
+            .iter()
+            .map(|address| (keccak256(address), Some(Account { nonce: 1, ..Default::default() })))
+            .collect(),
+        storages: addresses[..5]
+            .iter()
+            .map(|address| {
+                let storage = hashed_slots.iter().map(|hashed_slot| (*hashed_slot, U256::from(2)));
+                (keccak256(address), HashedStorage::from_iter(false, storage))
+            })
+            .collect(),
+    };
+    let full = TrieWitness::from_tx(provider.tx_ref()).compute(state.clone()).unwrap();
+
+    // Target an updated account, an untouched account and a missing account
+    let updated = keccak256(addresses[0]);
+    let untouched = keccak256(addresses[15]);
+    let missing = keccak256(Address::random());
+    let targets = B256HashMap::from_iter([
+        (updated, hashed_slots.clone()),
+        (untouched, hashed_slots),
+        (missing, B256HashSet::default()),
+    ]);
+    let partial =
+        TrieWitness::from_tx(provider.tx_ref()).compute_targets(targets.clone(), &state).unwrap();
+
+    // the storage nodes of the updated account are the same as in the full witness
+    assert_eq!(partial.storage_nodes[&updated], full.storage_nodes[&updated]);
+    assert!(!partial.storage_nodes.contains_key(&keccak256(addresses[1])));
+
+    // the current values of all targets are proven
+    let multiproof = Proof::from_tx(provider.tx_ref()).multiproof(targets).unwrap();
     for node in multiproof.account_subtree.values() {
-        assert_eq!(witness.get(&keccak256(node)), Some(node));
+        assert_eq!(partial.account_nodes.get(&keccak256(node)), Some(node));
+    }
+    for node in multiproof.storages[&untouched].subtree.values() {
+        assert_eq!(partial.storage_nodes[&untouched].get(&keccak256(node)), Some(node));
+    }
+
+    // accounts that are not part of the state transition fail the full witness
+    let err = TrieWitness::from_tx(provider.tx_ref())
+        .compute(HashedPostState {
+            accounts: HashMap::default(),
+            storages: HashMap::from_iter([(untouched, HashedStorage::new(false))]),
+        })
+        .unwrap_err();
+    assert!(matches!(err, TrieWitnessError::MissingAccount(address) if address == untouched));
+}
+
+#[test]
+fn witness_updates_match_state_root() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    let addresses = (0..30).map(|_| Address::random()).collect::<Vec<_>>();
+    let slots = (0..10).map(|_| B256::random()).collect::<Vec<_>>();
+
+    // Insert accounts and slots into database and persist the trie
+    provider
+        .insert_account_for_hashing(
+            addresses.iter().map(|address| (*address, Some(Account::default()))),
+        )
+        .unwrap();
+    provider
+        .insert_storage_for_hashing(addresses.iter().map(|address| {
+            (*address, slots.iter().map(|slot| StorageEntry { key: *slot, value: U256::from(1) }))
+        }))
+        .unwrap();
+    let (_, updates) = StateRoot::from_tx(provider.tx_ref()).root_with_updates().unwrap();
+    provider.write_trie_updates(&updates).unwrap();
+
+    // Update a few accounts and slots, remove some slots and destroy one account
+    let mut state = HashedPostState {
+        accounts: addresses[..10]
+            .iter()
+            .map(|address| {
+                (keccak256(address), Some(Account { balance: U256::from(1), ..Default::default() }))
+            })
+            .collect(),
+        storages: addresses[..5]
+            .iter()
+            .map(|address| {
+                let storage = slots
+                    .iter()
+                    .step_by(2)
+                    .enumerate()
+                    .map(|(i, slot)| (keccak256(slot), U256::from(i % 2)));
+                (keccak256(address), HashedStorage::from_iter(false, storage))
+            })
+            .collect(),
+    };
+    let destroyed = keccak256(addresses[20]);
+    state.accounts.insert(destroyed, None);
+    state.storages.insert(destroyed, HashedStorage::new(true));
+
+    let result =
+        TrieWitness::from_tx(provider.tx_ref()).compute_with_updates(state.clone()).unwrap();
+    let witness = TrieWitness::from_tx(provider.tx_ref()).compute(state.clone()).unwrap();
+    assert_eq!(result.witness, witness);
+
+    let expected_root = StateRoot::overlay_root(provider.tx_ref(), state.clone()).unwrap();
+    assert_eq!(result.root, expected_root);
+
+    // Walking the database trie with the updates applied yields the same root
+    let updates = result.updates.into_sorted();
+    let state = state.into_sorted();
+    let root = StateRoot::new(
+        InMemoryTrieCursorFactory::new(DatabaseTrieCursorFactory::new(provider.tx_ref()), &updates),
+        HashedPostStateCursorFactory::new(
+            DatabaseHashedCursorFactory::new(provider.tx_ref()),
+            &state,
+        ),
+    )
+    .root()
+    .unwrap();
+    assert_eq!(root, expected_root);
+}
+
+#[test]
+fn witness_for_destroyed_and_recreated_account() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    let address = Address::random();
+    let hashed_address = keccak256(address);
+    let slots = (0..10).map(|_| B256::random()).collect::<Vec<_>>();
+
+    // Insert account and slots into database and persist the trie
+    provider.insert_account_for_hashing([(address, Some(Account::default()))]).unwrap();
+    provider
+        .insert_storage_for_hashing([(
+            address,
+            slots.iter().map(|slot| StorageEntry { key: *slot, value: U256::from(1) }),
+        )])
+        .unwrap();
+    let (_, updates) = StateRoot::from_tx(provider.tx_ref()).root_with_updates().unwrap();
+    provider.write_trie_updates(&updates).unwrap();
+
+    // The account is destroyed and recreated with one of its previous slots and a new slot
+    let new_slot = keccak256(B256::random());
+    let state = HashedPostState {
+        accounts: HashMap::from_iter([(
+            hashed_address,
+            Some(Account { nonce: 1, ..Default::default() }),
+        )]),
+        storages: HashMap::from_iter([(
+            hashed_address,
+            HashedStorage::from_iter(
+                true,
+                [(keccak256(slots[0]), U256::from(2)), (new_slot, U256::from(3))],
+            ),
+        )]),
+    };
+
+    let result =
+        TrieWitness::from_tx(provider.tx_ref()).compute_with_updates(state.clone()).unwrap();
+    assert_eq!(result.root, StateRoot::overlay_root(provider.tx_ref(), state.clone()).unwrap());
+    assert_eq!(result.witness, TrieWitness::from_tx(provider.tx_ref()).compute(state).unwrap());
+
+    // The witness proves the old storage, so that it can be cleared
+    let multiproof = Proof::from_tx(provider.tx_ref())
+        .multiproof(HashMap::from_iter([(
+            hashed_address,
+            slots.iter().map(keccak256).collect::<B256HashSet>(),
+        )]))
+        .unwrap();
+    for node in multiproof.storages[&hashed_address].subtree.values() {
+        assert_eq!(result.witness.storage_nodes[&hashed_address].get(&keccak256(node)), Some(node));
+    }
+}
+
+#[test]
+fn incremental_witness_reuses_prior_nodes() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    // `a` and `c` share a branch node below the root, which the root collapses into once `b` is
+    // removed
+    let [a, b, c] = [0x11, 0x20, 0x12].map(B256::repeat_byte);
+    let mut hashed_account_cursor =
+        provider.tx_ref().cursor_write::<tables::HashedAccounts>().unwrap();
+    for hashed_address in [a, c, b] {
+        hashed_account_cursor
+            .upsert(hashed_address, &Account { nonce: 1, ..Default::default() })
+            .unwrap();
+    }
+
+    let cursors = Arc::new(AtomicUsize::new(0));
+    let trie_witness = || {
+        TrieWitness::new(
+            CountingTrieCursorFactory {
+                inner: DatabaseTrieCursorFactory::new(provider.tx_ref()),
+                cursors: cursors.clone(),
+            },
+            DatabaseHashedCursorFactory::new(provider.tx_ref()),
+        )
+    };
+
+    // the first block updates `a`
+    let first = HashedPostState {
+        accounts: HashMap::from_iter([(a, Some(Account { nonce: 2, ..Default::default() }))]),
+        storages: HashMap::default(),
+    };
+    let prior = trie_witness().compute(first).unwrap().into_flat_map();
+
+    // the second block removes `b`, which reveals the shared branch node
+    let second =
+        HashedPostState { accounts: HashMap::from_iter([(b, None)]), storages: HashMap::default() };
+    cursors.store(0, Ordering::Relaxed);
+    let witness = trie_witness().compute(second.clone()).unwrap().into_flat_map();
+    let full_cursors = cursors.swap(0, Ordering::Relaxed);
+    let incremental = trie_witness().compute_incremental(second, prior.clone()).unwrap();
+    assert!(cursors.load(Ordering::Relaxed) < full_cursors);
+
+    let mut expected = witness;
+    expected.extend(prior);
+    assert_eq!(incremental, expected);
+}
+
+#[test]
+fn verify_computed_witness() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    let addresses = (0..10).map(|_| Address::random()).collect::<Vec<_>>();
+    let slots = (0..5).map(|_| B256::random()).collect::<Vec<_>>();
+    provider
+        .insert_account_for_hashing(
+            addresses.iter().map(|address| (*address, Some(Account::default()))),
+        )
+        .unwrap();
+    // the storage tries differ, so that no storage nodes are shared between the accounts
+    let value = |address: &Address| U256::from_be_bytes(keccak256(address).0);
+    provider
+        .insert_storage_for_hashing(addresses.iter().map(|address| {
+            (*address, slots.iter().map(|slot| StorageEntry { key: *slot, value: value(address) }))
+        }))
+        .unwrap();
+    let state_root = StateRoot::from_tx(provider.tx_ref()).root().unwrap();
+
+    // update a few accounts and slots of some of them
+    let hashed_slots = slots[..2].iter().map(keccak256).collect::<Vec<_>>();
+    let state = HashedPostState {
+        accounts: addresses[..5]
+            .iter()
+            .map(|address| (keccak256(address), Some(Account { nonce: 1, ..Default::default() })))
+            .collect(),
+        storages: addresses[..3]
+            .iter()
+            .map(|address| {
+                let storage = hashed_slots.iter().map(|hashed_slot| (*hashed_slot, U256::from(2)));
+                (keccak256(address), HashedStorage::from_iter(false, storage))
+            })
+            .collect(),
+    };
+    let witness =
+        TrieWitness::from_tx(provider.tx_ref()).compute(state.clone()).unwrap().into_flat_map();
+
+    // the witness reveals the current values of exactly the changed accounts and slots
+    let verified = verify_witness(state_root, &witness).unwrap();
+    assert!(verified.unreachable.is_empty());
+    assert_eq!(
+        verified.accounts.keys().copied().collect::<B256HashSet>(),
+        state.accounts.keys().copied().collect()
+    );
+    assert!(verified.accounts.values().all(|account| account.nonce == 0));
+    assert_eq!(verified.storages.len(), state.storages.len());
+    for address in &addresses[..3] {
+        let slots = &verified.storages[&keccak256(address)];
+        assert_eq!(
+            slots.keys().copied().collect::<B256HashSet>(),
+            hashed_slots.iter().copied().collect()
+        );
+        assert!(slots.values().all(|slot_value| *slot_value == value(address)));
+    }
+
+    // nodes of another state are reported as unreachable
+    let verified = verify_witness(B256::random(), &witness).unwrap();
+    assert_eq!(verified.unreachable.len(), witness.len());
+
+    // a corrupted node is rejected
+    let mut corrupted = witness;
+    let key = *corrupted.keys().next().unwrap();
+    corrupted.insert(key, Bytes::from_static(&[0xc2, 0x80, 0x80]));
+    assert!(matches!(
+        verify_witness(state_root, &corrupted),
+        Err(TrieWitnessError::NodeHashMismatch { key: mismatched, .. }) if mismatched == key
+    ));
+}
+
+#[test]
+fn eip1186_proof_from_witness() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    let addresses = (0..10).map(|_| Address::random()).collect::<Vec<_>>();
+    let slots = (0..5).map(|_| B256::random()).collect::<Vec<_>>();
+    provider
+        .insert_account_for_hashing(addresses.iter().enumerate().map(|(nonce, address)| {
+            (*address, Some(Account { nonce: nonce as u64, ..Default::default() }))
+        }))
+        .unwrap();
+    provider
+        .insert_storage_for_hashing(addresses[..5].iter().map(|address| {
+            let value = U256::from_be_bytes(keccak256(address).0);
+            (*address, slots.iter().map(move |slot| StorageEntry { key: *slot, value }))
+        }))
+        .unwrap();
+    let state_root = StateRoot::from_tx(provider.tx_ref()).root().unwrap();
+
+    // the witness reveals the changed account and slots, the unchanged slot and the account
+    // without storage are proven absent
+    let (address, missing) = (addresses[0], Address::random());
+    let (changed_slots, unchanged_slot) = (&slots[..2], slots[2]);
+    let state = HashedPostState {
+        accounts: [address, addresses[7], missing]
+            .iter()
+            .map(|address| (keccak256(address), Some(Account::default())))
+            .collect(),
+        storages: HashMap::from_iter([(
+            keccak256(address),
+            HashedStorage::from_iter(
+                false,
+                changed_slots.iter().map(|slot| (keccak256(slot), U256::from(1))),
+            ),
+        )]),
+    };
+    let witness = TrieWitness::from_tx(provider.tx_ref()).compute(state).unwrap().into_flat_map();
+
+    for (address, slots) in [(address, changed_slots), (addresses[7], &[][..]), (missing, &[][..])]
+    {
+        let proof = to_eip1186_proof(&witness, state_root, address, slots).unwrap();
+        assert_eq!(proof, Proof::from_tx(provider.tx_ref()).account_proof(address, slots).unwrap());
+        proof.verify(state_root).unwrap();
     }
-    for node in multiproof.storages.iter().flat_map(|(_, storage)| storage.subtree.values()) {
-        assert_eq!(witness.get(&keccak256(node)), Some(node));
+
+    // the witness doesn't include the nodes of the unchanged slot
+    assert_eq!(to_eip1186_proof(&witness, state_root, address, &[unchanged_slot]), None);
+    // other accounts can only be proven if the witness happens to contain their proof, e.g. as
+    // part of the exclusion proof of the missing account
+    for other in &addresses[1..7] {
+        let expected = Proof::from_tx(provider.tx_ref()).account_proof(*other, &[]).unwrap();
+        let complete = expected.proof.iter().all(|node| witness.contains_key(&keccak256(node)));
+        assert_eq!(
+            to_eip1186_proof(&witness, state_root, *other, &[]),
+            complete.then_some(expected)
+        );
+    }
+    assert_eq!(to_eip1186_proof(&witness, B256::random(), address, &[]), None);
+}
+
+#[test]
+fn prune_witness_to_targets() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    let addresses = (0..10).map(|_| Address::random()).collect::<Vec<_>>();
+    let slots = (0..5).map(|_| B256::random()).collect::<Vec<_>>();
+    provider
+        .insert_account_for_hashing(
+            addresses.iter().map(|address| (*address, Some(Account::default()))),
+        )
+        .unwrap();
+    provider
+        .insert_storage_for_hashing(addresses.iter().map(|address| {
+            let value = U256::from_be_bytes(keccak256(address).0);
+            (*address, slots.iter().map(move |slot| StorageEntry { key: *slot, value }))
+        }))
+        .unwrap();
+    let state_root = StateRoot::from_tx(provider.tx_ref()).root().unwrap();
+
+    let hashed_slots = slots[..2].iter().map(keccak256).collect::<B256HashSet>();
+    let state = HashedPostState {
+        accounts: addresses[..4]
+            .iter()
+            .map(|address| (keccak256(address), Some(Account { nonce: 1, ..Default::default() })))
+            .collect(),
+        storages: addresses[..2]
+            .iter()
+            .map(|address| {
+                let storage = hashed_slots.iter().map(|hashed_slot| (*hashed_slot, U256::from(1)));
+                (keccak256(address), HashedStorage::from_iter(false, storage))
+            })
+            .collect(),
+    };
+    let witness = TrieWitness::from_tx(provider.tx_ref()).compute(state).unwrap().into_flat_map();
+
+    // the changed state stays provable, but the storage roots of the accounts without changed
+    // slots are dropped
+    let mut targets = addresses[..4]
+        .iter()
+        .map(|address| (keccak256(address), B256HashSet::default()))
+        .collect::<B256HashMap<_>>();
+    for address in &addresses[..2] {
+        targets.insert(keccak256(address), hashed_slots.clone());
     }
+    let pruned = prune_unreachable_nodes(witness.clone(), state_root, &targets);
+    assert_eq!(pruned.len(), witness.len() - 2);
+    for (i, address) in addresses[..4].iter().enumerate() {
+        let slots = if i < 2 { &slots[..2] } else { &[][..] };
+        assert!(to_eip1186_proof(&pruned, state_root, *address, slots).is_some());
+    }
+
+    // pruning to a single account keeps exactly its proof
+    let (address, target_slots) = (addresses[0], &slots[..1]);
+    let targets = HashMap::from_iter([(
+        keccak256(address),
+        target_slots.iter().map(keccak256).collect::<B256HashSet>(),
+    )]);
+    let pruned = prune_unreachable_nodes(witness.clone(), state_root, &targets);
+    assert!(pruned.len() < witness.len());
+    assert!(pruned.iter().all(|(hash, node)| witness.get(hash) == Some(node)));
+    let proof = to_eip1186_proof(&pruned, state_root, address, target_slots).unwrap();
+    assert_eq!(
+        proof,
+        Proof::from_tx(provider.tx_ref()).account_proof(address, target_slots).unwrap()
+    );
+    let proof_nodes =
+        proof.proof.iter().chain(&proof.storage_proofs[0].proof).collect::<HashSet<_>>();
+    assert_eq!(proof_nodes.len(), pruned.len());
+    // the other accounts and slots are not proven anymore
+    assert_eq!(to_eip1186_proof(&pruned, state_root, addresses[1], &[]), None);
+    assert_eq!(to_eip1186_proof(&pruned, state_root, address, &slots[1..2]), None);
+
+    // nothing is kept for another state
+    assert!(prune_unreachable_nodes(witness, B256::random(), &targets).is_empty());
+}
+
+#[test]
+fn witness_stats() {
+    let factory = create_test_provider_factory();
+    let provider = factory.provider_rw().unwrap();
+
+    // `a` and `c` share a branch node below the root, which the root collapses into once `b` is
+    // removed
+    let [a, b, c] = [0x11, 0x20, 0x12].map(B256::repeat_byte);
+    let mut hashed_account_cursor =
+        provider.tx_ref().cursor_write::<tables::HashedAccounts>().unwrap();
+    for hashed_address in [a, c, b] {
+        hashed_account_cursor
+            .upsert(hashed_address, &Account { nonce: 1, ..Default::default() })
+            .unwrap();
+    }
+    let hashed_slot = B256::with_last_byte(1);
+    provider
+        .tx_ref()
+        .cursor_dup_write::<tables::HashedStorages>()
+        .unwrap()
+        .upsert(a, &StorageEntry { key: hashed_slot, value: U256::from(1) })
+        .unwrap();
+
+    // updating the storage of `a` only needs the nodes of the multiproof: the root, the shared
+    // branch node and the leaf of `a`, and the storage root leaf
+    let (witness, stats) = TrieWitness::from_tx(provider.tx_ref())
+        .compute_with_stats(HashedPostState {
+            accounts: HashMap::from_iter([(a, Some(Account { nonce: 1, ..Default::default() }))]),
+            storages: HashMap::from_iter([(
+                a,
+                HashedStorage::from_iter(false, [(hashed_slot, U256::from(2))]),
+            )]),
+        })
+        .unwrap();
+    assert_eq!((stats.account_nodes, stats.storage_nodes), (3, 1));
+    assert_eq!(stats.node_count(), witness.len());
+    assert_eq!(stats.total_bytes, witness.statistics().total_bytes);
+    assert_eq!(stats.max_depth, 2);
+    assert_eq!((stats.multiproof_nodes, stats.extra_nodes), (4, 0));
+    assert_eq!(stats.provider_lookups, 0);
+
+    // removing `b` needs the root, the leaf of `b` and its empty storage root from the multiproof,
+    // and additionally fetches the shared branch node, which is not part of the proof
+    let (witness, stats) = TrieWitness::from_tx(provider.tx_ref())
+        .compute_with_stats(HashedPostState {
+            accounts: HashMap::from_iter([(b, None)]),
+            storages: HashMap::default(),
+        })
+        .unwrap();
+    assert_eq!((stats.account_nodes, stats.storage_nodes), (3, 1));
+    assert_eq!(stats.node_count(), witness.len());
+    assert_eq!(stats.total_bytes, witness.statistics().total_bytes);
+    assert_eq!(stats.max_depth, 1);
+    assert_eq!((stats.multiproof_nodes, stats.extra_nodes), (3, 1));
+    assert_eq!(stats.provider_lookups, 1);
+
+    // the witness is the same as without statistics
+    assert_eq!(
+        witness,
+        TrieWitness::from_tx(provider.tx_ref())
+            .compute(HashedPostState {
+                accounts: HashMap::from_iter([(b, None)]),
+                storages: HashMap::default(),
+            })
+            .unwrap()
+    );
 }
@@ -0,0 +1,64 @@
+#![allow(missing_docs, unreachable_pub)]
+use alloy_primitives::{keccak256, B256, U256};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use reth_primitives_traits::Account;
+use reth_trie::{
+    hashed_cursor::{noop::NoopHashedCursorFactory, HashedPostStateCursorFactory},
+    trie_cursor::noop::NoopTrieCursorFactory,
+    witness::TrieWitness,
+    HashedPostState, HashedStorage,
+};
+
+pub fn witness(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Trie Witness");
+    group.sample_size(10);
+
+    for size in [100, 1_000] {
+        let (state, target) = generate_test_data(size);
+        let state_sorted = state.into_sorted();
+
+        for parallelism in [1, 4] {
+            group.bench_function(BenchmarkId::new(format!("{parallelism} threads"), size), |b| {
+                b.iter(|| {
+                    TrieWitness::new(
+                        NoopTrieCursorFactory::default(),
+                        HashedPostStateCursorFactory::new(
+                            NoopHashedCursorFactory::default(),
+                            &state_sorted,
+                        ),
+                    )
+                    .with_parallelism(parallelism)
+                    .compute(target.clone())
+                    .unwrap()
+                })
+            });
+        }
+    }
+}
+
+/// Generates a state of `size` accounts with 100 storage slots each, and a target state updating
+/// 10 slots of every account.
+fn generate_test_data(size: usize) -> (HashedPostState, HashedPostState) {
+    let mut state = HashedPostState::default();
+    let mut target = HashedPostState::default();
+    for i in 0..size {
+        let hashed_address = keccak256(B256::from(U256::from(i)));
+        let account = Some(Account { nonce: i as u64, ..Default::default() });
+        let slots = (0..100u64).map(|j| (keccak256(B256::from(U256::from(j))), U256::from(j + 1)));
+
+        state.accounts.insert(hashed_address, account);
+        state.storages.insert(hashed_address, HashedStorage::from_iter(false, slots.clone()));
+        target.accounts.insert(hashed_address, account);
+        target.storages.insert(
+            hashed_address,
+            HashedStorage::from_iter(
+                false,
+                slots.take(10).map(|(slot, value)| (slot, value * U256::from(2))),
+            ),
+        );
+    }
+    (state, target)
+}
+
+criterion_group!(witness_benches, witness);
+criterion_main!(witness_benches);
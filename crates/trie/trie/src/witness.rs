@@ -1,26 +1,792 @@
 use crate::{
     hashed_cursor::{HashedCursor, HashedCursorFactory},
-    prefix_set::TriePrefixSetsMut,
+    prefix_set::{PrefixSetMut, TriePrefixSetsMut},
     proof::{Proof, ProofBlindedProviderFactory},
     trie_cursor::TrieCursorFactory,
+    updates::TrieUpdates,
     HashedPostState,
 };
+use alloy_consensus::constants::KECCAK_EMPTY;
 use alloy_primitives::{
     keccak256,
-    map::{B256HashMap, B256HashSet, Entry, HashMap},
-    Bytes, B256,
+    map::{B256HashMap, B256HashSet, Entry, HashMap, HashSet},
+    Address, Bytes, B256, U256,
 };
+use alloy_rlp::{BufMut, Decodable, Encodable, Header, EMPTY_STRING_CODE};
+use alloy_trie::proof::ProofNodes;
 use itertools::Itertools;
 use reth_execution_errors::{
     SparseStateTrieErrorKind, SparseTrieError, SparseTrieErrorKind, StateProofError,
     TrieWitnessError,
 };
-use reth_trie_common::{MultiProofTargets, Nibbles};
+use reth_fs_util::FsPathError;
+use reth_primitives_traits::Account;
+use reth_trie_common::{
+    root::storage_root_unsorted, AccountProof, BranchNode, MultiProof, MultiProofTargets, Nibbles,
+    RlpNode, StorageProof, TrieAccount, TrieNode, EMPTY_ROOT_HASH,
+};
 use reth_trie_sparse::{
     blinded::{BlindedProvider, BlindedProviderFactory, RevealedNode},
-    SparseStateTrie,
+    RevealedSparseTrie, SparseStateTrie,
+};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
 };
-use std::sync::{mpsc, Arc};
+
+/// Trie nodes required to apply a state transition, as computed by [`TrieWitness::compute`],
+/// keyed by their hashes.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct StateWitness {
+    /// Nodes of the account trie.
+    pub account_nodes: B256HashMap<Bytes>,
+    /// Nodes of the storage tries, keyed by hashed address.
+    pub storage_nodes: B256HashMap<B256HashMap<Bytes>>,
+    /// Preimages of the hashed addresses and slots of the state transition, keyed by their
+    /// hashes. Only collected if enabled with [`TrieWitness::with_preimages`].
+    pub preimages: B256HashMap<Bytes>,
+}
+
+impl StateWitness {
+    /// Returns `true` if the witness contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.account_nodes.is_empty() && self.storage_nodes.values().all(|nodes| nodes.is_empty())
+    }
+
+    /// Returns the number of nodes of all tries. Nodes shared between tries are counted once per
+    /// trie.
+    pub fn len(&self) -> usize {
+        self.account_nodes.len() +
+            self.storage_nodes.values().map(|nodes| nodes.len()).sum::<usize>()
+    }
+
+    /// Merges the nodes and preimages of another witness, e.g. one computed independently for
+    /// another state transition, into this witness.
+    pub fn extend(&mut self, other: Self) {
+        for (hash, node) in other.account_nodes {
+            self.account_nodes.entry(hash).or_insert(node);
+        }
+        for (hashed_address, nodes) in other.storage_nodes {
+            let storage_nodes = self.storage_nodes.entry(hashed_address).or_default();
+            for (hash, node) in nodes {
+                storage_nodes.entry(hash).or_insert(node);
+            }
+        }
+        for (hash, preimage) in other.preimages {
+            self.preimages.entry(hash).or_insert(preimage);
+        }
+    }
+
+    /// Records the nodes of the account proof and of its storage proofs.
+    pub fn extend_with_proof(&mut self, proof: &AccountProof) {
+        for node in &proof.proof {
+            self.insert(None, node.clone());
+        }
+        let hashed_address = keccak256(proof.address);
+        for node in proof.storage_proofs.iter().flat_map(|proof| &proof.proof) {
+            self.insert(Some(hashed_address), node.clone());
+        }
+    }
+
+    /// Records a node of the account trie, or of the storage trie of `hashed_address`.
+    ///
+    /// Returns `false` if the node was already recorded for the trie.
+    fn insert(&mut self, hashed_address: Option<B256>, node: Bytes) -> bool {
+        let nodes = match hashed_address {
+            Some(hashed_address) => self.storage_nodes.entry(hashed_address).or_default(),
+            None => &mut self.account_nodes,
+        };
+        match nodes.entry(keccak256(&node)) {
+            Entry::Vacant(entry) => {
+                entry.insert(node);
+                true
+            }
+            Entry::Occupied(_) => false,
+        }
+    }
+
+    /// Verifies that the witness is a partial state trie with the root `expected_root`.
+    ///
+    /// Every node must be keyed by its hash and be reachable from `expected_root`, or for storage
+    /// nodes from the storage root of the account leaf at the hashed address. Returns `false` if
+    /// that is not the case, e.g. for nodes of another state or for a witness without the root
+    /// node, and an error if a reachable node can't be decoded.
+    pub fn verify(&self, expected_root: B256) -> Result<bool, StateProofError> {
+        let mut storage_roots = B256HashMap::default();
+        if !verify_trie(&self.account_nodes, expected_root, Some(&mut storage_roots))? {
+            return Ok(false)
+        }
+
+        for (hashed_address, nodes) in &self.storage_nodes {
+            if nodes.is_empty() {
+                continue
+            }
+            let Some(storage_root) = storage_roots.get(hashed_address) else { return Ok(false) };
+            if !verify_trie(nodes, *storage_root, None)? {
+                return Ok(false)
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Returns statistics about the nodes of all tries, see [`witness_statistics`].
+    pub fn statistics(&self) -> WitnessStatistics {
+        let mut statistics = WitnessStatistics::default();
+        for node in self
+            .account_nodes
+            .values()
+            .chain(self.storage_nodes.values().flat_map(|nodes| nodes.values()))
+        {
+            statistics.record(node);
+        }
+        statistics
+    }
+
+    /// Merges the nodes of all tries into a single map, as returned by `debug_executionWitness`.
+    ///
+    /// Preimages are not included.
+    pub fn into_flat_map(self) -> B256HashMap<Bytes> {
+        self.into_witness().into_map()
+    }
+
+    /// Merges the nodes of all tries into a [`Witness`] ordered by their hashes.
+    ///
+    /// Preimages are not included.
+    pub fn into_witness(self) -> Witness {
+        let mut nodes = BTreeMap::from_iter(self.account_nodes);
+        nodes.extend(self.storage_nodes.into_values().flatten());
+        Witness(nodes)
+    }
+}
+
+/// Trie nodes of all tries of a [`StateWitness`], keyed and ordered by their hashes.
+///
+/// Unlike the maps of [`StateWitness`], the nodes are iterated in a stable order, so that output
+/// derived from them, e.g. RPC responses or test fixtures, is deterministic.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct Witness(BTreeMap<B256, Bytes>);
+
+impl Witness {
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the witness contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the nodes ordered by their hashes.
+    pub fn into_sorted_vec(self) -> Vec<Bytes> {
+        self.0.into_values().collect()
+    }
+
+    /// Returns the nodes keyed by their hashes.
+    pub fn into_map(self) -> B256HashMap<Bytes> {
+        self.0.into_iter().collect()
+    }
+}
+
+/// Encodes the nodes as an RLP list of `[hash, node]` pairs sorted by hash, see
+/// [`encode_witness`].
+impl Encodable for Witness {
+    fn encode(&self, out: &mut dyn BufMut) {
+        encode_witness_entries(self.0.iter(), out);
+    }
+
+    fn length(&self) -> usize {
+        witness_entries_header(self.0.iter()).length_with_payload()
+    }
+}
+
+/// Decodes the format of [`encode_witness`], rejecting entries that are not strictly sorted by
+/// hash.
+impl Decodable for Witness {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let mut payload = Header::decode_bytes(buf, true)?;
+        let mut nodes = BTreeMap::new();
+        let mut last_hash = None;
+        while !payload.is_empty() {
+            let mut entry = Header::decode_bytes(&mut payload, true)?;
+            let hash = B256::decode(&mut entry)?;
+            let node = Bytes::decode(&mut entry)?;
+            if !entry.is_empty() {
+                return Err(alloy_rlp::Error::UnexpectedLength)
+            }
+            if last_hash.is_some_and(|last_hash| last_hash >= hash) {
+                return Err(alloy_rlp::Error::Custom("witness entries are not sorted by hash"))
+            }
+            last_hash = Some(hash);
+            nodes.insert(hash, node);
+        }
+        Ok(Self(nodes))
+    }
+}
+
+/// RLP encodes the nodes of a witness, e.g. as returned by [`StateWitness::into_flat_map`], as a
+/// list of `[hash, node]` pairs sorted by hash, so that the encoding is canonical.
+pub fn encode_witness(witness: &B256HashMap<Bytes>) -> Bytes {
+    let mut entries = witness.iter().collect::<Vec<_>>();
+    entries.sort_unstable_by_key(|(hash, _)| *hash);
+    let mut out = Vec::new();
+    encode_witness_entries(entries.into_iter(), &mut out);
+    out.into()
+}
+
+/// Decodes nodes encoded with [`encode_witness`].
+pub fn decode_witness(mut data: &[u8]) -> Result<B256HashMap<Bytes>, alloy_rlp::Error> {
+    let witness = Witness::decode(&mut data)?;
+    if !data.is_empty() {
+        return Err(alloy_rlp::Error::UnexpectedLength)
+    }
+    Ok(witness.into_map())
+}
+
+/// Decodes a single node of a witness.
+pub fn decode_witness_node(encoded: &Bytes) -> Result<TrieNode, alloy_rlp::Error> {
+    TrieNode::decode(&mut &encoded[..])
+}
+
+/// Returns the RLP list header of a `[hash, node]` pair.
+fn witness_entry_header(node: &Bytes) -> Header {
+    Header { list: true, payload_length: B256::len_bytes() + 1 + node.length() }
+}
+
+/// Returns the RLP list header of the `[hash, node]` pairs.
+fn witness_entries_header<'a>(entries: impl Iterator<Item = (&'a B256, &'a Bytes)>) -> Header {
+    let payload_length =
+        entries.map(|(_, node)| witness_entry_header(node).length_with_payload()).sum();
+    Header { list: true, payload_length }
+}
+
+/// RLP encodes the `[hash, node]` pairs in the order of the iterator.
+fn encode_witness_entries<'a>(
+    entries: impl Iterator<Item = (&'a B256, &'a Bytes)> + Clone,
+    out: &mut dyn BufMut,
+) {
+    witness_entries_header(entries.clone()).encode(out);
+    for (hash, node) in entries {
+        witness_entry_header(node).encode(out);
+        hash.encode(out);
+        node.encode(out);
+    }
+}
+
+/// Number and size of the nodes of a witness by node type, e.g. for benchmarking proof sizes.
+///
+/// Nodes embedded in their parent are not counted separately.
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub struct WitnessStatistics {
+    /// The number of nodes, including nodes that can't be decoded.
+    pub node_count: usize,
+    /// The total size of the RLP encoded nodes in bytes.
+    pub total_bytes: usize,
+    /// The number of branch nodes.
+    pub branch_nodes: usize,
+    /// The number of extension nodes.
+    pub extension_nodes: usize,
+    /// The number of leaf nodes.
+    pub leaf_nodes: usize,
+}
+
+impl WitnessStatistics {
+    fn record(&mut self, node: &Bytes) {
+        self.node_count += 1;
+        self.total_bytes += node.len();
+        match decode_witness_node(node) {
+            Ok(TrieNode::Branch(_)) => self.branch_nodes += 1,
+            Ok(TrieNode::Extension(_)) => self.extension_nodes += 1,
+            Ok(TrieNode::Leaf(_)) => self.leaf_nodes += 1,
+            Ok(TrieNode::EmptyRoot) | Err(_) => {}
+        }
+    }
+}
+
+/// Returns statistics about the nodes of a witness keyed by their hashes, e.g. as returned by
+/// [`StateWitness::into_flat_map`].
+pub fn witness_statistics(witness: &B256HashMap<Bytes>) -> WitnessStatistics {
+    let mut statistics = WitnessStatistics::default();
+    for node in witness.values() {
+        statistics.record(node);
+    }
+    statistics
+}
+
+/// Statistics about the computation of a witness, as returned by
+/// [`TrieWitness::compute_with_stats`], e.g. for tuning witness-based protocols.
+///
+/// Only the nodes recorded by the computation are counted, nodes merged beforehand are not. See
+/// [`WitnessStatistics`] for statistics about the node types of a witness.
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WitnessStats {
+    /// The number of recorded account trie nodes.
+    pub account_nodes: usize,
+    /// The number of recorded storage trie nodes.
+    pub storage_nodes: usize,
+    /// The total size of the recorded RLP encoded nodes in bytes.
+    pub total_bytes: usize,
+    /// The maximum depth of a recorded node in nibbles, the root being at depth 0.
+    pub max_depth: usize,
+    /// The number of recorded nodes that were part of the multiproof of the proof targets.
+    pub multiproof_nodes: usize,
+    /// The number of recorded nodes that were revealed beyond the multiproof while updating the
+    /// tries, e.g. the siblings of removed leaves.
+    pub extra_nodes: usize,
+    /// The number of lookups of blinded nodes from the provider while updating the tries.
+    pub provider_lookups: usize,
+}
+
+impl WitnessStats {
+    /// Returns the total number of recorded nodes.
+    pub const fn node_count(&self) -> usize {
+        self.account_nodes + self.storage_nodes
+    }
+
+    fn record(&mut self, hashed_address: Option<B256>, depth: usize, node: &Bytes) {
+        if hashed_address.is_some() {
+            self.storage_nodes += 1;
+        } else {
+            self.account_nodes += 1;
+        }
+        self.total_bytes += node.len();
+        self.max_depth = self.max_depth.max(depth);
+    }
+}
+
+/// Accounts and storage slots revealed by a witness, as returned by [`verify_witness`].
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct VerifiedWitness {
+    /// Accounts whose leaves are reachable from the state root, keyed by hashed address.
+    pub accounts: B256HashMap<TrieAccount>,
+    /// Storage slots whose leaves are reachable from the storage root of their account, keyed by
+    /// hashed address and hashed slot.
+    pub storages: B256HashMap<B256HashMap<U256>>,
+    /// Hashes of the nodes that are not reachable from the state root.
+    pub unreachable: B256HashSet,
+}
+
+/// Verifies a witness keyed by node hashes, e.g. as returned by [`StateWitness::into_flat_map`],
+/// against the state root `root`, and returns the accounts and storage slots it reveals.
+///
+/// Every node must be keyed by its hash, otherwise [`TrieWitnessError::NodeHashMismatch`] is
+/// returned. The account trie is walked from `root` and the storage trie of every revealed
+/// account from its storage root, failing if a reachable node can't be decoded. Nodes that are
+/// not reachable, e.g. nodes of another state, are not an error but reported in
+/// [`VerifiedWitness::unreachable`].
+pub fn verify_witness(
+    root: B256,
+    witness: &B256HashMap<Bytes>,
+) -> Result<VerifiedWitness, TrieWitnessError> {
+    for (key, node) in witness {
+        let hash = keccak256(node);
+        if hash != *key {
+            return Err(TrieWitnessError::NodeHashMismatch { key: *key, hash })
+        }
+    }
+
+    let mut verified = VerifiedWitness::default();
+    let mut reached = walk_trie::<TrieWitnessError>(witness, root, |path, value| {
+        let hashed_address = leaf_key(&path).ok_or(INVALID_LEAF_PATH)?;
+        verified.accounts.insert(hashed_address, TrieAccount::decode(&mut &value[..])?);
+        Ok(())
+    })?;
+    for (hashed_address, account) in &verified.accounts {
+        let mut slots = B256HashMap::default();
+        reached.extend(walk_trie::<TrieWitnessError>(
+            witness,
+            account.storage_root,
+            |path, value| {
+                let hashed_slot = leaf_key(&path).ok_or(INVALID_LEAF_PATH)?;
+                slots.insert(hashed_slot, U256::decode(&mut &value[..])?);
+                Ok(())
+            },
+        )?);
+        if !slots.is_empty() {
+            verified.storages.insert(*hashed_address, slots);
+        }
+    }
+
+    verified.unreachable =
+        witness.keys().filter(|hash| !reached.contains(*hash)).copied().collect();
+    Ok(verified)
+}
+
+/// Reconstructs the proof of the account at `address` and its storage `slots` from a witness
+/// keyed by node hashes, e.g. as returned by [`StateWitness::into_flat_map`], so that an
+/// `eth_getProof` (EIP-1186) response can be derived from an already computed witness.
+///
+/// The proofs are ordered from the root to the last node on the path of the key, like the proofs
+/// of [`Proof::account_proof`]. Returns `None` if a node on the path of the account or of a slot
+/// is missing from the witness or can't be decoded.
+pub fn to_eip1186_proof(
+    witness: &B256HashMap<Bytes>,
+    state_root: B256,
+    address: Address,
+    slots: &[B256],
+) -> Option<AccountProof> {
+    let mut proof = Vec::new();
+    let value =
+        witness_proof_path(witness, state_root, Nibbles::unpack(keccak256(address)), &mut proof)?;
+    let account = value.map(|value| TrieAccount::decode(&mut &value[..])).transpose().ok()?;
+    let info = account.map(|account| Account {
+        balance: account.balance,
+        nonce: account.nonce,
+        bytecode_hash: (account.code_hash != KECCAK_EMPTY).then_some(account.code_hash),
+    });
+    let storage_root = account.map_or(EMPTY_ROOT_HASH, |account| account.storage_root);
+
+    let mut storage_proofs = Vec::with_capacity(slots.len());
+    for slot in slots {
+        let mut storage_proof = StorageProof::new(*slot);
+        let value = witness_proof_path(
+            witness,
+            storage_root,
+            storage_proof.nibbles.clone(),
+            &mut storage_proof.proof,
+        )?;
+        if let Some(value) = value {
+            storage_proof.value = U256::decode(&mut &value[..]).ok()?;
+        }
+        storage_proofs.push(storage_proof);
+    }
+
+    Some(AccountProof { address, info, proof, storage_root, storage_proofs })
+}
+
+/// Removes the nodes of a witness keyed by node hashes that are not on the proof path of any of
+/// the `targets`, i.e. of the targeted accounts in the trie with the given `state_root` and of
+/// their targeted storage slots. Targets are keyed by hashed address and hashed slot.
+///
+/// The pruned witness still proves the targets, see [`to_eip1186_proof`], but it may lack nodes
+/// required to apply a state transition, e.g. the siblings of removed leaves.
+pub fn prune_unreachable_nodes(
+    mut witness: B256HashMap<Bytes>,
+    state_root: B256,
+    targets: &MultiProofTargets,
+) -> B256HashMap<Bytes> {
+    // the nodes on the proof paths of all targets
+    let mut required = Vec::new();
+    for (hashed_address, hashed_slots) in targets {
+        let value = witness_proof_path(
+            &witness,
+            state_root,
+            Nibbles::unpack(hashed_address),
+            &mut required,
+        );
+
+        // the storage trie can only be walked if the account is revealed
+        let Some(Some(value)) = value else { continue };
+        let Ok(account) = TrieAccount::decode(&mut &value[..]) else { continue };
+        for hashed_slot in hashed_slots {
+            let _ = witness_proof_path(
+                &witness,
+                account.storage_root,
+                Nibbles::unpack(hashed_slot),
+                &mut required,
+            );
+        }
+    }
+
+    let required = required.iter().map(keccak256).collect::<B256HashSet>();
+    witness.retain(|hash, _| required.contains(hash));
+    witness
+}
+
+/// Pushes the nodes on the path of `key` in the trie with the given `root` to `proof`, starting
+/// with the root node, and returns the value of the leaf at `key`, if it exists.
+///
+/// Returns `None` if a node on the path is missing from the `witness` or can't be decoded. The
+/// nodes up to the missing node are pushed nonetheless.
+fn witness_proof_path(
+    witness: &B256HashMap<Bytes>,
+    root: B256,
+    key: Nibbles,
+    proof: &mut Vec<Bytes>,
+) -> Option<Option<Vec<u8>>> {
+    if root == EMPTY_ROOT_HASH {
+        proof.push(Bytes::from([EMPTY_STRING_CODE]));
+        return Some(None)
+    }
+
+    let mut path = Nibbles::default();
+    let mut next = RlpNode::word_rlp(&root);
+    loop {
+        let node = match next.as_hash() {
+            Some(hash) => {
+                let node = witness.get(&hash)?;
+                proof.push(node.clone());
+                decode_witness_node(node).ok()?
+            }
+            None => TrieNode::decode(&mut next.as_slice()).ok()?,
+        };
+
+        match node {
+            TrieNode::Branch(branch) => {
+                let Some(nibble) = key.get(path.len()) else { break };
+                let Some((child_path, child)) = branch_node_children(&path, &branch)
+                    .into_iter()
+                    .find(|(child_path, _)| child_path.last() == Some(*nibble))
+                else {
+                    break
+                };
+                path = child_path;
+                next = child;
+            }
+            TrieNode::Extension(extension) => {
+                if !key.slice(path.len()..).starts_with(&extension.key) {
+                    break
+                }
+                path.extend_from_slice(&extension.key);
+                next = extension.child;
+            }
+            TrieNode::Leaf(leaf) => {
+                path.extend_from_slice(&leaf.key);
+                return Some((path == key).then_some(leaf.value))
+            }
+            TrieNode::EmptyRoot => break,
+        }
+    }
+    Some(None)
+}
+
+/// Error for a leaf whose full path isn't 32 bytes.
+const INVALID_LEAF_PATH: alloy_rlp::Error = alloy_rlp::Error::Custom("leaf path is not 32 bytes");
+
+/// Receives the nodes of a witness as they are recorded by [`TrieWitness::compute_into`], e.g. to
+/// write them to disk instead of keeping the whole witness in memory.
+pub trait WitnessSink {
+    /// Records the node with the given hash.
+    ///
+    /// The same node may be recorded more than once, e.g. if it is part of multiple tries.
+    fn record(&mut self, hash: B256, node: Bytes) -> Result<(), TrieWitnessError>;
+}
+
+impl WitnessSink for B256HashMap<Bytes> {
+    fn record(&mut self, hash: B256, node: Bytes) -> Result<(), TrieWitnessError> {
+        self.entry(hash).or_insert(node);
+        Ok(())
+    }
+}
+
+/// A [`WitnessSink`] that appends the nodes to a file.
+///
+/// Every entry consists of the hash of the node, the length of the node as a little-endian `u32`
+/// and the node itself. The file can be loaded with [`read_witness_file`].
+#[derive(Debug)]
+pub struct FileWitnessSink {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    /// Hashes of the written nodes, if nodes are deduplicated.
+    seen: Option<B256HashSet>,
+}
+
+impl FileWitnessSink {
+    /// Creates the file at `path`, truncating it if it exists.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, TrieWitnessError> {
+        let path = path.as_ref();
+        let file = reth_fs_util::create_file(path).map_err(sink_error)?;
+        Ok(Self { writer: BufWriter::new(file), path: path.to_path_buf(), seen: None })
+    }
+
+    /// Skip nodes that were already written, at the cost of keeping the hashes of all written
+    /// nodes in memory.
+    pub fn with_deduplication(mut self, deduplicate: bool) -> Self {
+        self.seen = deduplicate.then(B256HashSet::default);
+        self
+    }
+
+    /// Flushes the buffered entries to the file.
+    pub fn finish(mut self) -> Result<(), TrieWitnessError> {
+        self.writer.flush().map_err(|err| sink_error(FsPathError::write(err, &self.path)))
+    }
+}
+
+impl WitnessSink for FileWitnessSink {
+    fn record(&mut self, hash: B256, node: Bytes) -> Result<(), TrieWitnessError> {
+        if self.seen.as_mut().is_some_and(|seen| !seen.insert(hash)) {
+            return Ok(())
+        }
+        let len = u32::try_from(node.len()).map_err(|_| {
+            sink_error(io::Error::new(io::ErrorKind::InvalidInput, "node too large"))
+        })?;
+        self.writer
+            .write_all(hash.as_slice())
+            .and_then(|()| self.writer.write_all(&len.to_le_bytes()))
+            .and_then(|()| self.writer.write_all(&node))
+            .map_err(|err| sink_error(FsPathError::write(err, &self.path)))
+    }
+}
+
+/// Loads the nodes written by a [`FileWitnessSink`], keyed by their hashes.
+pub fn read_witness_file(path: impl AsRef<Path>) -> Result<B256HashMap<Bytes>, TrieWitnessError> {
+    let path = path.as_ref();
+    let data = reth_fs_util::read(path).map_err(sink_error)?;
+    let mut nodes = B256HashMap::default();
+    let mut data = &data[..];
+    while !data.is_empty() {
+        let Some((hash, len, rest)) = data
+            .split_first_chunk::<32>()
+            .and_then(|(hash, rest)| Some((hash, rest.split_first_chunk::<4>()?)))
+            .map(|(hash, (len, rest))| (B256::from(hash), u32::from_le_bytes(*len), rest))
+            .filter(|(_, len, rest)| rest.len() >= *len as usize)
+        else {
+            let err = io::Error::new(io::ErrorKind::UnexpectedEof, "truncated witness entry");
+            return Err(sink_error(FsPathError::read(err, path)))
+        };
+        let (node, rest) = rest.split_at(len as usize);
+        nodes.insert(hash, Bytes::copy_from_slice(node));
+        data = rest;
+    }
+    Ok(nodes)
+}
+
+fn sink_error(err: impl core::error::Error + Send + Sync + 'static) -> TrieWitnessError {
+    TrieWitnessError::Sink(Box::new(err))
+}
+
+/// Destination of the nodes recorded by a [`WitnessRecorder`].
+trait RecordNode {
+    /// Records a node of the account trie, or of the storage trie of `hashed_address`.
+    ///
+    /// Returns `false` if the node is known to be recorded already.
+    fn record_node(
+        &mut self,
+        hashed_address: Option<B256>,
+        node: Bytes,
+    ) -> Result<bool, TrieWitnessError>;
+}
+
+impl RecordNode for StateWitness {
+    fn record_node(
+        &mut self,
+        hashed_address: Option<B256>,
+        node: Bytes,
+    ) -> Result<bool, TrieWitnessError> {
+        Ok(self.insert(hashed_address, node))
+    }
+}
+
+impl<S: WitnessSink + ?Sized> RecordNode for &mut S {
+    fn record_node(&mut self, _: Option<B256>, node: Bytes) -> Result<bool, TrieWitnessError> {
+        self.record(keccak256(&node), node)?;
+        Ok(true)
+    }
+}
+
+/// Records the nodes of a witness, by default into a [`StateWitness`].
+///
+/// All recorded nodes are nodes of the current tries, so a path always maps to the same node, and
+/// nodes at already recorded paths are skipped without hashing them again.
+#[derive(Debug)]
+struct WitnessRecorder<W = StateWitness> {
+    /// Recorded witness.
+    witness: W,
+    /// Paths of the recorded nodes, with the hashed address for storage trie nodes.
+    recorded_paths: HashSet<(Option<B256>, Nibbles)>,
+    /// Number of nodes in the witness.
+    node_count: usize,
+    /// Maximum number of nodes in the witness, see [`TrieWitness::with_max_nodes`].
+    max_nodes: Option<usize>,
+    /// Statistics about the recorded nodes.
+    stats: WitnessStats,
+}
+
+impl<W: RecordNode> WitnessRecorder<W> {
+    /// Creates a recorder for a witness that already contains `node_count` nodes.
+    fn new(witness: W, node_count: usize, max_nodes: Option<usize>) -> Self {
+        Self {
+            witness,
+            recorded_paths: HashSet::default(),
+            node_count,
+            max_nodes,
+            stats: WitnessStats::default(),
+        }
+    }
+
+    /// Records the node at `path` of the account trie, or of the storage trie of `hashed_address`.
+    ///
+    /// Returns `true` if the node was newly recorded.
+    fn record(
+        &mut self,
+        hashed_address: Option<B256>,
+        path: Nibbles,
+        node: &Bytes,
+    ) -> Result<bool, TrieWitnessError> {
+        let depth = path.len();
+        if self.recorded_paths.insert((hashed_address, path)) &&
+            self.witness.record_node(hashed_address, node.clone())?
+        {
+            self.stats.record(hashed_address, depth, node);
+            self.node_count += 1;
+            if let Some(max_nodes) = self.max_nodes.filter(|max| self.node_count > *max) {
+                return Err(TrieWitnessError::WitnessTooLarge(max_nodes))
+            }
+            return Ok(true)
+        }
+        Ok(false)
+    }
+
+    /// Records a node that was revealed beyond the multiproof, see [`Self::record`].
+    fn record_extra(
+        &mut self,
+        hashed_address: Option<B256>,
+        path: Nibbles,
+        node: &Bytes,
+    ) -> Result<(), TrieWitnessError> {
+        if self.record(hashed_address, path, node)? {
+            self.stats.extra_nodes += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Result of [`TrieWitness::compute_with_updates`].
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct WitnessRootResult {
+    /// The state transition witness.
+    pub witness: StateWitness,
+    /// The state root after applying the state transition.
+    pub root: B256,
+    /// The trie updates of the state transition, including removed nodes.
+    pub updates: TrieUpdates,
+}
+
+/// The state root and trie updates of a state transition, see [`WitnessRootResult`].
+type RootAndUpdates = (B256, TrieUpdates);
+
+/// Function looking up the preimage of a hashed address or slot, see
+/// [`TrieWitness::with_preimage_provider`].
+#[derive(Clone)]
+pub struct PreimageProvider(Arc<PreimageFn>);
+
+type PreimageFn = dyn Fn(&B256) -> Option<Bytes> + Send + Sync;
+
+impl PreimageProvider {
+    /// Creates a new preimage provider from the lookup function.
+    pub fn new(f: impl Fn(&B256) -> Option<Bytes> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    /// Returns the preimage of `hash`, if known.
+    pub fn preimage(&self, hash: &B256) -> Option<Bytes> {
+        (self.0)(hash)
+    }
+}
+
+impl core::fmt::Debug for PreimageProvider {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PreimageProvider").finish_non_exhaustive()
+    }
+}
 
 /// State transition witness for the trie.
 #[derive(Debug)]
@@ -32,7 +798,15 @@ pub struct TrieWitness<T, H> {
     /// A set of prefix sets that have changes.
     prefix_sets: TriePrefixSetsMut,
     /// Recorded witness.
-    witness: B256HashMap<Bytes>,
+    witness: StateWitness,
+    /// Number of threads used to update storage tries.
+    parallelism: usize,
+    /// Whether to collect the preimages of the proof targets.
+    collect_preimages: bool,
+    /// Lookup for the preimages of the proof targets.
+    preimage_provider: Option<PreimageProvider>,
+    /// Maximum number of nodes in the witness.
+    max_nodes: Option<usize>,
 }
 
 impl<T, H> TrieWitness<T, H> {
@@ -42,7 +816,11 @@ impl<T, H> TrieWitness<T, H> {
             trie_cursor_factory,
             hashed_cursor_factory,
             prefix_sets: TriePrefixSetsMut::default(),
-            witness: HashMap::default(),
+            witness: StateWitness::default(),
+            parallelism: 1,
+            collect_preimages: false,
+            preimage_provider: None,
+            max_nodes: None,
         }
     }
 
@@ -53,6 +831,10 @@ impl<T, H> TrieWitness<T, H> {
             hashed_cursor_factory: self.hashed_cursor_factory,
             prefix_sets: self.prefix_sets,
             witness: self.witness,
+            parallelism: self.parallelism,
+            collect_preimages: self.collect_preimages,
+            preimage_provider: self.preimage_provider,
+            max_nodes: self.max_nodes,
         }
     }
 
@@ -63,6 +845,10 @@ impl<T, H> TrieWitness<T, H> {
             hashed_cursor_factory,
             prefix_sets: self.prefix_sets,
             witness: self.witness,
+            parallelism: self.parallelism,
+            collect_preimages: self.collect_preimages,
+            preimage_provider: self.preimage_provider,
+            max_nodes: self.max_nodes,
         }
     }
 
@@ -71,6 +857,55 @@ impl<T, H> TrieWitness<T, H> {
         self.prefix_sets = prefix_sets;
         self
     }
+
+    /// Set the number of threads used to update the storage tries of the proof targets.
+    ///
+    /// Storage tries are independent of each other, so with a parallelism greater than 1 they are
+    /// updated concurrently before the account trie is updated sequentially. The resulting witness
+    /// is identical to the sequential one. Defaults to 1.
+    pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Set whether to collect the preimages of all hashed addresses and slots of the state
+    /// transition into [`StateWitness::preimages`]. Defaults to `false`.
+    ///
+    /// Preimages are looked up with the provider set with [`Self::with_preimage_provider`], and
+    /// hashes with an unknown preimage are skipped.
+    pub const fn with_preimages(mut self, collect_preimages: bool) -> Self {
+        self.collect_preimages = collect_preimages;
+        self
+    }
+
+    /// Set the maximum number of nodes in the witness, including the nodes merged beforehand.
+    ///
+    /// The computation fails with [`TrieWitnessError::WitnessTooLarge`] as soon as the witness
+    /// exceeds `max_nodes` nodes, to bound the memory used for large state transitions.
+    pub const fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    /// Set the provider used to look up preimages, see [`Self::with_preimages`].
+    pub fn with_preimage_provider(mut self, preimage_provider: PreimageProvider) -> Self {
+        self.preimage_provider = Some(preimage_provider);
+        self
+    }
+
+    /// Merge an independently computed witness into the recorded witness, so that the computed
+    /// witness includes its nodes. See [`StateWitness::extend`].
+    pub fn merge(mut self, other: StateWitness) -> Self {
+        self.witness.extend(other);
+        self
+    }
+
+    /// Record the nodes of the account proof in the witness, so that the computed witness
+    /// includes them. See [`StateWitness::extend_with_proof`].
+    pub fn extend_with_proof(mut self, proof: &AccountProof) -> Self {
+        self.witness.extend_with_proof(proof);
+        self
+    }
 }
 
 impl<T, H> TrieWitness<T, H>
@@ -84,88 +919,373 @@ where
     /// # Arguments
     ///
     /// `state` - state transition containing both modified and touched accounts and storage slots.
-    pub fn compute(
-        mut self,
+    pub fn compute(self, state: HashedPostState) -> Result<StateWitness, TrieWitnessError> {
+        if state.is_empty() {
+            return Ok(self.witness)
+        }
+
+        let proof_targets = self.get_proof_targets(&state)?;
+        Ok(self.compute_witness(proof_targets, &state, false, false, &B256HashMap::default())?.0)
+    }
+
+    /// Same as [`Self::compute`], but also returns statistics about the computation, see
+    /// [`WitnessStats`].
+    pub fn compute_with_stats(
+        self,
         state: HashedPostState,
+    ) -> Result<(StateWitness, WitnessStats), TrieWitnessError> {
+        if state.is_empty() {
+            return Ok((self.witness, WitnessStats::default()))
+        }
+
+        let proof_targets = self.get_proof_targets(&state)?;
+        let (witness, _, stats) =
+            self.compute_witness(proof_targets, &state, false, false, &B256HashMap::default())?;
+        Ok((witness, stats))
+    }
+
+    /// Same as [`Self::compute`], but reuses the nodes of a previously computed witness, e.g. of
+    /// the previous block, and returns the witness as a flat map.
+    ///
+    /// Nodes that are needed beyond the proofs of the state transition, such as the siblings of
+    /// removed leaves, are taken from `prior_witness` instead of being fetched from the database
+    /// if their hashes are present. The returned witness includes all nodes of `prior_witness`.
+    pub fn compute_incremental(
+        self,
+        state: HashedPostState,
+        prior_witness: B256HashMap<Bytes>,
     ) -> Result<B256HashMap<Bytes>, TrieWitnessError> {
+        let max_nodes = self.max_nodes;
+        let mut witness = if state.is_empty() {
+            self.witness.into_flat_map()
+        } else {
+            let proof_targets = self.get_proof_targets(&state)?;
+            self.compute_witness(proof_targets, &state, false, false, &prior_witness)?
+                .0
+                .into_flat_map()
+        };
+        witness.extend(prior_witness);
+        if let Some(max_nodes) = max_nodes.filter(|max| witness.len() > *max) {
+            return Err(TrieWitnessError::WitnessTooLarge(max_nodes))
+        }
+        Ok(witness)
+    }
+
+    /// Same as [`Self::compute`], but records the nodes into `sink` as they are computed instead of
+    /// collecting them in a [`StateWitness`].
+    ///
+    /// Nodes merged into the witness beforehand are recorded first. Preimages are not recorded.
+    ///
+    /// Note: the proofs of the state transition are still held in memory while the nodes are
+    /// recorded.
+    pub fn compute_into(
+        mut self,
+        state: HashedPostState,
+        sink: &mut impl WitnessSink,
+    ) -> Result<(), TrieWitnessError> {
+        let merged = std::mem::take(&mut self.witness).into_witness();
+        let node_count = merged.len();
+        for (hash, node) in merged.0 {
+            sink.record(hash, node)?;
+        }
         if state.is_empty() {
-            return Ok(self.witness)
+            return Ok(())
         }
 
         let proof_targets = self.get_proof_targets(&state)?;
+        let mut recorder = WitnessRecorder::new(sink, node_count, self.max_nodes);
+        self.compute_inner(
+            proof_targets,
+            &state,
+            false,
+            false,
+            &B256HashMap::default(),
+            &mut recorder,
+        )?;
+        Ok(())
+    }
+
+    /// Compute the state transition witness together with the resulting state root and the trie
+    /// updates, so that the intermediate nodes can be persisted.
+    ///
+    /// See [`Self::compute`] for details.
+    pub fn compute_with_updates(
+        self,
+        state: HashedPostState,
+    ) -> Result<WitnessRootResult, TrieWitnessError> {
+        let proof_targets = self.get_proof_targets(&state)?;
+        let (witness, root_and_updates, _) =
+            self.compute_witness(proof_targets, &state, false, true, &B256HashMap::default())?;
+        let (root, updates) = root_and_updates.expect("updates are retained");
+        Ok(WitnessRootResult { witness, root, updates })
+    }
+
+    /// Compute the witness for the given subset of accounts and storage slots only.
+    ///
+    /// Targets that are changed by `state` are updated with their new values, the other targets are
+    /// left untouched and only their current values are proven. Unlike [`Self::compute`], accounts
+    /// absent from `state` are not an error. Changes in `state` outside of `targets` are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// `targets` - hashed addresses and hashed slots to compute the witness for.
+    /// `state` - state transition containing both modified and touched accounts and storage slots.
+    pub fn compute_targets(
+        self,
+        targets: MultiProofTargets,
+        state: &HashedPostState,
+    ) -> Result<StateWitness, TrieWitnessError> {
+        if targets.is_empty() {
+            return Ok(self.witness)
+        }
+
+        Ok(self.compute_witness(targets, state, true, false, &B256HashMap::default())?.0)
+    }
+
+    /// Computes the witness for `proof_targets` into a [`StateWitness`], including the nodes
+    /// merged beforehand and the preimages if enabled, together with statistics about the
+    /// computation. See [`Self::compute_inner`].
+    fn compute_witness(
+        mut self,
+        proof_targets: MultiProofTargets,
+        state: &HashedPostState,
+        partial: bool,
+        retain_updates: bool,
+        known_nodes: &B256HashMap<Bytes>,
+    ) -> Result<(StateWitness, Option<RootAndUpdates>, WitnessStats), TrieWitnessError> {
+        let witness = std::mem::take(&mut self.witness);
+        let node_count = witness.len();
+        let mut recorder = WitnessRecorder::new(witness, node_count, self.max_nodes);
+        if self.collect_preimages {
+            if let Some(provider) = &self.preimage_provider {
+                let hashes = proof_targets.iter().flat_map(|(hashed_address, slots)| {
+                    std::iter::once(hashed_address).chain(slots)
+                });
+                for hash in hashes {
+                    if let Some(preimage) = provider.preimage(hash) {
+                        recorder.witness.preimages.insert(*hash, preimage);
+                    }
+                }
+            }
+        }
+        let root_and_updates = self.compute_inner(
+            proof_targets,
+            state,
+            partial,
+            retain_updates,
+            known_nodes,
+            &mut recorder,
+        )?;
+        Ok((recorder.witness, root_and_updates, recorder.stats))
+    }
+
+    /// Computes the witness for `proof_targets`, applying the changes from `state`.
+    ///
+    /// If `partial` is set, target accounts that are absent from `state` keep their current value
+    /// instead of failing with [`TrieWitnessError::MissingAccount`]. If `retain_updates` is set,
+    /// the resulting state root and trie updates are returned.
+    ///
+    /// Blinded nodes are looked up in the hash-keyed `known_nodes` before they are fetched from
+    /// the database. The known nodes have no branch node masks, so they must be empty if
+    /// `retain_updates` is set.
+    fn compute_inner<W: RecordNode>(
+        mut self,
+        proof_targets: MultiProofTargets,
+        state: &HashedPostState,
+        partial: bool,
+        retain_updates: bool,
+        known_nodes: &B256HashMap<Bytes>,
+        recorder: &mut WitnessRecorder<W>,
+    ) -> Result<Option<(B256, TrieUpdates)>, TrieWitnessError> {
+        let accounts = self.get_target_accounts(&proof_targets, state, partial)?;
+        // all nodes of wiped storage tries change, so that the stored branch nodes can't be used
+        for hashed_address in proof_targets.keys() {
+            if state.storages.get(hashed_address).is_some_and(|storage| storage.wiped) {
+                self.prefix_sets.storage_prefix_sets.insert(*hashed_address, PrefixSetMut::all());
+                if state.accounts.get(hashed_address).is_some_and(Option::is_none) {
+                    self.prefix_sets.destroyed_accounts.insert(*hashed_address);
+                }
+            }
+        }
         let multiproof =
             Proof::new(self.trie_cursor_factory.clone(), self.hashed_cursor_factory.clone())
                 .with_prefix_sets_mut(self.prefix_sets.clone())
+                .with_branch_node_masks(retain_updates)
                 .multiproof(proof_targets.clone())?;
 
         // Record all nodes from multiproof in the witness
-        for account_node in multiproof.account_subtree.values() {
-            if let Entry::Vacant(entry) = self.witness.entry(keccak256(account_node.as_ref())) {
-                entry.insert(account_node.clone());
-            }
-        }
-        for storage_node in multiproof.storages.values().flat_map(|s| s.subtree.values()) {
-            if let Entry::Vacant(entry) = self.witness.entry(keccak256(storage_node.as_ref())) {
-                entry.insert(storage_node.clone());
-            }
+        record_proof_nodes(recorder, None, &multiproof.account_subtree)?;
+        for (hashed_address, storage) in &multiproof.storages {
+            record_proof_nodes(recorder, Some(*hashed_address), &storage.subtree)?;
         }
 
+        let parallelism = self.parallelism;
+        let known_nodes = locate_known_nodes(&multiproof, known_nodes)?;
         let (tx, rx) = mpsc::channel();
+        let provider_lookups = Arc::new(AtomicUsize::new(0));
         let proof_provider_factory = ProofBlindedProviderFactory::new(
             self.trie_cursor_factory,
             self.hashed_cursor_factory,
             Arc::new(self.prefix_sets),
         );
-        let mut sparse_trie =
-            SparseStateTrie::new(WitnessBlindedProviderFactory::new(proof_provider_factory, tx));
+        let mut sparse_trie = SparseStateTrie::new(WitnessBlindedProviderFactory::new(
+            proof_provider_factory,
+            Arc::new(known_nodes),
+            provider_lookups.clone(),
+            tx,
+        ))
+        .with_updates(retain_updates);
         sparse_trie.reveal_multiproof(proof_targets.clone(), multiproof)?;
 
+        let proof_targets = proof_targets.into_iter().sorted_unstable_by_key(|(ha, _)| *ha);
+
         // Attempt to update state trie to gather additional information for the witness.
-        for (hashed_address, hashed_slots) in
-            proof_targets.into_iter().sorted_unstable_by_key(|(ha, _)| *ha)
-        {
-            // Update storage trie first.
-            let storage = state.storages.get(&hashed_address);
-            let storage_trie = sparse_trie.storage_trie_mut(&hashed_address).ok_or(
-                SparseStateTrieErrorKind::SparseStorageTrie(
-                    hashed_address,
-                    SparseTrieErrorKind::Blind,
-                ),
-            )?;
-            for hashed_slot in hashed_slots.into_iter().sorted_unstable() {
-                let storage_nibbles = Nibbles::unpack(hashed_slot);
-                let maybe_leaf_value = storage
-                    .and_then(|s| s.storage.get(&hashed_slot))
-                    .filter(|v| !v.is_zero())
-                    .map(|v| alloy_rlp::encode_fixed_size(v).to_vec());
-
-                if let Some(value) = maybe_leaf_value {
-                    storage_trie.update_leaf(storage_nibbles, value).map_err(|err| {
-                        SparseStateTrieErrorKind::SparseStorageTrie(hashed_address, err.into_kind())
-                    })?;
-                } else {
-                    storage_trie.remove_leaf(&storage_nibbles).map_err(|err| {
-                        SparseStateTrieErrorKind::SparseStorageTrie(hashed_address, err.into_kind())
-                    })?;
+        if parallelism > 1 {
+            // Take the storage tries out of the state trie, so that they can be updated
+            // concurrently.
+            let mut storage_tries = proof_targets
+                .map(|(hashed_address, hashed_slots)| {
+                    let storage_trie = sparse_trie.take_storage_trie(&hashed_address);
+                    (hashed_address, hashed_slots, storage_trie)
+                })
+                .collect::<Vec<_>>();
+
+            let chunk_size = storage_tries.len().div_ceil(parallelism).max(1);
+            std::thread::scope(|scope| {
+                let handles = storage_tries
+                    .chunks_mut(chunk_size)
+                    .map(|chunk| {
+                        scope.spawn(|| {
+                            chunk
+                                .iter_mut()
+                                .map(|(hashed_address, hashed_slots, storage_trie)| {
+                                    let storage_trie = storage_trie
+                                        .as_mut()
+                                        .and_then(|trie| trie.as_revealed_mut());
+                                    update_storage_trie(
+                                        storage_trie,
+                                        *hashed_address,
+                                        std::mem::take(hashed_slots),
+                                        state,
+                                    )
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                handles
+                    .into_iter()
+                    .flat_map(|handle| {
+                        handle.join().unwrap_or_else(|err| std::panic::resume_unwind(err))
+                    })
+                    .collect::<Result<(), _>>()
+            })?;
+
+            for (hashed_address, _, storage_trie) in storage_tries {
+                if let Some(storage_trie) = storage_trie {
+                    sparse_trie.insert_storage_trie(hashed_address, storage_trie);
                 }
+                update_account(&mut sparse_trie, hashed_address, accounts.get(&hashed_address))?;
             }
+        } else {
+            for (hashed_address, hashed_slots) in proof_targets {
+                update_storage_trie(
+                    sparse_trie.storage_trie_mut(&hashed_address),
+                    hashed_address,
+                    hashed_slots,
+                    state,
+                )?;
+                update_account(&mut sparse_trie, hashed_address, accounts.get(&hashed_address))?;
 
-            // Calculate storage root after updates.
-            storage_trie.root();
+                while let Ok((hashed_address, path, node)) = rx.try_recv() {
+                    recorder.record_extra(hashed_address, path, &node)?;
+                }
+            }
+        }
 
-            let account = state
-                .accounts
-                .get(&hashed_address)
-                .ok_or(TrieWitnessError::MissingAccount(hashed_address))?
-                .unwrap_or_default();
-            sparse_trie.update_account(hashed_address, account)?;
+        // the new storage of a wiped storage trie consists of the changed slots only, verify that
+        // all previous slots were removed
+        if !partial {
+            for (hashed_address, storage) in &state.storages {
+                if !storage.wiped {
+                    continue
+                }
+                let Some(actual) = sparse_trie.storage_root(*hashed_address) else { continue };
+                let expected = storage_root_unsorted(
+                    storage
+                        .storage
+                        .iter()
+                        .filter(|(_, value)| !value.is_zero())
+                        .map(|(k, v)| (*k, *v)),
+                );
+                if actual != expected {
+                    return Err(TrieWitnessError::RootMismatch {
+                        hashed_address: *hashed_address,
+                        expected,
+                        actual,
+                    })
+                }
+            }
+        }
 
-            while let Ok(node) = rx.try_recv() {
-                self.witness.insert(keccak256(&node), node);
+        let root_and_updates = if retain_updates {
+            let root = sparse_trie
+                .root()
+                .ok_or(SparseStateTrieErrorKind::Sparse(SparseTrieErrorKind::Blind))?;
+            let mut updates = sparse_trie
+                .take_trie_updates()
+                .ok_or(SparseStateTrieErrorKind::Sparse(SparseTrieErrorKind::Blind))?;
+            // the sparse trie only removes the revealed nodes of wiped storage tries, mark them as
+            // deleted so that no stale nodes are left behind
+            for (hashed_address, storage) in &state.storages {
+                if storage.wiped {
+                    updates.storage_tries.entry(*hashed_address).or_default().set_deleted(true);
+                }
             }
+            Some((root, updates))
+        } else {
+            None
+        };
+
+        while let Ok((hashed_address, path, node)) = rx.try_recv() {
+            recorder.record_extra(hashed_address, path, &node)?;
         }
+        recorder.stats.provider_lookups += provider_lookups.load(Ordering::Relaxed);
 
-        Ok(self.witness)
+        Ok(root_and_updates)
+    }
+
+    /// Resolve the accounts to apply to the account trie for all `proof_targets`.
+    ///
+    /// Accounts are taken from `state`. If `partial` is set, target accounts absent from `state`
+    /// are looked up in the current state instead, and skipped if they don't exist.
+    fn get_target_accounts(
+        &self,
+        proof_targets: &MultiProofTargets,
+        state: &HashedPostState,
+        partial: bool,
+    ) -> Result<B256HashMap<Account>, TrieWitnessError> {
+        let mut accounts = B256HashMap::default();
+        let mut account_cursor = partial
+            .then(|| self.hashed_cursor_factory.hashed_account_cursor())
+            .transpose()
+            .map_err(StateProofError::from)?;
+        for hashed_address in proof_targets.keys() {
+            let account = match (state.accounts.get(hashed_address), &mut account_cursor) {
+                (Some(account), _) => account.unwrap_or_default(),
+                (None, Some(cursor)) => {
+                    match cursor.seek(*hashed_address).map_err(StateProofError::from)? {
+                        Some((key, account)) if key == *hashed_address => account,
+                        // the account doesn't exist, but its storage may still be changed
+                        _ if state.storages.contains_key(hashed_address) => Account::default(),
+                        _ => continue,
+                    }
+                }
+                (None, None) => return Err(TrieWitnessError::MissingAccount(*hashed_address)),
+            };
+            accounts.insert(*hashed_address, account);
+        }
+        Ok(accounts)
     }
 
     /// Retrieve proof targets for incoming hashed state.
@@ -198,17 +1318,261 @@ where
     }
 }
 
+/// Records the proof nodes of the account trie, or of the storage trie of `hashed_address`.
+///
+/// Fails with [`TrieWitnessError::InvalidProofNode`] if a node can't be decoded, so that corrupt
+/// nodes are told apart from failures of the proof computation.
+fn record_proof_nodes<W: RecordNode>(
+    recorder: &mut WitnessRecorder<W>,
+    hashed_address: Option<B256>,
+    nodes: &ProofNodes,
+) -> Result<(), TrieWitnessError> {
+    for (path, node) in nodes.iter() {
+        decode_witness_node(node)
+            .map_err(|source| TrieWitnessError::InvalidProofNode { path: path.clone(), source })?;
+        if recorder.record(hashed_address, path.clone(), node)? {
+            recorder.stats.multiproof_nodes += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Applies the changes to `hashed_slots` of the account from `state` to its storage trie and
+/// calculates the storage root.
+///
+/// Slots that are neither changed by `state` nor part of a wiped storage are left untouched.
+fn update_storage_trie<P: BlindedProvider>(
+    storage_trie: Option<&mut RevealedSparseTrie<P>>,
+    hashed_address: B256,
+    hashed_slots: B256HashSet,
+    state: &HashedPostState,
+) -> Result<(), TrieWitnessError> {
+    let storage = state.storages.get(&hashed_address);
+    let storage_trie = storage_trie.ok_or(SparseStateTrieErrorKind::SparseStorageTrie(
+        hashed_address,
+        SparseTrieErrorKind::Blind,
+    ))?;
+    for hashed_slot in hashed_slots.into_iter().sorted_unstable() {
+        let Some(storage) = storage.filter(|s| s.wiped || s.storage.contains_key(&hashed_slot))
+        else {
+            continue
+        };
+        let storage_nibbles = Nibbles::unpack(hashed_slot);
+        let maybe_leaf_value = storage
+            .storage
+            .get(&hashed_slot)
+            .filter(|v| !v.is_zero())
+            .map(|v| alloy_rlp::encode_fixed_size(v).to_vec());
+
+        if let Some(value) = maybe_leaf_value {
+            storage_trie.update_leaf(storage_nibbles, value).map_err(|err| {
+                SparseStateTrieErrorKind::SparseStorageTrie(hashed_address, err.into_kind())
+            })?;
+        } else {
+            storage_trie.remove_leaf(&storage_nibbles).map_err(|err| {
+                SparseStateTrieErrorKind::SparseStorageTrie(hashed_address, err.into_kind())
+            })?;
+        }
+    }
+
+    // Calculate storage root after updates.
+    storage_trie.root();
+
+    Ok(())
+}
+
+/// Applies the resolved `account` to the account trie, skipping accounts that don't exist.
+fn update_account<F: BlindedProviderFactory>(
+    sparse_trie: &mut SparseStateTrie<F>,
+    hashed_address: B256,
+    account: Option<&Account>,
+) -> Result<(), TrieWitnessError> {
+    if let Some(account) = account {
+        sparse_trie.update_account(hashed_address, *account)?;
+    }
+    Ok(())
+}
+
+/// Locates the hash-keyed `known_nodes` in the tries of `multiproof` by following the child
+/// hashes of the proof nodes, so that they can be revealed without fetching them.
+///
+/// Returns the located nodes keyed by the hashed address for storage trie nodes and their path.
+fn locate_known_nodes(
+    multiproof: &MultiProof,
+    known_nodes: &B256HashMap<Bytes>,
+) -> Result<KnownNodes, TrieWitnessError> {
+    let mut located = KnownNodes::default();
+    if known_nodes.is_empty() {
+        return Ok(located)
+    }
+
+    let subtries = std::iter::once((None, &multiproof.account_subtree)).chain(
+        multiproof
+            .storages
+            .iter()
+            .map(|(hashed_address, storage)| (Some(*hashed_address), &storage.subtree)),
+    );
+    for (hashed_address, subtree) in subtries {
+        let mut stack =
+            subtree.iter().map(|(path, node)| (path.clone(), node.clone())).collect::<Vec<_>>();
+        while let Some((path, node)) = stack.pop() {
+            let mut children = Vec::new();
+            match decode_witness_node(&node)? {
+                TrieNode::Branch(branch) => {
+                    for (child_path, child) in branch_node_children(&path, &branch) {
+                        if let Some(hash) = child.as_hash() {
+                            children.push((child_path, hash));
+                        }
+                    }
+                }
+                TrieNode::Extension(extension) => {
+                    if let Some(hash) = extension.child.as_hash() {
+                        let mut child_path = path;
+                        child_path.extend_from_slice(&extension.key);
+                        children.push((child_path, hash));
+                    }
+                }
+                TrieNode::Leaf(_) | TrieNode::EmptyRoot => {}
+            }
+
+            for (child_path, hash) in children {
+                if subtree.contains_key(&child_path) {
+                    continue
+                }
+                let Some(child) = known_nodes.get(&hash) else { continue };
+                if located.insert((hashed_address, child_path.clone()), child.clone()).is_none() {
+                    stack.push((child_path, child.clone()));
+                }
+            }
+        }
+    }
+    Ok(located)
+}
+
+/// Walks the trie from `root` over the hash-keyed `nodes` and returns `true` if all nodes are
+/// reachable. Blinded nodes, i.e. hashes without a node, are skipped.
+///
+/// If `storage_roots` is set, the trie is an account trie and the storage roots of the revealed
+/// accounts are collected, keyed by hashed address.
+fn verify_trie(
+    nodes: &B256HashMap<Bytes>,
+    root: B256,
+    mut storage_roots: Option<&mut B256HashMap<B256>>,
+) -> Result<bool, StateProofError> {
+    if nodes.iter().any(|(hash, node)| *hash != keccak256(node)) {
+        return Ok(false)
+    }
+    if !nodes.contains_key(&root) {
+        return Ok(root == EMPTY_ROOT_HASH && nodes.is_empty())
+    }
+
+    let mut valid = true;
+    let reached = walk_trie::<StateProofError>(nodes, root, |path, value| {
+        if let Some(storage_roots) = storage_roots.as_deref_mut() {
+            let Some(hashed_address) = leaf_key(&path) else {
+                valid = false;
+                return Ok(())
+            };
+            let account = TrieAccount::decode(&mut &value[..])?;
+            storage_roots.insert(hashed_address, account.storage_root);
+        }
+        Ok(())
+    })?;
+
+    Ok(valid && nodes.keys().all(|hash| reached.contains(hash)))
+}
+
+/// Walks the trie from `root` over the hash-keyed `nodes`, calling `on_leaf` with the full path
+/// and the value of every reachable leaf. Blinded nodes, i.e. hashes without a node, are skipped.
+///
+/// Returns the hashes of the reached nodes, including the hashes of embedded nodes.
+fn walk_trie<E: From<alloy_rlp::Error>>(
+    nodes: &B256HashMap<Bytes>,
+    root: B256,
+    mut on_leaf: impl FnMut(Nibbles, &[u8]) -> Result<(), E>,
+) -> Result<B256HashSet, E> {
+    let mut reached = B256HashSet::default();
+    let mut stack = vec![(Nibbles::default(), RlpNode::word_rlp(&root))];
+    while let Some((path, child)) = stack.pop() {
+        let node = match child.as_hash() {
+            Some(hash) => {
+                let Some(node) = nodes.get(&hash) else { continue };
+                reached.insert(hash);
+                decode_witness_node(node)?
+            }
+            None => {
+                // embedded nodes may be recorded on their own as well
+                reached.insert(keccak256(child.as_slice()));
+                TrieNode::decode(&mut child.as_slice())?
+            }
+        };
+
+        match node {
+            TrieNode::Branch(branch) => stack.extend(branch_node_children(&path, &branch)),
+            TrieNode::Extension(extension) => {
+                let mut child_path = path;
+                child_path.extend_from_slice(&extension.key);
+                stack.push((child_path, extension.child));
+            }
+            TrieNode::Leaf(leaf) => {
+                let mut full_path = path;
+                full_path.extend_from_slice(&leaf.key);
+                on_leaf(full_path, &leaf.value)?;
+            }
+            TrieNode::EmptyRoot => {}
+        }
+    }
+
+    Ok(reached)
+}
+
+/// Returns the paths and references of the children of the `branch` node at `path`, in nibble
+/// order.
+pub fn branch_node_children(path: &Nibbles, branch: &BranchNode) -> Vec<(Nibbles, RlpNode)> {
+    branch
+        .as_ref()
+        .children()
+        .filter_map(|(nibble, child)| {
+            let mut child_path = path.clone();
+            child_path.push(nibble);
+            Some((child_path, child?.clone()))
+        })
+        .collect()
+}
+
+/// Returns the hashed key of the leaf at the full `path`, or `None` if the path isn't 32 bytes.
+fn leaf_key(path: &Nibbles) -> Option<B256> {
+    (path.len() == 64).then(|| B256::from_slice(&path.pack()))
+}
+
+/// A node fetched by a [`WitnessBlindedProvider`]: the hashed address for storage trie nodes, the
+/// path and the node.
+type WitnessNode = (Option<B256>, Nibbles, Bytes);
+
+/// Nodes that are revealed without fetching them, keyed by the hashed address for storage trie
+/// nodes and their path.
+type KnownNodes = HashMap<(Option<B256>, Nibbles), Bytes>;
+
 #[derive(Debug)]
 struct WitnessBlindedProviderFactory<F> {
     /// Blinded node provider factory.
     provider_factory: F,
+    /// Nodes that are returned instead of fetching them from the provider.
+    known_nodes: Arc<KnownNodes>,
+    /// Number of lookups from the provider, see [`WitnessStats::provider_lookups`].
+    provider_lookups: Arc<AtomicUsize>,
     /// Sender for forwarding fetched blinded node.
-    tx: mpsc::Sender<Bytes>,
+    tx: mpsc::Sender<WitnessNode>,
 }
 
 impl<F> WitnessBlindedProviderFactory<F> {
-    const fn new(provider_factory: F, tx: mpsc::Sender<Bytes>) -> Self {
-        Self { provider_factory, tx }
+    const fn new(
+        provider_factory: F,
+        known_nodes: Arc<KnownNodes>,
+        provider_lookups: Arc<AtomicUsize>,
+        tx: mpsc::Sender<WitnessNode>,
+    ) -> Self {
+        Self { provider_factory, known_nodes, provider_lookups, tx }
     }
 }
 
@@ -223,12 +1587,24 @@ where
 
     fn account_node_provider(&self) -> Self::AccountNodeProvider {
         let provider = self.provider_factory.account_node_provider();
-        WitnessBlindedProvider::new(provider, self.tx.clone())
+        WitnessBlindedProvider::new(
+            provider,
+            None,
+            self.known_nodes.clone(),
+            self.provider_lookups.clone(),
+            self.tx.clone(),
+        )
     }
 
     fn storage_node_provider(&self, account: B256) -> Self::StorageNodeProvider {
         let provider = self.provider_factory.storage_node_provider(account);
-        WitnessBlindedProvider::new(provider, self.tx.clone())
+        WitnessBlindedProvider::new(
+            provider,
+            Some(account),
+            self.known_nodes.clone(),
+            self.provider_lookups.clone(),
+            self.tx.clone(),
+        )
     }
 }
 
@@ -236,24 +1612,397 @@ where
 struct WitnessBlindedProvider<P> {
     /// Proof-based blinded.
     provider: P,
+    /// The hashed address of the account for storage trie providers, `None` for the account trie.
+    hashed_address: Option<B256>,
+    /// Nodes that are returned instead of fetching them from the provider.
+    known_nodes: Arc<KnownNodes>,
+    /// Number of lookups from the provider, see [`WitnessStats::provider_lookups`].
+    provider_lookups: Arc<AtomicUsize>,
     /// Sender for forwarding fetched blinded node.
-    tx: mpsc::Sender<Bytes>,
+    tx: mpsc::Sender<WitnessNode>,
 }
 
 impl<P> WitnessBlindedProvider<P> {
-    const fn new(provider: P, tx: mpsc::Sender<Bytes>) -> Self {
-        Self { provider, tx }
+    const fn new(
+        provider: P,
+        hashed_address: Option<B256>,
+        known_nodes: Arc<KnownNodes>,
+        provider_lookups: Arc<AtomicUsize>,
+        tx: mpsc::Sender<WitnessNode>,
+    ) -> Self {
+        Self { provider, hashed_address, known_nodes, provider_lookups, tx }
     }
 }
 
 impl<P: BlindedProvider> BlindedProvider for WitnessBlindedProvider<P> {
     fn blinded_node(&mut self, path: &Nibbles) -> Result<Option<RevealedNode>, SparseTrieError> {
-        let maybe_node = self.provider.blinded_node(path)?;
+        let maybe_node = match self.known_nodes.get(&(self.hashed_address, path.clone())) {
+            Some(node) => {
+                Some(RevealedNode { node: node.clone(), tree_mask: None, hash_mask: None })
+            }
+            None => {
+                self.provider_lookups.fetch_add(1, Ordering::Relaxed);
+                self.provider.blinded_node(path)?
+            }
+        };
         if let Some(node) = &maybe_node {
             self.tx
-                .send(node.node.clone())
+                .send((self.hashed_address, path.clone(), node.node.clone()))
                 .map_err(|error| SparseTrieErrorKind::Other(Box::new(error)))?;
         }
         Ok(maybe_node)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorder_skips_recorded_paths() {
+        let node = Bytes::from_static(&[0xc2, 0x80, 0x80]);
+        let other = Bytes::from_static(&[0xc2, 0x01, 0x80]);
+        let hashed_address = B256::random();
+
+        let mut recorder = WitnessRecorder::new(StateWitness::default(), 0, None);
+        // the same node is recorded from the multiproof and the blinded provider
+        recorder.record(None, Nibbles::default(), &node).unwrap();
+        recorder.record(None, Nibbles::default(), &node).unwrap();
+        // the same node at different paths is only stored once
+        recorder.record(None, Nibbles::from_nibbles([1]), &node).unwrap();
+        recorder.record(None, Nibbles::from_nibbles([2]), &other).unwrap();
+        recorder.record(Some(hashed_address), Nibbles::default(), &node).unwrap();
+        recorder.record(Some(hashed_address), Nibbles::default(), &node).unwrap();
+
+        let witness = recorder.witness;
+        assert_eq!(witness.account_nodes.len(), 2);
+        assert_eq!(witness.storage_nodes[&hashed_address].len(), 1);
+
+        // nodes shared between tries are merged
+        let witness = witness.into_witness();
+        assert_eq!(witness.len(), 2);
+        let mut expected = vec![node, other];
+        expected.sort_by_key(|node| keccak256(node));
+        assert_eq!(witness.into_sorted_vec(), expected);
+    }
+
+    #[test]
+    fn rejects_invalid_proof_nodes() {
+        let empty_root = Bytes::from_static(&[EMPTY_STRING_CODE]);
+        let garbage = Bytes::from_static(&[0xc1]);
+        let path = Nibbles::from_nibbles([0x1]);
+
+        let mut recorder = WitnessRecorder::new(StateWitness::default(), 0, None);
+        let nodes = ProofNodes::from_iter([(Nibbles::default(), empty_root.clone())]);
+        record_proof_nodes(&mut recorder, None, &nodes).unwrap();
+
+        let nodes =
+            ProofNodes::from_iter([(Nibbles::default(), empty_root), (path.clone(), garbage)]);
+        assert!(matches!(
+            record_proof_nodes(&mut recorder, None, &nodes),
+            Err(TrieWitnessError::InvalidProofNode { path: invalid, .. }) if invalid == path
+        ));
+        assert_eq!(recorder.witness.len(), 1);
+    }
+
+    #[test]
+    fn decode_branch_node_children() {
+        let children = [RlpNode::word_rlp(&B256::random()), RlpNode::word_rlp(&B256::random())];
+        let branch = BranchNode::new(
+            children.to_vec(),
+            reth_trie_common::TrieMask::new(0b0100_0000_0000_0010),
+        );
+        let encoded = Bytes::from(alloy_rlp::encode(TrieNode::Branch(branch)));
+
+        let Ok(TrieNode::Branch(branch)) = decode_witness_node(&encoded) else {
+            panic!("expected a branch node")
+        };
+        let path = Nibbles::from_nibbles([0x7]);
+        assert_eq!(
+            branch_node_children(&path, &branch),
+            vec![
+                (Nibbles::from_nibbles([0x7, 0x1]), children[0].clone()),
+                (Nibbles::from_nibbles([0x7, 0xe]), children[1].clone()),
+            ]
+        );
+
+        assert!(decode_witness_node(&Bytes::from_static(&[0xc1])).is_err());
+    }
+
+    #[test]
+    fn recorder_limits_nodes() {
+        let nodes = [[0xc2, 0x80, 0x80], [0xc2, 0x01, 0x80], [0xc2, 0x02, 0x80]].map(Bytes::from);
+
+        let mut recorder = WitnessRecorder::new(StateWitness::default(), 1, Some(3));
+        recorder.record(None, Nibbles::default(), &nodes[0]).unwrap();
+        // nodes that are already recorded don't count
+        recorder.record(None, Nibbles::from_nibbles([1]), &nodes[0]).unwrap();
+        recorder.record(None, Nibbles::from_nibbles([2]), &nodes[1]).unwrap();
+        assert_eq!(recorder.node_count, 3);
+        assert!(matches!(
+            recorder.record(None, Nibbles::from_nibbles([3]), &nodes[2]),
+            Err(TrieWitnessError::WitnessTooLarge(3))
+        ));
+    }
+
+    #[test]
+    fn extend_witness() {
+        let node = Bytes::from_static(&[0xc2, 0x80, 0x80]);
+        let other = Bytes::from_static(&[0xc2, 0x01, 0x80]);
+        let hashed_address = B256::random();
+
+        let mut witness = StateWitness::default();
+        witness.insert(None, node.clone());
+        witness.insert(Some(hashed_address), node.clone());
+
+        let mut other_witness = StateWitness::default();
+        other_witness.insert(None, node.clone());
+        other_witness.insert(None, other.clone());
+        other_witness.insert(Some(B256::ZERO), other.clone());
+        other_witness.preimages.insert(hashed_address, Bytes::from_static(&[1]));
+
+        witness.extend(other_witness);
+        assert_eq!(
+            witness.account_nodes,
+            B256HashMap::from_iter([(keccak256(&node), node), (keccak256(&other), other)])
+        );
+        assert_eq!(witness.storage_nodes[&hashed_address].len(), 1);
+        assert_eq!(witness.storage_nodes[&B256::ZERO].len(), 1);
+        assert_eq!(witness.preimages.len(), 1);
+    }
+
+    #[test]
+    fn extend_witness_with_proof() {
+        let node = Bytes::from_static(&[0xc2, 0x80, 0x80]);
+        let other = Bytes::from_static(&[0xc2, 0x01, 0x80]);
+        let address = Address::random();
+        let proof = AccountProof {
+            address,
+            proof: vec![node.clone(), other],
+            storage_proofs: vec![reth_trie_common::StorageProof {
+                proof: vec![node.clone()],
+                ..Default::default()
+            }],
+            ..AccountProof::new(address)
+        };
+
+        let witness = TrieWitness::new((), ()).extend_with_proof(&proof).witness;
+        assert_eq!(witness.account_nodes.len(), 2);
+        assert_eq!(
+            witness.storage_nodes[&keccak256(address)],
+            B256HashMap::from_iter([(keccak256(&node), node)])
+        );
+    }
+
+    #[test]
+    fn verify_state_witness() {
+        let hashed_address = B256::random();
+        let account = TrieAccount { nonce: 1, ..Default::default() };
+        let leaf = TrieNode::Leaf(reth_trie_common::LeafNode::new(
+            Nibbles::unpack(hashed_address),
+            alloy_rlp::encode(account),
+        ));
+        let leaf = Bytes::from(alloy_rlp::encode(leaf));
+        let root = keccak256(&leaf);
+        let empty_root = Bytes::from_static(&[EMPTY_STRING_CODE]);
+
+        let mut witness = StateWitness::default();
+        witness.insert(None, leaf);
+        witness.insert(Some(hashed_address), empty_root.clone());
+        assert!(witness.verify(root).unwrap());
+        assert!(!witness.verify(B256::random()).unwrap());
+        assert!(StateWitness::default().verify(EMPTY_ROOT_HASH).unwrap());
+
+        // storage nodes of an account that is not revealed
+        let mut unknown_storage = witness.clone();
+        unknown_storage.insert(Some(B256::random()), empty_root);
+        assert!(!unknown_storage.verify(root).unwrap());
+
+        // node that is not reachable from the root
+        let mut unreachable = witness.clone();
+        unreachable.insert(None, Bytes::from_static(&[0xc2, 0x80, 0x80]));
+        assert!(!unreachable.verify(root).unwrap());
+
+        // node that is not keyed by its hash
+        let mut wrong_key = witness;
+        wrong_key.account_nodes.insert(B256::random(), Bytes::from_static(&[0xc2, 0x80, 0x80]));
+        assert!(!wrong_key.verify(root).unwrap());
+
+        // root node that can't be decoded
+        let mut invalid = StateWitness::default();
+        let node = Bytes::from_static(&[0xc1, 0x80]);
+        invalid.insert(None, node.clone());
+        assert!(invalid.verify(keccak256(node)).is_err());
+    }
+
+    #[test]
+    fn verify_flat_witness() {
+        let (hashed_address, hashed_slot) = (B256::random(), B256::random());
+        let storage_leaf = TrieNode::Leaf(reth_trie_common::LeafNode::new(
+            Nibbles::unpack(hashed_slot),
+            alloy_rlp::encode_fixed_size(&U256::from(2)).to_vec(),
+        ));
+        let storage_leaf = Bytes::from(alloy_rlp::encode(storage_leaf));
+        let account =
+            TrieAccount { nonce: 1, storage_root: keccak256(&storage_leaf), ..Default::default() };
+        let leaf = TrieNode::Leaf(reth_trie_common::LeafNode::new(
+            Nibbles::unpack(hashed_address),
+            alloy_rlp::encode(account),
+        ));
+        let leaf = Bytes::from(alloy_rlp::encode(leaf));
+        let root = keccak256(&leaf);
+        let extra = Bytes::from_static(&[0xc2, 0x80, 0x80]);
+
+        let mut witness = B256HashMap::default();
+        for node in [&leaf, &storage_leaf, &extra] {
+            witness.insert(keccak256(node), node.clone());
+        }
+        assert_eq!(
+            verify_witness(root, &witness).unwrap(),
+            VerifiedWitness {
+                accounts: B256HashMap::from_iter([(hashed_address, account)]),
+                storages: B256HashMap::from_iter([(
+                    hashed_address,
+                    B256HashMap::from_iter([(hashed_slot, U256::from(2))]),
+                )]),
+                unreachable: B256HashSet::from_iter([keccak256(&extra)]),
+            }
+        );
+
+        // without the root node, no node is reachable
+        let verified = verify_witness(B256::random(), &witness).unwrap();
+        assert!(verified.accounts.is_empty() && verified.storages.is_empty());
+        assert_eq!(verified.unreachable.len(), 3);
+
+        // node that is not keyed by its hash
+        witness.insert(account.storage_root, extra.clone());
+        assert!(matches!(
+            verify_witness(root, &witness),
+            Err(TrieWitnessError::NodeHashMismatch { key, hash })
+                if key == account.storage_root && hash == keccak256(&extra)
+        ));
+    }
+
+    #[test]
+    fn statistics() {
+        let hashed_address = B256::random();
+        let leaf = TrieNode::Leaf(reth_trie_common::LeafNode::new(
+            Nibbles::unpack(hashed_address),
+            alloy_rlp::encode(TrieAccount::default()),
+        ));
+        let leaf = Bytes::from(alloy_rlp::encode(leaf));
+        let branch =
+            Bytes::from(alloy_rlp::encode(TrieNode::Branch(reth_trie_common::BranchNode::new(
+                vec![RlpNode::word_rlp(&B256::random()), RlpNode::word_rlp(&B256::random())],
+                reth_trie_common::TrieMask::new(0b11),
+            ))));
+        let extension = Bytes::from(alloy_rlp::encode(TrieNode::Extension(
+            reth_trie_common::ExtensionNode::new(
+                Nibbles::from_nibbles([1, 2]),
+                RlpNode::word_rlp(&keccak256(&branch)),
+            ),
+        )));
+        let empty_root = Bytes::from_static(&[EMPTY_STRING_CODE]);
+
+        let mut witness = StateWitness::default();
+        witness.insert(None, extension.clone());
+        witness.insert(None, branch.clone());
+        witness.insert(None, leaf.clone());
+        witness.insert(Some(hashed_address), empty_root.clone());
+        witness.insert(Some(hashed_address), leaf.clone());
+
+        let expected = WitnessStatistics {
+            node_count: 5,
+            total_bytes: extension.len() + branch.len() + 2 * leaf.len() + empty_root.len(),
+            branch_nodes: 1,
+            extension_nodes: 1,
+            leaf_nodes: 2,
+        };
+        assert_eq!(witness.statistics(), expected);
+
+        // nodes shared between tries are only counted once
+        let flat = witness.into_flat_map();
+        assert_eq!(
+            witness_statistics(&flat),
+            WitnessStatistics {
+                node_count: 4,
+                total_bytes: expected.total_bytes - leaf.len(),
+                leaf_nodes: 1,
+                ..expected
+            }
+        );
+        assert_eq!(witness_statistics(&B256HashMap::default()), WitnessStatistics::default());
+    }
+
+    #[test]
+    fn witness_encoding() {
+        let nodes = [
+            Bytes::from_static(&[0xc2, 0x80, 0x80]),
+            Bytes::from_static(&[EMPTY_STRING_CODE]),
+            Bytes::from(vec![0xab; 64]),
+        ];
+        let witness: B256HashMap<_> =
+            nodes.iter().map(|node| (keccak256(node), node.clone())).collect();
+
+        let encoded = encode_witness(&witness);
+        assert_eq!(decode_witness(&encoded).unwrap(), witness);
+        let sorted = Witness(witness.clone().into_iter().collect());
+        assert_eq!(sorted.length(), encoded.len());
+        assert_eq!(alloy_rlp::encode(&sorted), encoded);
+
+        // the encoding doesn't depend on the iteration order of the map
+        let reversed: B256HashMap<_> =
+            nodes.iter().rev().map(|node| (keccak256(node), node.clone())).collect();
+        assert_eq!(encode_witness(&reversed), encoded);
+
+        assert_eq!(
+            decode_witness(&encode_witness(&B256HashMap::default())).unwrap(),
+            B256HashMap::default()
+        );
+
+        // trailing bytes
+        let mut trailing = encoded.to_vec();
+        trailing.push(0x80);
+        assert_eq!(decode_witness(&trailing), Err(alloy_rlp::Error::UnexpectedLength));
+
+        // entries that are not sorted by hash
+        let mut entries = witness.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(hash, _)| std::cmp::Reverse(*hash));
+        let mut unsorted = Vec::new();
+        encode_witness_entries(entries.into_iter(), &mut unsorted);
+        assert!(matches!(decode_witness(&unsorted), Err(alloy_rlp::Error::Custom(_))));
+    }
+
+    #[test]
+    fn file_witness_sink() {
+        let node = Bytes::from_static(&[0xc2, 0x80, 0x80]);
+        let other = Bytes::from(vec![0xab; 64]);
+        let expected = B256HashMap::from_iter([
+            (keccak256(&node), node.clone()),
+            (keccak256(&other), other.clone()),
+        ]);
+        let dir = tempfile::tempdir().unwrap();
+
+        for deduplicate in [false, true] {
+            let path = dir.path().join("witness");
+            let mut sink = FileWitnessSink::create(&path).unwrap().with_deduplication(deduplicate);
+            for node in [&node, &other, &node] {
+                sink.record(keccak256(node), node.clone()).unwrap();
+            }
+            sink.finish().unwrap();
+
+            // the duplicate node is only written without deduplication
+            let written =
+                if deduplicate { vec![&node, &other] } else { vec![&node, &other, &node] };
+            let len = written.iter().map(|node| 32 + 4 + node.len()).sum::<usize>();
+            assert_eq!(std::fs::metadata(&path).unwrap().len(), len as u64);
+            assert_eq!(read_witness_file(&path).unwrap(), expected);
+
+            // truncated entry
+            let mut data = std::fs::read(&path).unwrap();
+            data.pop();
+            std::fs::write(&path, data).unwrap();
+            assert!(matches!(read_witness_file(&path), Err(TrieWitnessError::Sink(_))));
+        }
+    }
+}